@@ -2,12 +2,14 @@
 
 use sea_orm::entity::prelude::*;
 
+use crate::discord_id::{kind, DiscordId};
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
 #[sea_orm(table_name = "archive_rule")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
-    pub from_channel: i64,
-    pub to_channel: i64,
+    pub from_channel: DiscordId<kind::Channel>,
+    pub to_channel: DiscordId<kind::Channel>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -2,6 +2,8 @@
 
 use sea_orm::entity::prelude::*;
 
+use crate::discord_id::{kind, DiscordId};
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
 #[sea_orm(table_name = "delivery")]
 pub struct Model {
@@ -10,7 +12,8 @@ pub struct Model {
     pub created_by: Uuid,
     pub created_at: TimeDateTimeWithTimeZone,
     #[sea_orm(unique)]
-    pub discord_message_id: Option<i64>,
+    pub discord_message_id: Option<DiscordId<kind::Message>>,
+    pub updated_at: TimeDateTimeWithTimeZone,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
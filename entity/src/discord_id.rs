@@ -0,0 +1,180 @@
+//! Discord IDs, as stored in the database
+//!
+//! Discord IDs are unsigned 64-bit snowflakes, but Postgres has no unsigned integers, so they are
+//! stored in `BIGINT` columns. Snowflakes won't use the top bit until the 22nd century, so they fit
+//! unchanged, and IDs that don't are refused both ways rather than wrapped around. Each ID is
+//! tagged with what it identifies, so that a channel ID can't end up where a message ID belongs.
+
+use std::{
+    cmp::Ordering,
+    fmt::{self, Debug, Display},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+use sea_orm::{
+    sea_query::{ArrayType, ColumnType, Nullable, ValueType, ValueTypeErr},
+    ColIdx, DbErr, QueryResult, TryFromU64, TryGetError, TryGetable, Value,
+};
+
+/// What a [`DiscordId`] identifies
+pub mod kind {
+    pub trait Kind: 'static {
+        const NAME: &'static str;
+    }
+
+    macro_rules! kinds {
+        ($($kind:ident => $name:literal,)*) => {$(
+            pub enum $kind {}
+            impl Kind for $kind {
+                const NAME: &'static str = $name;
+            }
+        )*};
+    }
+
+    kinds! {
+        Application => "application",
+        Channel => "channel",
+        Emoji => "emoji",
+        Guild => "guild",
+        Message => "message",
+        Role => "role",
+        User => "user",
+    }
+}
+
+use kind::Kind;
+
+/// The ID of a Discord object of kind `K`
+pub struct DiscordId<K> {
+    id: u64,
+    kind: PhantomData<fn() -> K>,
+}
+
+impl<K: Kind> DiscordId<K> {
+    pub const fn new(id: u64) -> Self {
+        Self {
+            id,
+            kind: PhantomData,
+        }
+    }
+
+    pub const fn get(self) -> u64 {
+        self.id
+    }
+
+    fn from_stored(stored: i64) -> Option<Self> {
+        <u64 as TryFrom<i64>>::try_from(stored).ok().map(Self::new)
+    }
+
+    fn to_stored(self) -> Option<i64> {
+        <i64 as TryFrom<u64>>::try_from(self.id).ok()
+    }
+}
+
+// Derives would require `K` itself to implement these traits
+
+impl<K> Clone for DiscordId<K> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K> Copy for DiscordId<K> {}
+
+impl<K> PartialEq for DiscordId<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<K> Eq for DiscordId<K> {}
+
+impl<K> PartialOrd for DiscordId<K> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K> Ord for DiscordId<K> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.id.cmp(&other.id)
+    }
+}
+
+impl<K> Hash for DiscordId<K> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<K: Kind> Debug for DiscordId<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", K::NAME, self.id)
+    }
+}
+
+impl<K> Display for DiscordId<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.id, f)
+    }
+}
+
+impl<K: Kind> From<DiscordId<K>> for Value {
+    /// Panics if `id` doesn't fit into a `BIGINT`, rather than storing it as a different ID
+    fn from(id: DiscordId<K>) -> Self {
+        let stored = id
+            .to_stored()
+            .unwrap_or_else(|| panic!("{id:?} is too large to be stored"));
+        Value::BigInt(Some(stored))
+    }
+}
+
+impl<K> Nullable for DiscordId<K> {
+    fn null() -> Value {
+        Value::BigInt(None)
+    }
+}
+
+impl<K: Kind> TryGetable for DiscordId<K> {
+    fn try_get_by<I: ColIdx>(res: &QueryResult, idx: I) -> Result<Self, TryGetError> {
+        let stored = i64::try_get_by(res, idx)?;
+        Self::from_stored(stored).ok_or_else(|| {
+            TryGetError::DbErr(DbErr::Type(format!(
+                "{stored} is not a valid Discord {} ID",
+                K::NAME
+            )))
+        })
+    }
+}
+
+impl<K: Kind> ValueType for DiscordId<K> {
+    fn try_from(value: Value) -> Result<Self, ValueTypeErr> {
+        match value {
+            Value::BigInt(Some(stored)) => Self::from_stored(stored).ok_or(ValueTypeErr),
+            _ => Err(ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        format!("DiscordId<{}>", K::NAME)
+    }
+
+    fn array_type() -> ArrayType {
+        ArrayType::BigInt
+    }
+
+    fn column_type() -> ColumnType {
+        ColumnType::BigInteger
+    }
+}
+
+impl<K: Kind> TryFromU64 for DiscordId<K> {
+    fn try_from_u64(id: u64) -> Result<Self, DbErr> {
+        let id = Self::new(id);
+        match id.to_stored() {
+            Some(_) => Ok(id),
+            None => Err(DbErr::Type(format!("{id:?} is too large to be stored"))),
+        }
+    }
+}
@@ -2,9 +2,43 @@
 
 pub mod prelude;
 
+pub mod application_emoji;
+pub mod approval_channel;
 pub mod archive_rule;
+pub mod badge;
+pub mod badge_role;
+pub mod bump_channel;
+pub mod claim_link;
 pub mod delivery;
 pub mod delivery_item;
+pub mod discord_id;
+pub mod feature_flag;
+pub mod guild_ban;
+pub mod guild_setting;
+pub mod item_emoji;
+pub mod metrics_export;
+pub mod mirror_rule;
+pub mod notification;
+pub mod outbox_message;
+pub mod pending_request;
+pub mod pin_channel;
+pub mod ping_role;
+pub mod preset;
+pub mod quip;
 pub mod request;
+pub mod request_attachment;
+pub mod request_channel;
+pub mod request_extension;
+pub mod request_message;
+pub mod request_mirror;
+pub mod request_note;
+pub mod request_render_history;
+pub mod request_report;
+pub mod season;
+pub mod spam_event;
+pub mod stockpile_item;
 pub mod task;
+pub mod task_override;
 pub mod user;
+pub mod web_session;
+pub mod web_session_guild;
@@ -1,8 +1,41 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.6
 
+pub use super::application_emoji::Entity as ApplicationEmoji;
+pub use super::approval_channel::Entity as ApprovalChannel;
 pub use super::archive_rule::Entity as ArchiveRule;
+pub use super::badge::Entity as Badge;
+pub use super::badge_role::Entity as BadgeRole;
+pub use super::bump_channel::Entity as BumpChannel;
+pub use super::claim_link::Entity as ClaimLink;
 pub use super::delivery::Entity as Delivery;
 pub use super::delivery_item::Entity as DeliveryItem;
+pub use super::feature_flag::Entity as FeatureFlag;
+pub use super::guild_ban::Entity as GuildBan;
+pub use super::guild_setting::Entity as GuildSetting;
+pub use super::item_emoji::Entity as ItemEmoji;
+pub use super::metrics_export::Entity as MetricsExport;
+pub use super::mirror_rule::Entity as MirrorRule;
+pub use super::notification::Entity as Notification;
+pub use super::outbox_message::Entity as OutboxMessage;
+pub use super::pending_request::Entity as PendingRequest;
+pub use super::pin_channel::Entity as PinChannel;
+pub use super::ping_role::Entity as PingRole;
+pub use super::preset::Entity as Preset;
+pub use super::quip::Entity as Quip;
 pub use super::request::Entity as Request;
+pub use super::request_attachment::Entity as RequestAttachment;
+pub use super::request_channel::Entity as RequestChannel;
+pub use super::request_extension::Entity as RequestExtension;
+pub use super::request_message::Entity as RequestMessage;
+pub use super::request_mirror::Entity as RequestMirror;
+pub use super::request_note::Entity as RequestNote;
+pub use super::request_render_history::Entity as RequestRenderHistory;
+pub use super::request_report::Entity as RequestReport;
+pub use super::season::Entity as Season;
+pub use super::spam_event::Entity as SpamEvent;
+pub use super::stockpile_item::Entity as StockpileItem;
 pub use super::task::Entity as Task;
+pub use super::task_override::Entity as TaskOverride;
 pub use super::user::Entity as User;
+pub use super::web_session::Entity as WebSession;
+pub use super::web_session_guild::Entity as WebSessionGuild;
@@ -2,6 +2,8 @@
 
 use sea_orm::entity::prelude::*;
 
+use crate::discord_id::{kind, DiscordId};
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
 #[sea_orm(table_name = "request")]
 pub struct Model {
@@ -10,17 +12,99 @@ pub struct Model {
     pub created_by: Uuid,
     pub created_at: TimeDateTimeWithTimeZone,
     #[sea_orm(unique)]
-    pub discord_message_id: Option<i64>,
+    pub discord_message_id: Option<DiscordId<kind::Message>>,
     pub title: String,
-    pub discord_channel_id: Option<i64>,
-    pub thumbnail_url: Option<String>,
+    pub discord_channel_id: Option<DiscordId<kind::Channel>>,
     pub archived_on: Option<TimeDateTimeWithTimeZone>,
     pub expires_on: Option<TimeDateTimeWithTimeZone>,
-    pub discord_guild_id: Option<i64>,
+    pub discord_guild_id: Option<DiscordId<kind::Guild>>,
+    pub discord_application_id: Option<DiscordId<kind::Application>>,
+    pub split_from: Option<Uuid>,
+    pub merged_into: Option<Uuid>,
+    pub blocked_by: Option<Uuid>,
+    pub discord_archive_channel_id: Option<DiscordId<kind::Channel>>,
+    pub archive_attempted_at: Option<TimeDateTimeWithTimeZone>,
+    pub archive_attempts: i32,
+    pub archive_failed_at: Option<TimeDateTimeWithTimeZone>,
+    pub archive_error: Option<String>,
+    pub icon: Option<String>,
+    pub kind: String,
+    pub repeated_from: Option<Uuid>,
+    pub confirm_completion: bool,
+    pub completion_requested_at: Option<TimeDateTimeWithTimeZone>,
+    pub completion_confirmed_at: Option<TimeDateTimeWithTimeZone>,
+    pub discord_feed_channel_id: Option<DiscordId<kind::Channel>>,
+    pub discord_feed_message_id: Option<DiscordId<kind::Message>>,
+    pub pinned: bool,
+    pub rendered_at: Option<TimeDateTimeWithTimeZone>,
+    pub updated_at: TimeDateTimeWithTimeZone,
+    pub restricted_to_role: Option<DiscordId<kind::Role>>,
+    pub location_hex: Option<String>,
+    pub location_grid: Option<String>,
+    pub location_map_url: Option<String>,
+    pub location_held_by: Option<String>,
+    pub location_lost_at: Option<TimeDateTimeWithTimeZone>,
+    pub first_claimed_at: Option<TimeDateTimeWithTimeZone>,
+    pub quip: Option<String>,
+    pub bumped_at: Option<TimeDateTimeWithTimeZone>,
+    pub sticky: bool,
+    pub require_completion_evidence: Option<bool>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::claim_link::Entity")]
+    ClaimLink,
+    #[sea_orm(has_many = "super::notification::Entity")]
+    Notification,
+    #[sea_orm(has_many = "super::outbox_message::Entity")]
+    OutboxMessage,
+    #[sea_orm(has_many = "super::request_attachment::Entity")]
+    RequestAttachment,
+    #[sea_orm(has_many = "super::request_extension::Entity")]
+    RequestExtension,
+    #[sea_orm(has_many = "super::request_message::Entity")]
+    RequestMessage,
+    #[sea_orm(has_many = "super::request_mirror::Entity")]
+    RequestMirror,
+    #[sea_orm(has_many = "super::request_note::Entity")]
+    RequestNote,
+    #[sea_orm(has_many = "super::request_render_history::Entity")]
+    RequestRenderHistory,
+    #[sea_orm(has_many = "super::request_report::Entity")]
+    RequestReport,
+    #[sea_orm(
+        belongs_to = "Entity",
+        from = "Column::SplitFrom",
+        to = "Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    SelfRef,
+    #[sea_orm(
+        belongs_to = "Entity",
+        from = "Column::MergedInto",
+        to = "Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    MergedInto,
+    #[sea_orm(
+        belongs_to = "Entity",
+        from = "Column::BlockedBy",
+        to = "Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    BlockedBy,
+    #[sea_orm(
+        belongs_to = "Entity",
+        from = "Column::RepeatedFrom",
+        to = "Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    RepeatedFrom,
     #[sea_orm(has_many = "super::task::Entity")]
     Task,
     #[sea_orm(
@@ -33,6 +117,66 @@ pub enum Relation {
     User,
 }
 
+impl Related<super::claim_link::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ClaimLink.def()
+    }
+}
+
+impl Related<super::notification::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Notification.def()
+    }
+}
+
+impl Related<super::outbox_message::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::OutboxMessage.def()
+    }
+}
+
+impl Related<super::request_attachment::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RequestAttachment.def()
+    }
+}
+
+impl Related<super::request_extension::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RequestExtension.def()
+    }
+}
+
+impl Related<super::request_message::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RequestMessage.def()
+    }
+}
+
+impl Related<super::request_mirror::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RequestMirror.def()
+    }
+}
+
+impl Related<super::request_note::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RequestNote.def()
+    }
+}
+
+impl Related<super::request_render_history::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RequestRenderHistory.def()
+    }
+}
+
+impl Related<super::request_report::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RequestReport.def()
+    }
+}
+
 impl Related<super::task::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Task.def()
@@ -2,6 +2,8 @@
 
 use sea_orm::entity::prelude::*;
 
+use crate::discord_id::{kind, DiscordId};
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
 #[sea_orm(table_name = "task")]
 pub struct Model {
@@ -13,6 +15,21 @@ pub struct Model {
     pub assigned_to: Option<Uuid>,
     pub started_at: Option<TimeDateTimeWithTimeZone>,
     pub completed_at: Option<TimeDateTimeWithTimeZone>,
+    pub moved_to: Option<Uuid>,
+    pub effort: Option<i32>,
+    pub section: Option<String>,
+    pub removed_at: Option<TimeDateTimeWithTimeZone>,
+    pub removed_by: Option<Uuid>,
+    pub completed_by: Option<Uuid>,
+    pub claim_eta: Option<TimeDateTimeWithTimeZone>,
+    pub claim_comment: Option<String>,
+    pub allowed_user_id: Option<DiscordId<kind::User>>,
+    pub allowed_role_id: Option<DiscordId<kind::Role>>,
+    pub stocked_in: Option<String>,
+    pub stocked_crates: Option<i32>,
+    pub updated_at: TimeDateTimeWithTimeZone,
+    pub deadline: Option<TimeDateTimeWithTimeZone>,
+    pub completion_evidence: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -25,6 +42,14 @@ pub enum Relation {
         on_delete = "NoAction"
     )]
     Request,
+    #[sea_orm(
+        belongs_to = "super::request::Entity",
+        from = "Column::MovedTo",
+        to = "super::request::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    MovedTo,
     #[sea_orm(
         belongs_to = "super::user::Entity",
         from = "Column::AssignedTo",
@@ -33,6 +58,24 @@ pub enum Relation {
         on_delete = "NoAction"
     )]
     User,
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::RemovedBy",
+        to = "super::user::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    RemovedBy,
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::CompletedBy",
+        to = "super::user::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    CompletedBy,
+    #[sea_orm(has_many = "super::task_override::Entity")]
+    TaskOverride,
 }
 
 impl Related<super::request::Entity> for Entity {
@@ -41,6 +84,12 @@ impl Related<super::request::Entity> for Entity {
     }
 }
 
+impl Related<super::task_override::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TaskOverride.def()
+    }
+}
+
 impl Related<super::user::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::User.def()
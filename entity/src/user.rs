@@ -2,6 +2,8 @@
 
 use sea_orm::entity::prelude::*;
 
+use crate::discord_id::{kind, DiscordId};
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
 #[sea_orm(table_name = "user")]
 pub struct Model {
@@ -9,17 +11,53 @@ pub struct Model {
     pub id: Uuid,
     pub created_at: TimeDateTimeWithTimeZone,
     #[sea_orm(unique)]
-    pub discord_user_id: i64,
+    pub discord_user_id: DiscordId<kind::User>,
+    pub time_zone: Option<String>,
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub profile_synced_at: Option<TimeDateTimeWithTimeZone>,
+    pub notify_via: Option<String>,
+    pub notify_address: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::badge::Entity")]
+    Badge,
+    #[sea_orm(has_many = "super::claim_link::Entity")]
+    ClaimLink,
     #[sea_orm(has_many = "super::delivery::Entity")]
     Delivery,
+    #[sea_orm(has_many = "super::notification::Entity")]
+    Notification,
+    #[sea_orm(has_many = "super::pending_request::Entity")]
+    PendingRequest,
     #[sea_orm(has_many = "super::request::Entity")]
     Request,
+    #[sea_orm(has_many = "super::request_attachment::Entity")]
+    RequestAttachment,
+    #[sea_orm(has_many = "super::request_extension::Entity")]
+    RequestExtension,
+    #[sea_orm(has_many = "super::request_note::Entity")]
+    RequestNote,
+    #[sea_orm(has_many = "super::spam_event::Entity")]
+    SpamEvent,
     #[sea_orm(has_many = "super::task::Entity")]
     Task,
+    #[sea_orm(has_many = "super::web_session::Entity")]
+    WebSession,
+}
+
+impl Related<super::badge::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Badge.def()
+    }
+}
+
+impl Related<super::claim_link::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ClaimLink.def()
+    }
 }
 
 impl Related<super::delivery::Entity> for Entity {
@@ -28,16 +66,58 @@ impl Related<super::delivery::Entity> for Entity {
     }
 }
 
+impl Related<super::notification::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Notification.def()
+    }
+}
+
+impl Related<super::pending_request::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PendingRequest.def()
+    }
+}
+
 impl Related<super::request::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Request.def()
     }
 }
 
+impl Related<super::request_attachment::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RequestAttachment.def()
+    }
+}
+
+impl Related<super::request_extension::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RequestExtension.def()
+    }
+}
+
+impl Related<super::request_note::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RequestNote.def()
+    }
+}
+
+impl Related<super::spam_event::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SpamEvent.def()
+    }
+}
+
 impl Related<super::task::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Task.def()
     }
 }
 
+impl Related<super::web_session::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::WebSession.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -8,6 +8,72 @@ mod m20231219_195822_add_request_archival_flag;
 mod m20231219_210033_add_request_expiration_timer;
 mod m20240224_144248_add_delivery;
 mod m20240715_180531_add_discord_guild;
+mod m20261017_120000_add_request_application_id;
+mod m20261017_130000_add_request_message;
+mod m20261017_140000_add_request_split;
+mod m20261017_150000_add_request_merged_into;
+mod m20261017_160000_add_request_blocked_by;
+mod m20261017_170000_add_task_effort;
+mod m20261017_180000_add_time_zone;
+mod m20261017_190000_add_guild_quick_claim;
+mod m20261017_200000_add_spam_event;
+mod m20261017_210000_add_moderation;
+mod m20261017_220000_add_request_note;
+mod m20261017_230000_add_request_archive_channel;
+mod m20261017_231000_add_request_attachment;
+mod m20261017_232000_add_metrics_export;
+mod m20261017_233000_add_preset;
+mod m20261017_234000_add_request_extension;
+mod m20261017_235000_add_request_archive_attempt;
+mod m20261017_235010_add_request_archive_failure;
+mod m20261017_235020_add_guild_setup;
+mod m20261017_235030_add_request_icon;
+mod m20261017_235040_add_request_kind;
+mod m20261017_235050_add_task_section;
+mod m20261017_235100_add_request_repeated_from;
+mod m20261017_235110_add_request_mirror;
+mod m20261017_235120_add_claim_link;
+mod m20261017_235130_add_web_session_guild;
+mod m20261017_235140_add_request_report;
+mod m20261017_235150_add_pending_request;
+mod m20261017_235200_add_task_removal;
+mod m20261017_235210_add_completion_confirmation;
+mod m20261017_235220_add_task_completed_by;
+mod m20261017_235230_add_task_claim_note;
+mod m20261017_235240_add_request_feed;
+mod m20261017_235250_add_pin_channel;
+mod m20261017_235300_add_guild_target_completion;
+mod m20261017_235310_add_guild_thank_contributors;
+mod m20261017_235320_add_user_profile;
+mod m20261017_235330_add_task_override;
+mod m20261017_235340_add_feature_flag;
+mod m20261017_235350_add_request_rendered_at;
+mod m20261017_235400_add_task_reservation;
+mod m20261017_235410_add_request_approval;
+mod m20261017_235420_add_stockpile;
+mod m20261017_235430_add_updated_at;
+mod m20261017_235440_add_controller_indexes;
+mod m20261017_235450_add_request_restriction;
+mod m20261017_235500_add_request_location;
+mod m20261017_235510_add_region_loss;
+mod m20261017_235520_add_backlog_warning;
+mod m20261017_235530_add_notification;
+mod m20261017_235540_add_item_emoji;
+mod m20261017_235550_add_guild_plain_rendering;
+mod m20261017_235600_add_idle_alert;
+mod m20261017_235610_add_claim_capacity;
+mod m20261017_235620_add_season;
+mod m20261017_235630_add_badge;
+mod m20261017_235640_add_guild_freeze;
+mod m20261017_235650_add_quip;
+mod m20261017_235700_add_request_render_history;
+mod m20261017_235710_add_task_deadline;
+mod m20261017_235720_add_bump_channel;
+mod m20261017_235730_add_outbox_message;
+mod m20261017_235740_add_request_sticky;
+mod m20261017_235750_add_completion_evidence;
+mod m20261017_235800_add_content_filter;
+mod m20261017_235810_add_web_session_access_token;
 
 pub struct Migrator;
 
@@ -23,6 +89,72 @@ impl MigratorTrait for Migrator {
             Box::new(m20231219_210033_add_request_expiration_timer::Migration),
             Box::new(m20240224_144248_add_delivery::Migration),
             Box::new(m20240715_180531_add_discord_guild::Migration),
+            Box::new(m20261017_120000_add_request_application_id::Migration),
+            Box::new(m20261017_130000_add_request_message::Migration),
+            Box::new(m20261017_140000_add_request_split::Migration),
+            Box::new(m20261017_150000_add_request_merged_into::Migration),
+            Box::new(m20261017_160000_add_request_blocked_by::Migration),
+            Box::new(m20261017_170000_add_task_effort::Migration),
+            Box::new(m20261017_180000_add_time_zone::Migration),
+            Box::new(m20261017_190000_add_guild_quick_claim::Migration),
+            Box::new(m20261017_200000_add_spam_event::Migration),
+            Box::new(m20261017_210000_add_moderation::Migration),
+            Box::new(m20261017_220000_add_request_note::Migration),
+            Box::new(m20261017_230000_add_request_archive_channel::Migration),
+            Box::new(m20261017_231000_add_request_attachment::Migration),
+            Box::new(m20261017_232000_add_metrics_export::Migration),
+            Box::new(m20261017_233000_add_preset::Migration),
+            Box::new(m20261017_234000_add_request_extension::Migration),
+            Box::new(m20261017_235000_add_request_archive_attempt::Migration),
+            Box::new(m20261017_235010_add_request_archive_failure::Migration),
+            Box::new(m20261017_235020_add_guild_setup::Migration),
+            Box::new(m20261017_235030_add_request_icon::Migration),
+            Box::new(m20261017_235040_add_request_kind::Migration),
+            Box::new(m20261017_235050_add_task_section::Migration),
+            Box::new(m20261017_235100_add_request_repeated_from::Migration),
+            Box::new(m20261017_235110_add_request_mirror::Migration),
+            Box::new(m20261017_235120_add_claim_link::Migration),
+            Box::new(m20261017_235130_add_web_session_guild::Migration),
+            Box::new(m20261017_235140_add_request_report::Migration),
+            Box::new(m20261017_235150_add_pending_request::Migration),
+            Box::new(m20261017_235200_add_task_removal::Migration),
+            Box::new(m20261017_235210_add_completion_confirmation::Migration),
+            Box::new(m20261017_235220_add_task_completed_by::Migration),
+            Box::new(m20261017_235230_add_task_claim_note::Migration),
+            Box::new(m20261017_235240_add_request_feed::Migration),
+            Box::new(m20261017_235250_add_pin_channel::Migration),
+            Box::new(m20261017_235300_add_guild_target_completion::Migration),
+            Box::new(m20261017_235310_add_guild_thank_contributors::Migration),
+            Box::new(m20261017_235320_add_user_profile::Migration),
+            Box::new(m20261017_235330_add_task_override::Migration),
+            Box::new(m20261017_235340_add_feature_flag::Migration),
+            Box::new(m20261017_235350_add_request_rendered_at::Migration),
+            Box::new(m20261017_235400_add_task_reservation::Migration),
+            Box::new(m20261017_235410_add_request_approval::Migration),
+            Box::new(m20261017_235420_add_stockpile::Migration),
+            Box::new(m20261017_235430_add_updated_at::Migration),
+            Box::new(m20261017_235440_add_controller_indexes::Migration),
+            Box::new(m20261017_235450_add_request_restriction::Migration),
+            Box::new(m20261017_235500_add_request_location::Migration),
+            Box::new(m20261017_235510_add_region_loss::Migration),
+            Box::new(m20261017_235520_add_backlog_warning::Migration),
+            Box::new(m20261017_235530_add_notification::Migration),
+            Box::new(m20261017_235540_add_item_emoji::Migration),
+            Box::new(m20261017_235550_add_guild_plain_rendering::Migration),
+            Box::new(m20261017_235600_add_idle_alert::Migration),
+            Box::new(m20261017_235610_add_claim_capacity::Migration),
+            Box::new(m20261017_235620_add_season::Migration),
+            Box::new(m20261017_235630_add_badge::Migration),
+            Box::new(m20261017_235640_add_guild_freeze::Migration),
+            Box::new(m20261017_235650_add_quip::Migration),
+            Box::new(m20261017_235700_add_request_render_history::Migration),
+            Box::new(m20261017_235710_add_task_deadline::Migration),
+            Box::new(m20261017_235720_add_bump_channel::Migration),
+            Box::new(m20261017_235730_add_outbox_message::Migration),
+            Box::new(m20261017_235740_add_request_sticky::Migration),
+            Box::new(m20261017_235750_add_completion_evidence::Migration),
+            Box::new(m20261017_235800_add_content_filter::Migration),
+            Box::new(m20261017_235810_add_web_session_access_token::Migration),
         ]
     }
 }
@@ -0,0 +1,73 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RequestMessage::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RequestMessage::Id)
+                            .uuid()
+                            .not_null()
+                            .default(PgFunc::gen_random_uuid())
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(RequestMessage::Request).uuid().not_null())
+                    .col(ColumnDef::new(RequestMessage::Page).integer().not_null())
+                    .col(
+                        ColumnDef::new(RequestMessage::DiscordChannelId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(RequestMessage::DiscordMessageId)
+                            .big_unsigned()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(RequestMessage::Table)
+                            .from_col(RequestMessage::Request)
+                            .to_tbl(Request::Table)
+                            .to_col(Request::Id),
+                    )
+                    .index(
+                        Index::create()
+                            .col(RequestMessage::Request)
+                            .col(RequestMessage::Page)
+                            .unique(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RequestMessage::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RequestMessage {
+    Table,
+    Id,
+    Request,
+    Page,
+    DiscordChannelId,
+    DiscordMessageId,
+}
+
+#[derive(DeriveIden)]
+enum Request {
+    Table,
+    Id,
+}
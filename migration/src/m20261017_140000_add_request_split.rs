@@ -0,0 +1,74 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .add_column(ColumnDef::new(Request::SplitFrom).uuid())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("request_split_from_fkey")
+                            .from_tbl(Request::Table)
+                            .from_col(Request::SplitFrom)
+                            .to_tbl(Request::Table)
+                            .to_col(Request::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .add_column(ColumnDef::new(Task::MovedTo).uuid())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("task_moved_to_fkey")
+                            .from_tbl(Task::Table)
+                            .from_col(Task::MovedTo)
+                            .to_tbl(Request::Table)
+                            .to_col(Request::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .drop_column(Task::MovedTo)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .drop_column(Request::SplitFrom)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Request {
+    Table,
+    Id,
+    SplitFrom,
+}
+
+#[derive(DeriveIden)]
+enum Task {
+    Table,
+    MovedTo,
+}
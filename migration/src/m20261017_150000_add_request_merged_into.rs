@@ -0,0 +1,44 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .add_column(ColumnDef::new(Request::MergedInto).uuid())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("request_merged_into_fkey")
+                            .from_tbl(Request::Table)
+                            .from_col(Request::MergedInto)
+                            .to_tbl(Request::Table)
+                            .to_col(Request::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .drop_column(Request::MergedInto)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Request {
+    Table,
+    Id,
+    MergedInto,
+}
@@ -0,0 +1,44 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .add_column(ColumnDef::new(Request::BlockedBy).uuid())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("request_blocked_by_fkey")
+                            .from_tbl(Request::Table)
+                            .from_col(Request::BlockedBy)
+                            .to_tbl(Request::Table)
+                            .to_col(Request::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .drop_column(Request::BlockedBy)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Request {
+    Table,
+    Id,
+    BlockedBy,
+}
@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SpamEvent::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SpamEvent::Id)
+                            .uuid()
+                            .not_null()
+                            .default(PgFunc::gen_random_uuid())
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(SpamEvent::User).uuid().not_null())
+                    .col(
+                        ColumnDef::new(SpamEvent::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(SpamEvent::DiscordGuildId).big_unsigned())
+                    .col(
+                        ColumnDef::new(SpamEvent::DiscordChannelId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(SpamEvent::Command).string().not_null())
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(SpamEvent::Table)
+                            .from_col(SpamEvent::User)
+                            .to_tbl(User::Table)
+                            .to_col(User::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SpamEvent::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SpamEvent {
+    Table,
+    Id,
+    User,
+    CreatedAt,
+    DiscordGuildId,
+    DiscordChannelId,
+    Command,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
@@ -0,0 +1,104 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(GuildBan::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(GuildBan::DiscordGuildId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(GuildBan::User).uuid().not_null())
+                    .col(ColumnDef::new(GuildBan::BannedBy).uuid().not_null())
+                    .col(
+                        ColumnDef::new(GuildBan::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(GuildBan::Reason).string())
+                    .primary_key(
+                        Index::create()
+                            .col(GuildBan::DiscordGuildId)
+                            .col(GuildBan::User),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(GuildBan::Table)
+                            .from_col(GuildBan::User)
+                            .to_tbl(User::Table)
+                            .to_col(User::Id),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(GuildBan::Table)
+                            .from_col(GuildBan::BannedBy)
+                            .to_tbl(User::Table)
+                            .to_col(User::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(RequestChannel::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RequestChannel::DiscordChannelId)
+                            .big_unsigned()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(RequestChannel::DiscordGuildId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .index(Index::create().col(RequestChannel::DiscordGuildId))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RequestChannel::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(GuildBan::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum GuildBan {
+    Table,
+    DiscordGuildId,
+    User,
+    BannedBy,
+    CreatedAt,
+    Reason,
+}
+
+#[derive(DeriveIden)]
+enum RequestChannel {
+    Table,
+    DiscordChannelId,
+    DiscordGuildId,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
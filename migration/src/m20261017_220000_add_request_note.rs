@@ -0,0 +1,76 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RequestNote::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RequestNote::Id)
+                            .uuid()
+                            .not_null()
+                            .default(PgFunc::gen_random_uuid())
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(RequestNote::Request).uuid().not_null())
+                    .col(ColumnDef::new(RequestNote::CreatedBy).uuid().not_null())
+                    .col(
+                        ColumnDef::new(RequestNote::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(RequestNote::Note).string().not_null())
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(RequestNote::Table)
+                            .from_col(RequestNote::Request)
+                            .to_tbl(Request::Table)
+                            .to_col(Request::Id),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(RequestNote::Table)
+                            .from_col(RequestNote::CreatedBy)
+                            .to_tbl(User::Table)
+                            .to_col(User::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RequestNote::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RequestNote {
+    Table,
+    Id,
+    Request,
+    CreatedBy,
+    CreatedAt,
+    Note,
+}
+
+#[derive(DeriveIden)]
+enum Request {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
@@ -0,0 +1,86 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RequestAttachment::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RequestAttachment::Id)
+                            .uuid()
+                            .not_null()
+                            .default(PgFunc::gen_random_uuid())
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(RequestAttachment::Request).uuid().not_null())
+                    .col(
+                        ColumnDef::new(RequestAttachment::CreatedBy)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(RequestAttachment::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(RequestAttachment::Url).string().not_null())
+                    .col(
+                        ColumnDef::new(RequestAttachment::DiscordMessageId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(RequestAttachment::Table)
+                            .from_col(RequestAttachment::Request)
+                            .to_tbl(Request::Table)
+                            .to_col(Request::Id),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(RequestAttachment::Table)
+                            .from_col(RequestAttachment::CreatedBy)
+                            .to_tbl(User::Table)
+                            .to_col(User::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RequestAttachment::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RequestAttachment {
+    Table,
+    Id,
+    Request,
+    CreatedBy,
+    CreatedAt,
+    Url,
+    DiscordMessageId,
+}
+
+#[derive(DeriveIden)]
+enum Request {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
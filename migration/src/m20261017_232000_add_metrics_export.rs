@@ -0,0 +1,43 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MetricsExport::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MetricsExport::PeriodStart)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(MetricsExport::ExportedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MetricsExport::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MetricsExport {
+    Table,
+    PeriodStart,
+    ExportedAt,
+}
@@ -0,0 +1,78 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Presets that are available in every guild, as (name, tasks in the `/request` task syntax)
+const BUILT_IN_PRESETS: &[(&str, &str)] = &[
+    (
+        "Standard frontline resupply",
+        "10 crates of 7.62mm; 5 crates of 9mm; 5 crates of soldier supplies; \
+         5 crates of bandages; 3 crates of first aid kits; 3 crates of trauma kits; \
+         2 crates of blood plasma; 3 crates of sticky bombs",
+    ),
+    (
+        "Medical resupply",
+        "5 crates of bandages; 5 crates of first aid kits; 5 crates of trauma kits; \
+         5 crates of blood plasma",
+    ),
+    (
+        "Anti-tank resupply",
+        "5 crates of sticky bombs; 5 crates of RPG shells; 3 crates of 68mm; 2 crates of 30mm",
+    ),
+    ("Base building materials", "{3x} 1500 bmats; 500 rmats"),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Preset::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Preset::Id)
+                            .uuid()
+                            .not_null()
+                            .default(PgFunc::gen_random_uuid())
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Preset::DiscordGuildId).big_unsigned())
+                    .col(ColumnDef::new(Preset::Name).string().not_null())
+                    .col(ColumnDef::new(Preset::Tasks).string().not_null())
+                    .index(
+                        Index::create()
+                            .unique()
+                            .col(Preset::DiscordGuildId)
+                            .col(Preset::Name),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        let mut seed = Query::insert()
+            .into_table(Preset::Table)
+            .columns([Preset::Name, Preset::Tasks])
+            .to_owned();
+        for (name, tasks) in BUILT_IN_PRESETS {
+            seed.values_panic([(*name).into(), (*tasks).into()]);
+        }
+        manager.exec_stmt(seed).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Preset::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Preset {
+    Table,
+    Id,
+    DiscordGuildId,
+    Name,
+    Tasks,
+}
@@ -0,0 +1,68 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSetting::Table)
+                    .add_column(ColumnDef::new(GuildSetting::DefaultExpiresInSecs).big_integer())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(PingRole::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PingRole::DiscordGuildId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PingRole::DiscordRoleId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(PingRole::DiscordGuildId)
+                            .col(PingRole::DiscordRoleId),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PingRole::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSetting::Table)
+                    .drop_column(GuildSetting::DefaultExpiresInSecs)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum GuildSetting {
+    Table,
+    DefaultExpiresInSecs,
+}
+
+#[derive(DeriveIden)]
+enum PingRole {
+    Table,
+    DiscordGuildId,
+    DiscordRoleId,
+}
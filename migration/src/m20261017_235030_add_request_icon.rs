@@ -0,0 +1,117 @@
+use sea_orm_migration::prelude::*;
+
+/// Icons that used to be linked as Discord attachments, which Discord has started expiring
+const LEGACY_THUMBNAILS: &[(&str, &str)] = &[
+    ("truck", "https://cdn.discordapp.com/attachments/919852056091701299/920553851008987196/Dunne_Transport_Vehicle_Icon.png"),
+    ("flatbed", "https://cdn.discordapp.com/attachments/919852056091701299/920553850354688061/FlatbedTruckVehicleIcon.png"),
+    ("freighter", "https://cdn.discordapp.com/attachments/1170732453116248226/1182871827995963444/image.png"),
+    ("train", "https://cdn.discordapp.com/attachments/919852056091701299/1094794004945698938/ezgif.com-webp-to-png.png"),
+];
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ApplicationEmoji::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ApplicationEmoji::DiscordApplicationId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ApplicationEmoji::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(ApplicationEmoji::DiscordEmojiId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(ApplicationEmoji::DiscordApplicationId)
+                            .col(ApplicationEmoji::Name),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .add_column(ColumnDef::new(Request::Icon).string())
+                    .to_owned(),
+            )
+            .await?;
+        for (icon, url) in LEGACY_THUMBNAILS {
+            manager
+                .exec_stmt(
+                    Query::update()
+                        .table(Request::Table)
+                        .value(Request::Icon, *icon)
+                        .and_where(Expr::col(Request::ThumbnailUrl).eq(*url))
+                        .to_owned(),
+                )
+                .await?;
+        }
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .drop_column(Request::ThumbnailUrl)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .add_column(ColumnDef::new(Request::ThumbnailUrl).string())
+                    .to_owned(),
+            )
+            .await?;
+        for (icon, url) in LEGACY_THUMBNAILS {
+            manager
+                .exec_stmt(
+                    Query::update()
+                        .table(Request::Table)
+                        .value(Request::ThumbnailUrl, *url)
+                        .and_where(Expr::col(Request::Icon).eq(*icon))
+                        .to_owned(),
+                )
+                .await?;
+        }
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .drop_column(Request::Icon)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ApplicationEmoji::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ApplicationEmoji {
+    Table,
+    DiscordApplicationId,
+    Name,
+    DiscordEmojiId,
+}
+
+#[derive(DeriveIden)]
+enum Request {
+    Table,
+    ThumbnailUrl,
+    Icon,
+}
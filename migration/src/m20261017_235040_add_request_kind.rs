@@ -0,0 +1,82 @@
+use sea_orm_migration::prelude::*;
+
+/// The icon of each kind of request, see `m20261017_235030_add_request_icon`
+const KIND_ICONS: &[(&str, &str)] = &[
+    ("Truck", "truck"),
+    ("Flatbed", "flatbed"),
+    ("Freighter", "freighter"),
+    ("Train", "train"),
+];
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .add_column(
+                        ColumnDef::new(Request::Kind)
+                            .string()
+                            .not_null()
+                            .default("General"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        for (kind, icon) in KIND_ICONS {
+            manager
+                .exec_stmt(
+                    Query::update()
+                        .table(Request::Table)
+                        .value(Request::Kind, *kind)
+                        .and_where(Expr::col(Request::Icon).eq(*icon))
+                        .to_owned(),
+                )
+                .await?;
+        }
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSetting::Table)
+                    .add_column(ColumnDef::new(GuildSetting::Palette).string())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSetting::Table)
+                    .drop_column(GuildSetting::Palette)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .drop_column(Request::Kind)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Request {
+    Table,
+    Icon,
+    Kind,
+}
+
+#[derive(DeriveIden)]
+enum GuildSetting {
+    Table,
+    Palette,
+}
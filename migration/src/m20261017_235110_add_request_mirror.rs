@@ -0,0 +1,121 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MirrorRule::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MirrorRule::FromChannel)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MirrorRule::ToChannel)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MirrorRule::FromGuild)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MirrorRule::ToGuild)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MirrorRule::ApprovedBySource)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(MirrorRule::ApprovedByTarget)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(MirrorRule::FromChannel)
+                            .col(MirrorRule::ToChannel),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(RequestMirror::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(RequestMirror::Request).uuid().not_null())
+                    .col(
+                        ColumnDef::new(RequestMirror::DiscordChannelId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(RequestMirror::DiscordMessageId)
+                            .big_unsigned()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(RequestMirror::Request)
+                            .col(RequestMirror::DiscordChannelId),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(RequestMirror::Table)
+                            .from_col(RequestMirror::Request)
+                            .to_tbl(Request::Table)
+                            .to_col(Request::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RequestMirror::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(MirrorRule::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MirrorRule {
+    Table,
+    FromChannel,
+    ToChannel,
+    FromGuild,
+    ToGuild,
+    ApprovedBySource,
+    ApprovedByTarget,
+}
+
+#[derive(DeriveIden)]
+enum RequestMirror {
+    Table,
+    Request,
+    DiscordChannelId,
+    DiscordMessageId,
+}
+
+#[derive(DeriveIden)]
+enum Request {
+    Table,
+    Id,
+}
@@ -0,0 +1,77 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WebSession::Table)
+                    .add_column(
+                        ColumnDef::new(WebSession::GuildsFetchedAt).timestamp_with_time_zone(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(WebSessionGuild::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(WebSessionGuild::Session).uuid().not_null())
+                    .col(
+                        ColumnDef::new(WebSessionGuild::DiscordGuildId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(WebSessionGuild::Name).string().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(WebSessionGuild::Session)
+                            .col(WebSessionGuild::DiscordGuildId),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(WebSessionGuild::Table)
+                            .from_col(WebSessionGuild::Session)
+                            .to_tbl(WebSession::Table)
+                            .to_col(WebSession::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WebSessionGuild::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WebSession::Table)
+                    .drop_column(WebSession::GuildsFetchedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum WebSession {
+    Table,
+    Id,
+    GuildsFetchedAt,
+}
+
+#[derive(DeriveIden)]
+enum WebSessionGuild {
+    Table,
+    Session,
+    DiscordGuildId,
+    Name,
+}
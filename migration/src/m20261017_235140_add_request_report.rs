@@ -0,0 +1,122 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSetting::Table)
+                    .add_column(ColumnDef::new(GuildSetting::ReportChannel).big_integer())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(RequestReport::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RequestReport::Id)
+                            .uuid()
+                            .not_null()
+                            .default(PgFunc::gen_random_uuid())
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(RequestReport::Request).uuid().not_null())
+                    .col(ColumnDef::new(RequestReport::ReportedBy).uuid().not_null())
+                    .col(
+                        ColumnDef::new(RequestReport::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(RequestReport::Reason).string().not_null())
+                    .col(ColumnDef::new(RequestReport::Details).string())
+                    .col(
+                        ColumnDef::new(RequestReport::DiscordChannelId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(RequestReport::DiscordMessageId).big_integer())
+                    .col(ColumnDef::new(RequestReport::ResolvedBy).uuid())
+                    .col(ColumnDef::new(RequestReport::ResolvedAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(RequestReport::Resolution).string())
+                    .index(Index::create().col(RequestReport::Request))
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(RequestReport::Table)
+                            .from_col(RequestReport::Request)
+                            .to_tbl(Request::Table)
+                            .to_col(Request::Id),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(RequestReport::Table)
+                            .from_col(RequestReport::ReportedBy)
+                            .to_tbl(User::Table)
+                            .to_col(User::Id),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(RequestReport::Table)
+                            .from_col(RequestReport::ResolvedBy)
+                            .to_tbl(User::Table)
+                            .to_col(User::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RequestReport::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSetting::Table)
+                    .drop_column(GuildSetting::ReportChannel)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum GuildSetting {
+    Table,
+    ReportChannel,
+}
+
+#[derive(DeriveIden)]
+enum RequestReport {
+    Table,
+    Id,
+    Request,
+    ReportedBy,
+    CreatedAt,
+    Reason,
+    Details,
+    DiscordChannelId,
+    DiscordMessageId,
+    ResolvedBy,
+    ResolvedAt,
+    Resolution,
+}
+
+#[derive(DeriveIden)]
+enum Request {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
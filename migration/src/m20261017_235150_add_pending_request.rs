@@ -0,0 +1,97 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PendingRequest::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PendingRequest::Id)
+                            .uuid()
+                            .not_null()
+                            .default(PgFunc::gen_random_uuid())
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(PendingRequest::CreatedBy).uuid().not_null())
+                    .col(
+                        ColumnDef::new(PendingRequest::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(PendingRequest::Title).string().not_null())
+                    .col(ColumnDef::new(PendingRequest::Kind).string().not_null())
+                    .col(ColumnDef::new(PendingRequest::Icon).string())
+                    .col(ColumnDef::new(PendingRequest::ExpiresOn).timestamp_with_time_zone())
+                    .col(ColumnDef::new(PendingRequest::BlockedBy).uuid())
+                    .col(ColumnDef::new(PendingRequest::Tasks).string().not_null())
+                    .col(
+                        ColumnDef::new(PendingRequest::DuplicateOf)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(PendingRequest::Table)
+                            .from_col(PendingRequest::CreatedBy)
+                            .to_tbl(User::Table)
+                            .to_col(User::Id),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(PendingRequest::Table)
+                            .from_col(PendingRequest::BlockedBy)
+                            .to_tbl(Request::Table)
+                            .to_col(Request::Id),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(PendingRequest::Table)
+                            .from_col(PendingRequest::DuplicateOf)
+                            .to_tbl(Request::Table)
+                            .to_col(Request::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PendingRequest::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PendingRequest {
+    Table,
+    Id,
+    CreatedBy,
+    CreatedAt,
+    Title,
+    Kind,
+    Icon,
+    ExpiresOn,
+    BlockedBy,
+    Tasks,
+    DuplicateOf,
+}
+
+#[derive(DeriveIden)]
+enum Request {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
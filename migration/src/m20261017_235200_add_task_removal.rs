@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .add_column(ColumnDef::new(Task::RemovedAt).timestamp_with_time_zone())
+                    .add_column(ColumnDef::new(Task::RemovedBy).uuid())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("task_removed_by_fkey")
+                            .from_tbl(Task::Table)
+                            .from_col(Task::RemovedBy)
+                            .to_tbl(User::Table)
+                            .to_col(User::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .drop_column(Task::RemovedAt)
+                    .drop_column(Task::RemovedBy)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Task {
+    Table,
+    RemovedAt,
+    RemovedBy,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
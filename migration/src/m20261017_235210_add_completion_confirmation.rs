@@ -0,0 +1,94 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSetting::Table)
+                    .add_column(ColumnDef::new(GuildSetting::ConfirmCompletion).boolean())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .add_column(
+                        ColumnDef::new(Request::ConfirmCompletion)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .add_column(
+                        ColumnDef::new(Request::CompletionRequestedAt).timestamp_with_time_zone(),
+                    )
+                    .add_column(
+                        ColumnDef::new(Request::CompletionConfirmedAt).timestamp_with_time_zone(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PendingRequest::Table)
+                    .add_column(ColumnDef::new(PendingRequest::ConfirmCompletion).boolean())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PendingRequest::Table)
+                    .drop_column(PendingRequest::ConfirmCompletion)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .drop_column(Request::ConfirmCompletion)
+                    .drop_column(Request::CompletionRequestedAt)
+                    .drop_column(Request::CompletionConfirmedAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSetting::Table)
+                    .drop_column(GuildSetting::ConfirmCompletion)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum GuildSetting {
+    Table,
+    ConfirmCompletion,
+}
+
+#[derive(DeriveIden)]
+enum Request {
+    Table,
+    ConfirmCompletion,
+    CompletionRequestedAt,
+    CompletionConfirmedAt,
+}
+
+#[derive(DeriveIden)]
+enum PendingRequest {
+    Table,
+    ConfirmCompletion,
+}
@@ -0,0 +1,61 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .add_column(ColumnDef::new(Task::CompletedBy).uuid())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("task_completed_by_fkey")
+                            .from_tbl(Task::Table)
+                            .from_col(Task::CompletedBy)
+                            .to_tbl(User::Table)
+                            .to_col(User::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        // Completing a task used to make the completer its assignee
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Task::Table)
+                    .value(Task::CompletedBy, Expr::col(Task::AssignedTo))
+                    .and_where(Expr::col(Task::CompletedAt).is_not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .drop_column(Task::CompletedBy)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Task {
+    Table,
+    AssignedTo,
+    CompletedAt,
+    CompletedBy,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PinChannel::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PinChannel::DiscordChannelId)
+                            .big_unsigned()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PinChannel::DiscordGuildId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .index(Index::create().col(PinChannel::DiscordGuildId))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .add_column(
+                        ColumnDef::new(Request::Pinned)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .drop_column(Request::Pinned)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(PinChannel::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PinChannel {
+    Table,
+    DiscordChannelId,
+    DiscordGuildId,
+}
+
+#[derive(DeriveIden)]
+enum Request {
+    Table,
+    Pinned,
+}
@@ -0,0 +1,88 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TaskOverride::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TaskOverride::Id)
+                            .uuid()
+                            .not_null()
+                            .default(PgFunc::gen_random_uuid())
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(TaskOverride::Task).uuid().not_null())
+                    .col(ColumnDef::new(TaskOverride::OverriddenBy).uuid().not_null())
+                    .col(
+                        ColumnDef::new(TaskOverride::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(TaskOverride::State).string().not_null())
+                    .col(ColumnDef::new(TaskOverride::Assignee).uuid())
+                    .col(ColumnDef::new(TaskOverride::Reason).string())
+                    .index(Index::create().col(TaskOverride::Task))
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(TaskOverride::Table)
+                            .from_col(TaskOverride::Task)
+                            .to_tbl(Task::Table)
+                            .to_col(Task::Id),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(TaskOverride::Table)
+                            .from_col(TaskOverride::OverriddenBy)
+                            .to_tbl(User::Table)
+                            .to_col(User::Id),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(TaskOverride::Table)
+                            .from_col(TaskOverride::Assignee)
+                            .to_tbl(User::Table)
+                            .to_col(User::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TaskOverride::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TaskOverride {
+    Table,
+    Id,
+    Task,
+    OverriddenBy,
+    CreatedAt,
+    State,
+    Assignee,
+    Reason,
+}
+
+#[derive(DeriveIden)]
+enum Task {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
@@ -0,0 +1,44 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(FeatureFlag::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(FeatureFlag::DiscordGuildId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(FeatureFlag::Feature).string().not_null())
+                    .col(ColumnDef::new(FeatureFlag::Enabled).boolean().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(FeatureFlag::DiscordGuildId)
+                            .col(FeatureFlag::Feature),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(FeatureFlag::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum FeatureFlag {
+    Table,
+    DiscordGuildId,
+    Feature,
+    Enabled,
+}
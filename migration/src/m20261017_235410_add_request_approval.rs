@@ -0,0 +1,87 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ApprovalChannel::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ApprovalChannel::DiscordChannelId)
+                            .big_unsigned()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ApprovalChannel::DiscordGuildId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ApprovalChannel::QueueChannelId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .index(Index::create().col(ApprovalChannel::DiscordGuildId))
+                    .to_owned(),
+            )
+            .await?;
+        // Requests waiting for approval are pending without duplicating anything
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PendingRequest::Table)
+                    .modify_column(ColumnDef::new(PendingRequest::DuplicateOf).uuid().null())
+                    .add_column(ColumnDef::new(PendingRequest::DiscordChannelId).big_unsigned())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(PendingRequest::Table)
+                    .and_where(Expr::col(PendingRequest::DuplicateOf).is_null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PendingRequest::Table)
+                    .drop_column(PendingRequest::DiscordChannelId)
+                    .modify_column(
+                        ColumnDef::new(PendingRequest::DuplicateOf)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ApprovalChannel::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ApprovalChannel {
+    Table,
+    DiscordChannelId,
+    DiscordGuildId,
+    QueueChannelId,
+}
+
+#[derive(DeriveIden)]
+enum PendingRequest {
+    Table,
+    DuplicateOf,
+    DiscordChannelId,
+}
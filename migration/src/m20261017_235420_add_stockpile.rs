@@ -0,0 +1,99 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(StockpileItem::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(StockpileItem::DiscordGuildId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(StockpileItem::Stockpile).string().not_null())
+                    .col(ColumnDef::new(StockpileItem::Item).string().not_null())
+                    .col(
+                        ColumnDef::new(StockpileItem::Count)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(StockpileItem::DiscordGuildId)
+                            .col(StockpileItem::Stockpile)
+                            .col(StockpileItem::Item),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSetting::Table)
+                    .add_column(ColumnDef::new(GuildSetting::TargetStockpile).string())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .add_column(ColumnDef::new(Task::StockedIn).string())
+                    .add_column(ColumnDef::new(Task::StockedCrates).integer())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .drop_column(Task::StockedIn)
+                    .drop_column(Task::StockedCrates)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSetting::Table)
+                    .drop_column(GuildSetting::TargetStockpile)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(StockpileItem::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum StockpileItem {
+    Table,
+    DiscordGuildId,
+    Stockpile,
+    Item,
+    Count,
+}
+
+#[derive(DeriveIden)]
+enum GuildSetting {
+    Table,
+    TargetStockpile,
+}
+
+#[derive(DeriveIden)]
+enum Task {
+    Table,
+    StockedIn,
+    StockedCrates,
+}
@@ -0,0 +1,180 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Bumps `updated_at` whenever a row changes, ignoring changes to the columns that are passed as
+/// arguments (which should include `updated_at` itself), so that bookkeeping doesn't count
+const TOUCH_UPDATED_AT: &str = r#"
+CREATE FUNCTION touch_updated_at() RETURNS trigger AS $$
+BEGIN
+    IF to_jsonb(NEW) - TG_ARGV IS DISTINCT FROM to_jsonb(OLD) - TG_ARGV THEN
+        NEW.updated_at := now();
+    END IF;
+    RETURN NEW;
+END
+$$ LANGUAGE plpgsql
+"#;
+
+/// Bumps the `updated_at` of the request that a task belongs to whenever the task changes, since
+/// claiming and completing tasks is most of what happens to a request
+const TOUCH_REQUEST_OF_TASK: &str = r#"
+CREATE FUNCTION touch_request_of_task() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        UPDATE request SET updated_at = now() WHERE id = NEW.request;
+    ELSIF NEW.updated_at IS DISTINCT FROM OLD.updated_at THEN
+        UPDATE request SET updated_at = now() WHERE id = NEW.request;
+    END IF;
+    RETURN NULL;
+END
+$$ LANGUAGE plpgsql
+"#;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .add_column(
+                        ColumnDef::new(Request::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .add_column(
+                        ColumnDef::new(Task::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Delivery::Table)
+                    .add_column(
+                        ColumnDef::new(Delivery::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing rows were last touched when the last thing that was recorded about them happened
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            "UPDATE task SET updated_at = coalesce(
+                greatest(started_at, completed_at, removed_at),
+                (SELECT created_at FROM request WHERE request.id = task.request)
+            )",
+        )
+        .await?;
+        db.execute_unprepared(
+            "UPDATE request SET updated_at = greatest(
+                created_at,
+                archived_on,
+                (SELECT max(updated_at) FROM task WHERE task.request = request.id)
+            )",
+        )
+        .await?;
+        db.execute_unprepared("UPDATE delivery SET updated_at = created_at")
+            .await?;
+
+        db.execute_unprepared(TOUCH_UPDATED_AT).await?;
+        db.execute_unprepared(TOUCH_REQUEST_OF_TASK).await?;
+        // Re-rendering a request isn't a change to it
+        db.execute_unprepared(
+            "CREATE TRIGGER touch_updated_at BEFORE UPDATE ON request
+                FOR EACH ROW EXECUTE FUNCTION touch_updated_at('updated_at', 'rendered_at')",
+        )
+        .await?;
+        db.execute_unprepared(
+            "CREATE TRIGGER touch_updated_at BEFORE UPDATE ON task
+                FOR EACH ROW EXECUTE FUNCTION touch_updated_at('updated_at')",
+        )
+        .await?;
+        db.execute_unprepared(
+            "CREATE TRIGGER touch_request AFTER INSERT OR UPDATE ON task
+                FOR EACH ROW EXECUTE FUNCTION touch_request_of_task()",
+        )
+        .await?;
+        db.execute_unprepared(
+            "CREATE TRIGGER touch_updated_at BEFORE UPDATE ON delivery
+                FOR EACH ROW EXECUTE FUNCTION touch_updated_at('updated_at')",
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared("DROP TRIGGER touch_updated_at ON delivery")
+            .await?;
+        db.execute_unprepared("DROP TRIGGER touch_request ON task")
+            .await?;
+        db.execute_unprepared("DROP TRIGGER touch_updated_at ON task")
+            .await?;
+        db.execute_unprepared("DROP TRIGGER touch_updated_at ON request")
+            .await?;
+        db.execute_unprepared("DROP FUNCTION touch_request_of_task()")
+            .await?;
+        db.execute_unprepared("DROP FUNCTION touch_updated_at()")
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Delivery::Table)
+                    .drop_column(Delivery::UpdatedAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .drop_column(Task::UpdatedAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .drop_column(Request::UpdatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Request {
+    Table,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Task {
+    Table,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Delivery {
+    Table,
+    UpdatedAt,
+}
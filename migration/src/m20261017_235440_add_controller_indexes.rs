@@ -0,0 +1,67 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The controllers look for open requests that are about to expire (or have) every few
+        // seconds
+        manager
+            .create_index(
+                Index::create()
+                    .name("request_archived_on_expires_on_idx")
+                    .table(Request::Table)
+                    .col(Request::ArchivedOn)
+                    .col(Request::ExpiresOn)
+                    .to_owned(),
+            )
+            .await?;
+        // Foreign keys aren't indexed by themselves, but tasks are almost always looked up by
+        // request, often to find the ones that are still to be done
+        manager
+            .create_index(
+                Index::create()
+                    .name("task_request_completed_at_idx")
+                    .table(Task::Table)
+                    .col(Task::Request)
+                    .col(Task::CompletedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("task_request_completed_at_idx")
+                    .table(Task::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("request_archived_on_expires_on_idx")
+                    .table(Request::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Request {
+    Table,
+    ArchivedOn,
+    ExpiresOn,
+}
+
+#[derive(DeriveIden)]
+enum Task {
+    Table,
+    Request,
+    CompletedAt,
+}
@@ -0,0 +1,66 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .add_column(ColumnDef::new(Request::LocationHex).string())
+                    .add_column(ColumnDef::new(Request::LocationGrid).string())
+                    .add_column(ColumnDef::new(Request::LocationMapUrl).string())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PendingRequest::Table)
+                    .add_column(ColumnDef::new(PendingRequest::LocationHex).string())
+                    .add_column(ColumnDef::new(PendingRequest::LocationGrid).string())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PendingRequest::Table)
+                    .drop_column(PendingRequest::LocationHex)
+                    .drop_column(PendingRequest::LocationGrid)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .drop_column(Request::LocationHex)
+                    .drop_column(Request::LocationGrid)
+                    .drop_column(Request::LocationMapUrl)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Request {
+    Table,
+    LocationHex,
+    LocationGrid,
+    LocationMapUrl,
+}
+
+#[derive(DeriveIden)]
+enum PendingRequest {
+    Table,
+    LocationHex,
+    LocationGrid,
+}
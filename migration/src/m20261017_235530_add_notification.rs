@@ -0,0 +1,144 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Notification::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Notification::Id)
+                            .uuid()
+                            .not_null()
+                            .default(PgFunc::gen_random_uuid())
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Notification::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(Notification::Event).string().not_null())
+                    .col(ColumnDef::new(Notification::Content).string().not_null())
+                    .col(ColumnDef::new(Notification::Request).uuid())
+                    .col(ColumnDef::new(Notification::Recipient).uuid())
+                    .col(ColumnDef::new(Notification::DiscordGuildId).big_integer())
+                    .col(ColumnDef::new(Notification::DiscordApplicationId).big_integer())
+                    .col(ColumnDef::new(Notification::SentAt).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(Notification::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(Notification::Error).string())
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(Notification::Table)
+                            .from_col(Notification::Request)
+                            .to_tbl(Request::Table)
+                            .to_col(Request::Id),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(Notification::Table)
+                            .from_col(Notification::Recipient)
+                            .to_tbl(User::Table)
+                            .to_col(User::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("notification_sent_at_idx")
+                    .table(Notification::Table)
+                    .col(Notification::SentAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(ColumnDef::new(User::NotifyVia).string())
+                    .add_column(ColumnDef::new(User::NotifyAddress).string())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSetting::Table)
+                    .add_column(ColumnDef::new(GuildSetting::NotifyWebhookUrl).string())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSetting::Table)
+                    .drop_column(GuildSetting::NotifyWebhookUrl)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::NotifyVia)
+                    .drop_column(User::NotifyAddress)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Notification::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Notification {
+    Table,
+    Id,
+    CreatedAt,
+    Event,
+    Content,
+    Request,
+    Recipient,
+    DiscordGuildId,
+    DiscordApplicationId,
+    SentAt,
+    Attempts,
+    Error,
+}
+
+#[derive(DeriveIden)]
+enum Request {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+    NotifyVia,
+    NotifyAddress,
+}
+
+#[derive(DeriveIden)]
+enum GuildSetting {
+    Table,
+    NotifyWebhookUrl,
+}
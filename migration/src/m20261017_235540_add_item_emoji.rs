@@ -0,0 +1,44 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ItemEmoji::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ItemEmoji::DiscordGuildId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ItemEmoji::Item).string().not_null())
+                    .col(ColumnDef::new(ItemEmoji::Emoji).string().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(ItemEmoji::DiscordGuildId)
+                            .col(ItemEmoji::Item),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ItemEmoji::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ItemEmoji {
+    Table,
+    DiscordGuildId,
+    Item,
+    Emoji,
+}
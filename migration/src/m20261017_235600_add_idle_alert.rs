@@ -0,0 +1,68 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSetting::Table)
+                    .add_column(ColumnDef::new(GuildSetting::IdleAlertSecs).big_integer())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .add_column(ColumnDef::new(Request::FirstClaimedAt).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await?;
+        // Tasks that were unclaimed again since have lost when they were first claimed, but whatever
+        // is still claimed or completed is the best guess there is
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE request SET first_claimed_at = (
+                    SELECT min(least(started_at, completed_at)) FROM task WHERE task.request = request.id
+                )",
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .drop_column(Request::FirstClaimedAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSetting::Table)
+                    .drop_column(GuildSetting::IdleAlertSecs)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum GuildSetting {
+    Table,
+    IdleAlertSecs,
+}
+
+#[derive(DeriveIden)]
+enum Request {
+    Table,
+    FirstClaimedAt,
+}
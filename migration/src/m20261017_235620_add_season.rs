@@ -0,0 +1,74 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Season::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Season::DiscordGuildId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Season::Number).integer().not_null())
+                    .col(
+                        ColumnDef::new(Season::StartedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Season::EndedAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(Season::WarNumber).integer())
+                    .primary_key(
+                        Index::create()
+                            .col(Season::DiscordGuildId)
+                            .col(Season::Number),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSetting::Table)
+                    .add_column(ColumnDef::new(GuildSetting::SeasonLength).string())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSetting::Table)
+                    .drop_column(GuildSetting::SeasonLength)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Season::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Season {
+    Table,
+    DiscordGuildId,
+    Number,
+    StartedAt,
+    EndedAt,
+    WarNumber,
+}
+
+#[derive(DeriveIden)]
+enum GuildSetting {
+    Table,
+    SeasonLength,
+}
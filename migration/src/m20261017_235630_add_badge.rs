@@ -0,0 +1,152 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Badge::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Badge::DiscordGuildId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Badge::User).uuid().not_null())
+                    .col(ColumnDef::new(Badge::Kind).string().not_null())
+                    .col(ColumnDef::new(Badge::Period).string().not_null())
+                    .col(
+                        ColumnDef::new(Badge::EarnedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(Badge::Request).uuid())
+                    .primary_key(
+                        Index::create()
+                            .col(Badge::DiscordGuildId)
+                            .col(Badge::User)
+                            .col(Badge::Kind)
+                            .col(Badge::Period),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(Badge::Table)
+                            .from_col(Badge::User)
+                            .to_tbl(User::Table)
+                            .to_col(User::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(BadgeRole::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(BadgeRole::DiscordGuildId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(BadgeRole::Kind).string().not_null())
+                    .col(
+                        ColumnDef::new(BadgeRole::DiscordRoleId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(BadgeRole::DiscordGuildId)
+                            .col(BadgeRole::Kind),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSetting::Table)
+                    .add_column(ColumnDef::new(GuildSetting::BadgeAnnouncements).boolean())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Milestones that were reached before badges existed are awarded quietly, so that servers
+        // aren't flooded with congratulations for old deeds
+        let db = manager.get_connection();
+        for (kind, having) in [
+            ("first-completion", "TRUE"),
+            ("hundred-tasks", "count(*) >= 100"),
+        ] {
+            db.execute_unprepared(&format!(
+                "INSERT INTO badge (discord_guild_id, \"user\", kind, period, earned_at)
+                SELECT request.discord_guild_id, task.completed_by, '{kind}', '', max(task.completed_at)
+                FROM task JOIN request ON request.id = task.request
+                WHERE request.discord_guild_id IS NOT NULL
+                    AND request.merged_into IS NULL
+                    AND task.completed_at IS NOT NULL
+                    AND task.completed_by IS NOT NULL
+                    AND task.moved_to IS NULL
+                    AND task.removed_at IS NULL
+                    AND task.completed_by NOT IN (SELECT id FROM \"user\" WHERE discord_user_id = 0)
+                GROUP BY request.discord_guild_id, task.completed_by
+                HAVING {having}"
+            ))
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSetting::Table)
+                    .drop_column(GuildSetting::BadgeAnnouncements)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(BadgeRole::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Badge::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Badge {
+    Table,
+    DiscordGuildId,
+    User,
+    Kind,
+    Period,
+    EarnedAt,
+    Request,
+}
+
+#[derive(DeriveIden)]
+enum BadgeRole {
+    Table,
+    DiscordGuildId,
+    Kind,
+    DiscordRoleId,
+}
+
+#[derive(DeriveIden)]
+enum GuildSetting {
+    Table,
+    BadgeAnnouncements,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
@@ -0,0 +1,135 @@
+use sea_orm_migration::prelude::*;
+
+/// Quips that are available in every guild, which used to be built into the bot
+const BUILT_IN_QUIPS: &[&str] = &[
+    "Remember: There is no shadow council",
+    "Have you driven over Nautilus today?",
+    "quini bozo",
+    "Powered by your hopes and dreams... delicious!",
+    "9 out of 10 doctors recommend a daily diet of at least 10 rmats",
+    "Break war BTW",
+    "Almost as good as the old request bot",
+    "Instructions unclear? Try reading them bottom-up!",
+    "T2 will tech in 15 minutes",
+    "Not sponsored by cryptocurrency gambling",
+    "Abandoned Ward has been lost to the colonials",
+    "F",
+    "This command has failed successfully",
+    "Kingstone is under attack",
+    "Nuke Jade Cove",
+    "QRF Deez Nutz",
+    "You got any Delvins?",
+    "SCOPE CREEP",
+    "Daily reminder to press W",
+    "Daily reminder to set your MPF queues",
+    "Sledges will tech in 15 minutes",
+    "And our MPF champion is... CRIPPLING DEPRESSION!",
+];
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Quip::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Quip::Id)
+                            .uuid()
+                            .not_null()
+                            .default(PgFunc::gen_random_uuid())
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Quip::DiscordGuildId).big_unsigned())
+                    .col(ColumnDef::new(Quip::Pack).string().not_null())
+                    .col(ColumnDef::new(Quip::Text).string().not_null())
+                    .col(ColumnDef::new(Quip::Weight).integer().not_null().default(1))
+                    // As month * 100 + day, so that seasons recur every year
+                    .col(ColumnDef::new(Quip::AvailableFrom).small_integer())
+                    .col(ColumnDef::new(Quip::AvailableUntil).small_integer())
+                    .index(
+                        Index::create()
+                            .unique()
+                            .col(Quip::DiscordGuildId)
+                            .col(Quip::Text),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .add_column(ColumnDef::new(Request::Quip).string())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSetting::Table)
+                    .add_column(ColumnDef::new(GuildSetting::QuipPacks).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        let mut seed = Query::insert()
+            .into_table(Quip::Table)
+            .columns([Quip::Pack, Quip::Text])
+            .to_owned();
+        for text in BUILT_IN_QUIPS {
+            seed.values_panic(["default".into(), (*text).into()]);
+        }
+        manager.exec_stmt(seed).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(GuildSetting::Table)
+                    .drop_column(GuildSetting::QuipPacks)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .drop_column(Request::Quip)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Quip::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Quip {
+    Table,
+    Id,
+    DiscordGuildId,
+    Pack,
+    Text,
+    Weight,
+    AvailableFrom,
+    AvailableUntil,
+}
+
+#[derive(DeriveIden)]
+enum Request {
+    Table,
+    Quip,
+}
+
+#[derive(DeriveIden)]
+enum GuildSetting {
+    Table,
+    QuipPacks,
+}
@@ -0,0 +1,86 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RequestRenderHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RequestRenderHistory::Id)
+                            .uuid()
+                            .not_null()
+                            .default(PgFunc::gen_random_uuid())
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(RequestRenderHistory::Request)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(RequestRenderHistory::Page)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(RequestRenderHistory::RenderedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(RequestRenderHistory::Snapshot)
+                            .string()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(RequestRenderHistory::Table)
+                            .from_col(RequestRenderHistory::Request)
+                            .to_tbl(Request::Table)
+                            .to_col(Request::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("request_render_history_request_page_rendered_at_idx")
+                    .table(RequestRenderHistory::Table)
+                    .col(RequestRenderHistory::Request)
+                    .col(RequestRenderHistory::Page)
+                    .col(RequestRenderHistory::RenderedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RequestRenderHistory::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RequestRenderHistory {
+    Table,
+    Id,
+    Request,
+    Page,
+    RenderedAt,
+    Snapshot,
+}
+
+#[derive(DeriveIden)]
+enum Request {
+    Table,
+    Id,
+}
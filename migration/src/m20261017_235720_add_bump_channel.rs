@@ -0,0 +1,79 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(BumpChannel::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(BumpChannel::DiscordChannelId)
+                            .big_unsigned()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(BumpChannel::DiscordGuildId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(BumpChannel::BumpAfterDays)
+                            .integer()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("bump_channel_discord_guild_id_idx")
+                    .table(BumpChannel::Table)
+                    .col(BumpChannel::DiscordGuildId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .add_column(ColumnDef::new(Request::BumpedAt).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Request::Table)
+                    .drop_column(Request::BumpedAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(BumpChannel::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BumpChannel {
+    Table,
+    DiscordChannelId,
+    DiscordGuildId,
+    BumpAfterDays,
+}
+
+#[derive(DeriveIden)]
+enum Request {
+    Table,
+    BumpedAt,
+}
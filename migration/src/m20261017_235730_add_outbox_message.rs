@@ -0,0 +1,95 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(OutboxMessage::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OutboxMessage::Id)
+                            .uuid()
+                            .not_null()
+                            .default(PgFunc::gen_random_uuid())
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(OutboxMessage::Request).uuid().not_null())
+                    .col(
+                        ColumnDef::new(OutboxMessage::DiscordChannelId)
+                            .big_unsigned()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OutboxMessage::Payload)
+                            .json_binary()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OutboxMessage::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(OutboxMessage::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(OutboxMessage::NextAttemptAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(OutboxMessage::LastError).string())
+                    .foreign_key(
+                        ForeignKeyCreateStatement::new()
+                            .from_tbl(OutboxMessage::Table)
+                            .from_col(OutboxMessage::Request)
+                            .to_tbl(Request::Table)
+                            .to_col(Request::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("outbox_message_next_attempt_at_idx")
+                    .table(OutboxMessage::Table)
+                    .col(OutboxMessage::NextAttemptAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OutboxMessage::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum OutboxMessage {
+    Table,
+    Id,
+    Request,
+    DiscordChannelId,
+    Payload,
+    CreatedAt,
+    Attempts,
+    NextAttemptAt,
+    LastError,
+}
+
+#[derive(DeriveIden)]
+enum Request {
+    Table,
+    Id,
+}
@@ -0,0 +1,80 @@
+//! Fills in what older versions of the bot didn't record, see the `backfill-*` subcommands
+
+use entity::{
+    discord_id::{kind, DiscordId},
+    request,
+};
+use sea_orm::{
+    sea_query::Expr, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QuerySelect,
+};
+use serenity::model::id::ChannelId;
+
+use crate::{
+    discord_api::DiscordApi,
+    discord_ids::{FromDiscordId, ToDiscordId},
+    expiration_controller::Partition,
+};
+
+/// What [`guilds`] did
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct GuildsBackfilled {
+    /// How many requests had their server filled in
+    pub filled: u64,
+    /// Channels whose server couldn't be found, with how many requests are left without one there
+    pub unresolved: Vec<(ChannelId, u64)>,
+}
+
+/// Fills in the servers of the partition's requests that were made before servers were recorded
+///
+/// Requests in channels that the bot can no longer see (or in DMs) are left alone, and reported
+/// in [`GuildsBackfilled::unresolved`].
+pub async fn guilds(
+    db: &DatabaseConnection,
+    discord: &dyn DiscordApi,
+    partition: &Partition,
+) -> Result<GuildsBackfilled, DbErr> {
+    let channels = request::Entity::find()
+        .select_only()
+        .column(request::Column::DiscordChannelId)
+        .column_as(Expr::col(request::Column::Id).count(), "requests")
+        .filter(request::Column::DiscordGuildId.is_null())
+        .filter(request::Column::DiscordChannelId.is_not_null())
+        .filter(partition.condition())
+        .group_by(request::Column::DiscordChannelId)
+        .into_tuple::<(DiscordId<kind::Channel>, i64)>()
+        .all(db)
+        .await?;
+    let mut backfilled = GuildsBackfilled::default();
+    for (channel, requests) in channels {
+        let guild = match discord.get_channel_guild(channel.discord()).await {
+            Ok(Some(guild)) => guild,
+            // DMs have no server to fill in
+            Ok(None) => {
+                backfilled
+                    .unresolved
+                    .push((channel.discord(), requests as u64));
+                continue;
+            }
+            Err(err) => {
+                tracing::warn!(
+                    error = &err as &dyn std::error::Error,
+                    %channel,
+                    "failed to find the server of a channel, skipping its requests"
+                );
+                backfilled
+                    .unresolved
+                    .push((channel.discord(), requests as u64));
+                continue;
+            }
+        };
+        backfilled.filled += request::Entity::update_many()
+            .col_expr(request::Column::DiscordGuildId, Expr::value(guild.db_id()))
+            .filter(request::Column::DiscordGuildId.is_null())
+            .filter(request::Column::DiscordChannelId.eq(channel))
+            .filter(partition.condition())
+            .exec(db)
+            .await?
+            .rows_affected;
+    }
+    Ok(backfilled)
+}
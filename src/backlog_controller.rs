@@ -0,0 +1,242 @@
+//! Warns a server's leadership when logistics is falling behind
+//!
+//! Servers pick limits with `/server-backlog-warning`, on how many requests may be open at once and
+//! on how long the oldest open request may have been open. Once either is exceeded, a warning is
+//! posted to the server's feed channel (or its report channel, if it has no feed). Each limit is
+//! only warned about once, until the backlog is back under it.
+
+use std::{collections::HashSet, time::Duration};
+
+use entity::{
+    discord_id::{kind, DiscordId},
+    guild_setting, request,
+};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+};
+use time::OffsetDateTime;
+
+use crate::{
+    backoff::{self, Backoff},
+    discord_api::{self, DiscordApi},
+    discord_ids::FromDiscordId,
+    expiration_controller::Partition,
+    leader, stats,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// A limit that a server's backlog can exceed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Limit {
+    /// More than this many requests are open
+    Count(i32),
+    /// A request has been open for longer than this many seconds
+    Age(i64),
+}
+
+pub async fn run(db: &DatabaseConnection, discord: &dyn DiscordApi, partition: &Partition) {
+    // Keyed by the limit too, so that changing it warns again
+    let mut warned = HashSet::new();
+    let mut backoff = Backoff::new(POLL_INTERVAL, backoff::MAX_RETRY_DELAY);
+    loop {
+        let delay = match run_turn(db, discord, partition, &mut warned).await {
+            Ok(()) => {
+                backoff.succeeded();
+                POLL_INTERVAL
+            }
+            Err(err) => {
+                let delay = backoff.failed();
+                tracing::error!(error = &err as &dyn std::error::Error, retry_in = ?delay, "failed to check request backlogs, retrying...");
+                delay
+            }
+        };
+        tokio::time::sleep(delay).await;
+    }
+}
+
+async fn run_turn(
+    db: &DatabaseConnection,
+    discord: &dyn DiscordApi,
+    partition: &Partition,
+    warned: &mut HashSet<(DiscordId<kind::Guild>, Limit)>,
+) -> Result<(), DbErr> {
+    let Some(leadership) =
+        leader::try_lead(db, leader::Controller::Backlog, partition.application_id.0).await?
+    else {
+        return Ok(());
+    };
+    let now = OffsetDateTime::now_utc();
+    let settings = guild_setting::Entity::find()
+        .filter(
+            guild_setting::Column::BacklogWarnCount
+                .is_not_null()
+                .or(guild_setting::Column::BacklogWarnAgeSecs.is_not_null()),
+        )
+        .all(db)
+        .await?;
+    let mut still_exceeded = HashSet::new();
+    for settings in settings {
+        let guild = settings.discord_guild_id;
+        let Some(channel) = settings.feed_channel.or(settings.report_channel) else {
+            continue;
+        };
+        let open_requests = request::Entity::find()
+            .filter(request::Column::ArchivedOn.is_null())
+            .filter(request::Column::DiscordGuildId.eq(guild))
+            .filter(partition.condition());
+        let open = open_requests.clone().count(db).await?;
+        let oldest = open_requests
+            .order_by_asc(request::Column::CreatedAt)
+            .one(db)
+            .await?
+            .map(|req| req.created_at);
+        let exceeded = exceeded_limits(&settings, open, oldest, now);
+        let new = exceeded
+            .iter()
+            .filter(|limit| !warned.contains(&(guild, **limit)))
+            .count();
+        still_exceeded.extend(exceeded.iter().map(|limit| (guild, *limit)));
+        if new == 0 {
+            continue;
+        }
+        let channel = channel.discord();
+        let content = warning(&exceeded, open, oldest);
+        match discord
+            .send_message(
+                channel,
+                discord_api::create_message(|msg| {
+                    msg.content(content)
+                        .allowed_mentions(|mentions| mentions.empty_parse())
+                }),
+            )
+            .await
+        {
+            Ok(_) => {
+                warned.extend(exceeded.iter().map(|limit| (guild, *limit)));
+            }
+            Err(err) => {
+                tracing::error!(error = &err as &dyn std::error::Error, guild.id = %guild, %channel, "failed to warn about request backlog, retrying later...");
+            }
+        }
+    }
+    // Backlogs that were worked through are warned about again the next time they pile up
+    warned.retain(|key| still_exceeded.contains(key));
+    leadership.release().await
+}
+
+/// The server's limits that are exceeded by `open` requests, the oldest of which was created at
+/// `oldest`
+fn exceeded_limits(
+    settings: &guild_setting::Model,
+    open: u64,
+    oldest: Option<OffsetDateTime>,
+    now: OffsetDateTime,
+) -> Vec<Limit> {
+    let mut exceeded = Vec::new();
+    if let Some(count) = settings.backlog_warn_count {
+        if open > count as u64 {
+            exceeded.push(Limit::Count(count));
+        }
+    }
+    if let Some((age_secs, oldest)) = settings.backlog_warn_age_secs.zip(oldest) {
+        if now - oldest > time::Duration::seconds(age_secs) {
+            exceeded.push(Limit::Age(age_secs));
+        }
+    }
+    exceeded
+}
+
+fn warning(exceeded: &[Limit], open: u64, oldest: Option<OffsetDateTime>) -> String {
+    let mut content = "⚠️ Logistics is falling behind".to_string();
+    for limit in exceeded {
+        match limit {
+            Limit::Count(count) => {
+                content +=
+                    &format!("\n- **{open}** requests are open, more than the {count} allowed");
+            }
+            Limit::Age(age_secs) => {
+                if let Some(oldest) = oldest {
+                    content += &format!(
+                        "\n- The oldest open request was made <t:{}:R>, longer ago than the {} allowed",
+                        oldest.unix_timestamp(),
+                        stats::format_time_to_completion(time::Duration::seconds(*age_secs)),
+                    );
+                }
+            }
+        }
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use entity::{discord_id::DiscordId, guild_setting};
+    use time::{Duration, OffsetDateTime};
+
+    use super::{exceeded_limits, warning, Limit};
+
+    fn settings(count: Option<i32>, age: Option<Duration>) -> guild_setting::Model {
+        guild_setting::Model {
+            discord_guild_id: DiscordId::new(10),
+            time_zone: None,
+            quick_claim_emoji: None,
+            default_expires_in_secs: None,
+            palette: None,
+            report_channel: None,
+            confirm_completion: None,
+            feed_channel: Some(DiscordId::new(20)),
+            target_completion_secs: None,
+            thank_contributors: None,
+            target_stockpile: None,
+            faction: None,
+            region_loss: None,
+            backlog_warn_count: count,
+            backlog_warn_age_secs: age.map(|age| age.whole_seconds()),
+            notify_webhook_url: None,
+            plain_rendering: None,
+            idle_alert_secs: None,
+            claim_capacity: None,
+            season_length: None,
+            badge_announcements: None,
+            frozen_at: None,
+            freeze_notice: None,
+            quip_packs: None,
+            require_completion_evidence: None,
+            content_filter_words: None,
+            content_filter_mode: None,
+        }
+    }
+
+    #[test]
+    fn backlogs_over_either_limit_are_warned_about() {
+        let now = OffsetDateTime::UNIX_EPOCH + Duration::days(10);
+        let settings = settings(Some(20), Some(Duration::days(2)));
+        assert_eq!(exceeded_limits(&settings, 20, Some(now), now), vec![]);
+        assert_eq!(
+            exceeded_limits(&settings, 21, Some(now), now),
+            vec![Limit::Count(20)]
+        );
+        assert_eq!(
+            exceeded_limits(&settings, 1, Some(now - Duration::days(3)), now),
+            vec![Limit::Age(2 * 24 * 60 * 60)]
+        );
+        // Servers without open requests have no oldest request
+        assert_eq!(exceeded_limits(&settings, 0, None, now), vec![]);
+    }
+
+    #[test]
+    fn warnings_list_every_exceeded_limit() {
+        let oldest = OffsetDateTime::UNIX_EPOCH;
+        assert_eq!(
+            warning(
+                &[Limit::Count(20), Limit::Age(2 * 24 * 60 * 60)],
+                25,
+                Some(oldest)
+            ),
+            "⚠️ Logistics is falling behind\n\
+             - **25** requests are open, more than the 20 allowed\n\
+             - The oldest open request was made <t:0:R>, longer ago than the 2days allowed"
+        );
+    }
+}
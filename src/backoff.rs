@@ -0,0 +1,52 @@
+//! Exponential backoff for background controllers, so that they keep retrying through a database
+//! outage without hammering it
+
+use std::time::Duration;
+
+/// The longest that controllers wait between retries
+pub const MAX_RETRY_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// Delays that start at `initial` and double after every failure, up to `max`
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    next: Duration,
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            next: initial,
+        }
+    }
+
+    /// How long to wait before retrying after a failure
+    pub fn failed(&mut self) -> Duration {
+        let delay = self.next;
+        self.next = (self.next * 2).min(self.max);
+        delay
+    }
+
+    /// Starts over from `initial` after a success
+    pub fn succeeded(&mut self) {
+        self.next = self.initial;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Backoff;
+
+    #[test]
+    fn doubles_until_max_and_resets() {
+        let mut backoff = Backoff::new(Duration::from_secs(10), Duration::from_secs(60));
+        let delays = (0..4).map(|_| backoff.failed()).collect::<Vec<_>>();
+        assert_eq!(delays, [10, 20, 40, 60].map(Duration::from_secs));
+        backoff.succeeded();
+        assert_eq!(backoff.failed(), Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,269 @@
+//! Awards badges to contributors as they earn them, see [`crate::badges`]
+//!
+//! Milestones are awarded once the contributor has completed enough tasks in the server. The fastest
+//! request of each week is awarded once the week is over, to whoever completed its last task.
+//! Forgotten users are never awarded anything, since they are all credited to the same tombstone.
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use entity::{
+    badge, badge_role,
+    discord_id::{kind, DiscordId},
+    guild_setting, request, task, user,
+};
+use sea_orm::{
+    prelude::Uuid, sea_query::OnConflict, ActiveValue::Set, ColumnTrait, DatabaseConnection, DbErr,
+    EntityTrait, QueryFilter, QuerySelect,
+};
+use strum::IntoEnumIterator;
+use time::OffsetDateTime;
+
+use crate::{
+    backoff::{self, Backoff},
+    badges::{self, Badge},
+    discord_api::{self, DiscordApi},
+    discord_ids::{FromDiscordId, ToDiscordId},
+    expiration_controller::Partition,
+    forget, leader, request_link,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+pub async fn run(db: &DatabaseConnection, discord: &dyn DiscordApi, partition: &Partition) {
+    // The fastest request of a week only needs to be looked for once
+    let mut awarded_week = None;
+    let mut backoff = Backoff::new(POLL_INTERVAL, backoff::MAX_RETRY_DELAY);
+    loop {
+        let delay = match run_turn(db, discord, partition, &mut awarded_week).await {
+            Ok(()) => {
+                backoff.succeeded();
+                POLL_INTERVAL
+            }
+            Err(err) => {
+                let delay = backoff.failed();
+                tracing::error!(error = &err as &dyn std::error::Error, retry_in = ?delay, "failed to award badges, retrying...");
+                delay
+            }
+        };
+        tokio::time::sleep(delay).await;
+    }
+}
+
+pub async fn run_turn(
+    db: &DatabaseConnection,
+    discord: &dyn DiscordApi,
+    partition: &Partition,
+    awarded_week: &mut Option<String>,
+) -> Result<(), DbErr> {
+    let Some(leadership) =
+        leader::try_lead(db, leader::Controller::Badge, partition.application_id.0).await?
+    else {
+        return Ok(());
+    };
+    let now = OffsetDateTime::now_utc();
+    award_milestones(db, discord, now).await?;
+    let (from, until) = badges::last_week(now);
+    let week = badges::week_name(from.date());
+    if awarded_week.as_ref() != Some(&week) {
+        award_fastest(db, discord, &week, from, until, now).await?;
+        *awarded_week = Some(week);
+    }
+    leadership.release().await
+}
+
+async fn award_milestones(
+    db: &DatabaseConnection,
+    discord: &dyn DiscordApi,
+    now: OffsetDateTime,
+) -> Result<(), DbErr> {
+    let completions = task::Entity::find()
+        .select_only()
+        .column(request::Column::DiscordGuildId)
+        .column(task::Column::CompletedBy)
+        .inner_join(request::Entity)
+        .filter(request::Column::DiscordGuildId.is_not_null())
+        .filter(request::Column::MergedInto.is_null())
+        .filter(task::Column::CompletedBy.is_not_null())
+        .filter(task::Column::CompletedAt.is_not_null())
+        .filter(task::Column::MovedTo.is_null())
+        .filter(task::Column::RemovedAt.is_null())
+        .into_tuple::<(DiscordId<kind::Guild>, Uuid)>()
+        .all(db)
+        .await?;
+    let mut counts = HashMap::<_, u64>::new();
+    for completion in completions {
+        *counts.entry(completion).or_default() += 1;
+    }
+    let milestones = Badge::iter()
+        .filter(|badge| badge.milestone().is_some())
+        .collect::<Vec<_>>();
+    let earned = badge::Entity::find()
+        .filter(badge::Column::Kind.is_in(milestones.iter().map(|badge| badge.as_ref())))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|badge| (badge.discord_guild_id, badge.user, badge.kind))
+        .collect::<HashSet<_>>();
+    for ((guild, user), completed) in counts {
+        for &badge in &milestones {
+            if badge
+                .milestone()
+                .is_some_and(|milestone| completed >= milestone)
+                && !earned.contains(&(guild, user, badge.as_ref().to_string()))
+            {
+                award(db, discord, guild, user, badge, "", None, now).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn award_fastest(
+    db: &DatabaseConnection,
+    discord: &dyn DiscordApi,
+    week: &str,
+    from: OffsetDateTime,
+    until: OffsetDateTime,
+    now: OffsetDateTime,
+) -> Result<(), DbErr> {
+    let awarded = badge::Entity::find()
+        .filter(badge::Column::Kind.eq(Badge::FastestOfWeek.as_ref()))
+        .filter(badge::Column::Period.eq(week))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|badge| badge.discord_guild_id)
+        .collect::<HashSet<_>>();
+    // Requests are archived once they are completed, so anything completed last week was archived
+    // since it started
+    let mut requests_by_guild = HashMap::<_, Vec<_>>::new();
+    for (request, tasks) in request::Entity::find()
+        .filter(request::Column::DiscordGuildId.is_not_null())
+        .filter(request::Column::ArchivedOn.gte(from))
+        .find_with_related(task::Entity)
+        .all(db)
+        .await?
+    {
+        if let Some(guild) = request.discord_guild_id {
+            requests_by_guild
+                .entry(guild)
+                .or_default()
+                .push((request, tasks));
+        }
+    }
+    for (guild, requests) in requests_by_guild {
+        if awarded.contains(&guild) {
+            continue;
+        }
+        if let Some((request, finisher)) = badges::fastest(&requests, from, until) {
+            award(
+                db,
+                discord,
+                guild,
+                finisher,
+                Badge::FastestOfWeek,
+                week,
+                Some(request),
+                now,
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Awards `badge` to `user`, unless they already have it for the `period`
+///
+/// Granting the badge's role and congratulating them are best-effort, since the badge has already
+/// been awarded by then.
+#[allow(clippy::too_many_arguments)]
+async fn award(
+    db: &DatabaseConnection,
+    discord: &dyn DiscordApi,
+    guild: DiscordId<kind::Guild>,
+    user: Uuid,
+    badge: Badge,
+    period: &str,
+    request: Option<&request::Model>,
+    now: OffsetDateTime,
+) -> Result<(), DbErr> {
+    let Some(user_model) = user::Entity::find_by_id(user).one(db).await? else {
+        return Ok(());
+    };
+    if user_model.discord_user_id == forget::TOMBSTONE.db_id() {
+        return Ok(());
+    }
+    // Every bot in the server awards badges, so only the one that inserts it goes on to announce it
+    let inserted = badge::Entity::insert(badge::ActiveModel {
+        discord_guild_id: Set(guild),
+        user: Set(user),
+        kind: Set(badge.as_ref().to_string()),
+        period: Set(period.to_string()),
+        earned_at: Set(now),
+        request: Set(request.map(|request| request.id)),
+    })
+    .on_conflict(
+        OnConflict::columns([
+            badge::Column::DiscordGuildId,
+            badge::Column::User,
+            badge::Column::Kind,
+            badge::Column::Period,
+        ])
+        .do_nothing()
+        .to_owned(),
+    )
+    .exec_without_returning(db)
+    .await?;
+    if inserted == 0 {
+        return Ok(());
+    }
+    let discord_user = user_model.discord_user_id.discord();
+    tracing::info!(guild.id = %guild, user.id = %discord_user, badge = badge.as_ref(), period, "badge awarded");
+    if let Some(role) = badge_role::Entity::find_by_id((guild, badge.as_ref().to_string()))
+        .one(db)
+        .await?
+    {
+        if let Err(err) = discord
+            .add_member_role(
+                guild.discord(),
+                discord_user,
+                role.discord_role_id.discord(),
+            )
+            .await
+        {
+            tracing::warn!(error = &err as &dyn std::error::Error, guild.id = %guild, user.id = %discord_user, "failed to grant badge role, ignoring...");
+        }
+    }
+    let Some(settings) = guild_setting::Entity::find_by_id(guild).one(db).await? else {
+        return Ok(());
+    };
+    if settings.badge_announcements != Some(true) {
+        return Ok(());
+    }
+    let Some(channel) = settings.feed_channel.or(settings.report_channel) else {
+        return Ok(());
+    };
+    let link =
+        request.map(|request| request_link(request).unwrap_or_else(|| request.title.clone()));
+    let channel = channel.discord();
+    if let Err(err) = discord
+        .send_message(
+            channel,
+            discord_api::create_message(|msg| {
+                msg.content(badges::congratulation(
+                    badge,
+                    user_model.discord_user_id,
+                    link.as_deref(),
+                ))
+                .allowed_mentions(|mentions| mentions.empty_parse())
+            }),
+        )
+        .await
+    {
+        tracing::warn!(error = &err as &dyn std::error::Error, guild.id = %guild, %channel, "failed to congratulate on badge, ignoring...");
+    }
+    Ok(())
+}
@@ -0,0 +1,176 @@
+//! Badges that contributors earn for their milestones in a server, see `/server-badges`
+//!
+//! Badges are awarded by [`crate::badge_controller`]. Servers can have the bot congratulate whoever
+//! earns one in their feed channel, and grant a role for each kind of badge. Roles are only granted
+//! as badges are earned, so picking a role for a badge doesn't grant it to those who already have
+//! the badge.
+
+use entity::{
+    discord_id::{kind, DiscordId},
+    request, task,
+};
+use sea_orm::prelude::Uuid;
+use time::{Date, Duration, OffsetDateTime};
+
+use crate::stats;
+
+/// What a badge is awarded for
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, strum::AsRefStr, strum::EnumIter, strum::EnumString,
+)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Badge {
+    /// Completed their first task in the server
+    FirstCompletion,
+    /// Completed 100 tasks in the server
+    HundredTasks,
+    /// Completed the request that was completed the fastest in a week
+    FastestOfWeek,
+}
+
+impl Badge {
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::FirstCompletion => "First delivery",
+            Self::HundredTasks => "Hundred tasks",
+            Self::FastestOfWeek => "Fastest of the week",
+        }
+    }
+
+    /// How many completed tasks earn the badge, if it is a milestone
+    pub fn milestone(self) -> Option<u64> {
+        match self {
+            Self::FirstCompletion => Some(1),
+            Self::HundredTasks => Some(100),
+            Self::FastestOfWeek => None,
+        }
+    }
+}
+
+/// The week that started on Monday before the one that `now` is in, as (start, end), in UTC
+pub fn last_week(now: OffsetDateTime) -> (OffsetDateTime, OffsetDateTime) {
+    let today = now.date();
+    let this_week = today - Duration::days(today.weekday().number_days_from_monday().into());
+    let last_week = this_week - Duration::weeks(1);
+    (
+        last_week.midnight().assume_utc(),
+        this_week.midnight().assume_utc(),
+    )
+}
+
+/// The name of the week that `date` is in, such as `2026-W42`, which weekly badges are awarded for
+pub fn week_name(date: Date) -> String {
+    let (year, week, _) = date.to_iso_week_date();
+    format!("{year}-W{week:02}")
+}
+
+/// The request that was completed the fastest between `from` and `until`, along with whoever
+/// completed its last task
+pub fn fastest<'a>(
+    requests: &'a [(request::Model, Vec<task::Model>)],
+    from: OffsetDateTime,
+    until: OffsetDateTime,
+) -> Option<(&'a request::Model, Uuid)> {
+    requests
+        .iter()
+        .filter_map(|(request, tasks)| {
+            let completed_at = stats::completed_at(request, tasks)?;
+            if completed_at < from || completed_at >= until {
+                return None;
+            }
+            let finisher = tasks
+                .iter()
+                .filter(|task| task.moved_to.is_none() && task.removed_at.is_none())
+                .filter(|task| task.completed_at == Some(completed_at))
+                .find_map(|task| task.completed_by)?;
+            Some((completed_at - request.created_at, request, finisher))
+        })
+        .min_by_key(|(time_to_completion, request, _)| (*time_to_completion, request.id))
+        .map(|(_, request, finisher)| (request, finisher))
+}
+
+/// Congratulates `user` for earning `badge`, for the request that earned it, if any
+pub fn congratulation(badge: Badge, user: DiscordId<kind::User>, request: Option<&str>) -> String {
+    let reason = match badge {
+        Badge::FirstCompletion => "completing their first task in the server".to_string(),
+        Badge::HundredTasks => "completing 100 tasks in the server".to_string(),
+        Badge::FastestOfWeek => format!(
+            "completing {} faster than any other request last week",
+            request.unwrap_or("a request")
+        ),
+    };
+    format!(
+        "🎖️ <@{user}> earned the **{}** badge, for {reason}!",
+        badge.title()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use entity::{request, task};
+    use sea_orm::prelude::Uuid;
+    use time::{macros::datetime, Duration, OffsetDateTime};
+
+    use super::{fastest, last_week, week_name};
+    use crate::testing;
+
+    fn request(id: u128, created_at: OffsetDateTime) -> request::Model {
+        request::Model {
+            created_at,
+            updated_at: created_at,
+            ..testing::request(id)
+        }
+    }
+
+    fn task(
+        request: &request::Model,
+        completed_at: OffsetDateTime,
+        completed_by: u128,
+    ) -> task::Model {
+        task::Model {
+            completed_at: Some(completed_at),
+            completed_by: Some(Uuid::from_u128(completed_by)),
+            updated_at: completed_at,
+            ..testing::task(request, 0)
+        }
+    }
+
+    #[test]
+    fn weeks_start_on_monday() {
+        // A Wednesday
+        let (from, until) = last_week(datetime!(2026-10-14 12:00 UTC));
+        assert_eq!(from, datetime!(2026-10-05 00:00 UTC));
+        assert_eq!(until, datetime!(2026-10-12 00:00 UTC));
+        assert_eq!(week_name(from.date()), "2026-W41");
+    }
+
+    #[test]
+    fn fastest_request_is_credited_to_its_finisher() {
+        let (from, until) = last_week(datetime!(2026-10-14 12:00 UTC));
+        let slow = request(1, from);
+        let fast = request(2, from + Duration::days(1));
+        let too_late = request(3, until - Duration::minutes(5));
+        let requests = [
+            (
+                slow.clone(),
+                vec![task(&slow, from + Duration::hours(5), 10)],
+            ),
+            (
+                fast.clone(),
+                vec![
+                    task(&fast, fast.created_at + Duration::minutes(10), 11),
+                    task(&fast, fast.created_at + Duration::hours(1), 12),
+                ],
+            ),
+            // Completed fastest of all, but only this week
+            (
+                too_late.clone(),
+                vec![task(&too_late, until + Duration::minutes(1), 13)],
+            ),
+        ];
+        let (request, finisher) = fastest(&requests, from, until).unwrap();
+        assert_eq!(request.id, fast.id);
+        assert_eq!(finisher, Uuid::from_u128(12));
+        assert!(fastest(&requests[..0], from, until).is_none());
+    }
+}
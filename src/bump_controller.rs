@@ -0,0 +1,84 @@
+//! Bumps open requests in the channels that ask for it once they are old enough, see [`bumps`]
+//!
+//! Channels pick how many days old a request's message may get with `/request-bumps`. Requests are
+//! measured from when they were last posted, so each request is bumped at most once per interval.
+
+use std::time::Duration;
+
+use entity::{bump_channel, request};
+use sea_orm::{
+    ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+};
+use time::OffsetDateTime;
+
+use crate::{
+    backoff::{self, Backoff},
+    bumps,
+    discord_api::DiscordApi,
+    expiration_controller::Partition,
+    leader,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+pub async fn run(db: &DatabaseConnection, discord: &dyn DiscordApi, partition: &Partition) {
+    let mut backoff = Backoff::new(POLL_INTERVAL, backoff::MAX_RETRY_DELAY);
+    loop {
+        let delay = match run_turn(db, discord, partition).await {
+            Ok(()) => {
+                backoff.succeeded();
+                POLL_INTERVAL
+            }
+            Err(err) => {
+                let delay = backoff.failed();
+                tracing::error!(error = &err as &dyn std::error::Error, retry_in = ?delay, "failed to bump requests, retrying...");
+                delay
+            }
+        };
+        tokio::time::sleep(delay).await;
+    }
+}
+
+pub async fn run_turn(
+    db: &DatabaseConnection,
+    discord: &dyn DiscordApi,
+    partition: &Partition,
+) -> Result<(), DbErr> {
+    let Some(leadership) =
+        leader::try_lead(db, leader::Controller::Bump, partition.application_id.0).await?
+    else {
+        return Ok(());
+    };
+    let now = OffsetDateTime::now_utc();
+    for channel in bump_channel::Entity::find().all(db).await? {
+        // Settings from before the number of days was capped could reach before the start of time
+        let Some(posted_before) =
+            now.checked_sub(time::Duration::days(channel.bump_after_days.into()))
+        else {
+            continue;
+        };
+        let old_requests = request::Entity::find()
+            .filter(request::Column::ArchivedOn.is_null())
+            .filter(request::Column::DiscordChannelId.eq(channel.discord_channel_id))
+            .filter(request::Column::DiscordMessageId.is_not_null())
+            .filter(
+                Condition::any()
+                    .add(request::Column::BumpedAt.lt(posted_before))
+                    .add(
+                        Condition::all()
+                            .add(request::Column::BumpedAt.is_null())
+                            .add(request::Column::CreatedAt.lt(posted_before)),
+                    ),
+            )
+            .filter(partition.condition())
+            .order_by_asc(request::Column::CreatedAt)
+            .all(db)
+            .await?;
+        for req in old_requests {
+            if let Err(err) = bumps::bump(db, discord, &req, now).await {
+                tracing::warn!(error = &err as &dyn std::error::Error, request.id = %req.id, "failed to bump request, retrying later...");
+            }
+        }
+    }
+    leadership.release().await
+}
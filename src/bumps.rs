@@ -0,0 +1,169 @@
+//! Re-posting open requests at the bottom of their channel, so that they stay visible in busy ones
+//!
+//! Channels can have requests bumped once they are a number of days old with `/request-bumps`, see
+//! [`crate::bump_controller`], and requesters can bump their own requests with the "Bump" button.
+//! A bumped request is posted anew before its old messages are deleted, and its message IDs are
+//! swapped in one transaction, so that it is never left without a message if anything fails.
+
+use entity::{request, request_message};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    ModelTrait, QueryFilter, TransactionTrait,
+};
+use serenity::model::id::{ChannelId, MessageId};
+use snafu::{ResultExt, Snafu};
+use time::{Duration, OffsetDateTime};
+
+use crate::{
+    discord_api::{self, DiscordApi},
+    discord_ids::{FromDiscordId, ToDiscordId},
+    permissions, render_request, sync_feed, sync_mirrors, sync_pin,
+};
+
+/// How long a requester has to wait between bumping their request by hand
+pub const MIN_MANUAL_BUMP_INTERVAL: Duration = Duration::hours(1);
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum Error {
+    Database {
+        source: DbErr,
+    },
+    #[snafu(display("not allowed to bump the request"))]
+    Permissions {
+        source: permissions::Error,
+    },
+    #[snafu(display("failed to re-post the request in {channel}"))]
+    SendMessage {
+        source: serenity::Error,
+        channel: ChannelId,
+    },
+    #[snafu(display("failed to delete the request's old message {message}"))]
+    DeleteMessage {
+        source: serenity::Error,
+        message: MessageId,
+    },
+}
+
+/// When the request's current messages were posted, which is what bumping is measured from
+pub fn posted_at(request: &request::Model) -> OffsetDateTime {
+    request.bumped_at.unwrap_or(request.created_at)
+}
+
+/// Re-posts an open request at the bottom of its channel, and deletes its old messages
+///
+/// Returns the new message, or [`None`] if the request isn't posted anywhere or was moved, bumped,
+/// or archived in the meantime, in which case nothing is changed.
+pub async fn bump(
+    db: &DatabaseConnection,
+    api: &dyn DiscordApi,
+    request: &request::Model,
+    now: OffsetDateTime,
+) -> Result<Option<MessageId>, Error> {
+    let (Some(channel), Some(old_message)) =
+        (request.discord_channel_id, request.discord_message_id)
+    else {
+        return Ok(None);
+    };
+    let channel = channel.discord();
+    permissions::ensure(api, channel, permissions::POST.union(permissions::DELETE))
+        .await
+        .context(error::PermissionsSnafu)?;
+
+    let mut messages = Vec::new();
+    for rendered in render_request(db, request.id).await {
+        match api
+            .send_message(
+                channel,
+                discord_api::create_message(|msg| rendered.create_message(msg)),
+            )
+            .await
+        {
+            Ok(message) => messages.push(message),
+            Err(source) => {
+                delete_messages(api, channel, messages).await;
+                return Err(Error::SendMessage { source, channel });
+            }
+        }
+    }
+    let (first, followups) = messages
+        .split_first()
+        .expect("request rendered no messages");
+
+    let txn = db.begin().await.context(error::DatabaseSnafu)?;
+    // Only take over the request if it is still where we found it
+    let bumped = request::Entity::update_many()
+        .set(request::ActiveModel {
+            discord_message_id: Set(Some(first.db_id())),
+            bumped_at: Set(Some(now)),
+            // Pins don't follow the message, the new one is pinned again if the channel wants it
+            pinned: Set(false),
+            ..Default::default()
+        })
+        .filter(request::Column::Id.eq(request.id))
+        .filter(request::Column::DiscordMessageId.eq(old_message))
+        .filter(request::Column::ArchivedOn.is_null())
+        .exec(&txn)
+        .await
+        .context(error::DatabaseSnafu)?;
+    if bumped.rows_affected == 0 {
+        txn.rollback().await.context(error::DatabaseSnafu)?;
+        delete_messages(api, channel, messages).await;
+        return Ok(None);
+    }
+    let old_followups = request
+        .find_related(request_message::Entity)
+        .all(&txn)
+        .await
+        .context(error::DatabaseSnafu)?;
+    request_message::Entity::delete_many()
+        .filter(request_message::Column::Request.eq(request.id))
+        .exec(&txn)
+        .await
+        .context(error::DatabaseSnafu)?;
+    for (i, message) in followups.iter().enumerate() {
+        request_message::ActiveModel {
+            request: Set(request.id),
+            page: Set(i as i32 + 1),
+            discord_channel_id: Set(channel.db_id()),
+            discord_message_id: Set(message.db_id()),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await
+        .context(error::DatabaseSnafu)?;
+    }
+    txn.commit().await.context(error::DatabaseSnafu)?;
+
+    let old_messages = old_followups
+        .iter()
+        .map(|followup| {
+            (
+                followup.discord_channel_id.discord(),
+                followup.discord_message_id.discord(),
+            )
+        })
+        .chain([(channel, old_message.discord())]);
+    for (channel, message) in old_messages {
+        api.delete_message(channel, message)
+            .await
+            .context(error::DeleteMessageSnafu { message })?;
+    }
+    sync_mirrors(db, api, request.id).await;
+    sync_feed(db, api, request.id).await;
+    sync_pin(db, api, request.id).await;
+    Ok(Some(*first))
+}
+
+/// Cleans up the messages of a bump that didn't go through, the request still has its old ones
+async fn delete_messages(api: &dyn DiscordApi, channel: ChannelId, messages: Vec<MessageId>) {
+    for message in messages {
+        if let Err(err) = api.delete_message(channel, message).await {
+            tracing::warn!(
+                error = &err as &dyn std::error::Error,
+                %message,
+                "failed to delete the message of a bump that didn't go through, ignoring..."
+            );
+        }
+    }
+}
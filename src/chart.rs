@@ -0,0 +1,144 @@
+//! Draws [`GuildStats`] as a PNG image that can be attached to a message
+
+use plotters::{backend::BitMapBackendError, prelude::*};
+use snafu::{ResultExt, Snafu};
+
+use crate::stats::GuildStats;
+
+const WIDTH: u32 = 900;
+const HEIGHT: u32 = 900;
+const FONT: &str = "sans-serif";
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum Error {
+    #[snafu(display("failed to draw chart"))]
+    Draw {
+        source: DrawingAreaErrorKind<BitMapBackendError>,
+    },
+    #[snafu(display("failed to encode chart as PNG"))]
+    Encode { source: png::EncodingError },
+}
+
+/// Renders requests per day, average time to completion, and completion rate as stacked charts
+///
+/// If the server has a target time to completion, the share of requests that met it is drawn along
+/// with the completion rate.
+pub fn render(stats: &GuildStats) -> Result<Vec<u8>, Error> {
+    let mut pixels = vec![0; WIDTH as usize * HEIGHT as usize * 3];
+    {
+        let root = BitMapBackend::with_buffer(&mut pixels, (WIDTH, HEIGHT)).into_drawing_area();
+        root.fill(&WHITE).context(error::DrawSnafu)?;
+        let areas = root.split_evenly((3, 1));
+        let days = stats.days.len() as i32;
+        let label = |day: &i32| {
+            stats
+                .days
+                .get(*day as usize)
+                .map_or_else(String::new, |day| {
+                    format!("{}-{:02}", u8::from(day.date.month()), day.date.day())
+                })
+        };
+
+        let created = stats.days.iter().map(|day| day.created).max().unwrap_or(0);
+        let mut chart = ChartBuilder::on(&areas[0])
+            .caption("Requests per day", (FONT, 20))
+            .margin(10)
+            .x_label_area_size(30)
+            .y_label_area_size(40)
+            .build_cartesian_2d(0..days, 0..created as i32 + 1)
+            .context(error::DrawSnafu)?;
+        chart
+            .configure_mesh()
+            .disable_x_mesh()
+            .x_label_formatter(&label)
+            .draw()
+            .context(error::DrawSnafu)?;
+        chart
+            .draw_series(stats.days.iter().enumerate().map(|(i, day)| {
+                let i = i as i32;
+                Rectangle::new([(i, 0), (i + 1, day.created as i32)], BLUE.filled())
+            }))
+            .context(error::DrawSnafu)?;
+
+        let hours = stats
+            .days
+            .iter()
+            .enumerate()
+            .filter_map(|(i, day)| {
+                let time = day.average_time_to_completion?;
+                Some((i as i32, time.as_seconds_f64() / 3600.0))
+            })
+            .collect::<Vec<_>>();
+        let max_hours = hours.iter().map(|(_, hours)| *hours).fold(1.0, f64::max);
+        let mut chart = ChartBuilder::on(&areas[1])
+            .caption("Average time to completion", (FONT, 20))
+            .margin(10)
+            .x_label_area_size(30)
+            .y_label_area_size(40)
+            .build_cartesian_2d(0..days, 0.0..max_hours * 1.1)
+            .context(error::DrawSnafu)?;
+        chart
+            .configure_mesh()
+            .disable_x_mesh()
+            .x_label_formatter(&label)
+            .y_desc("Hours")
+            .draw()
+            .context(error::DrawSnafu)?;
+        chart
+            .draw_series(LineSeries::new(hours, &RED).point_size(3))
+            .context(error::DrawSnafu)?;
+
+        let rates = stats
+            .days
+            .iter()
+            .enumerate()
+            .filter_map(|(i, day)| Some((i as i32, day.completion_rate()? * 100.0)))
+            .collect::<Vec<_>>();
+        let caption = match stats.target_completion {
+            Some(_) => "Completion rate (green) and completed within target (magenta)",
+            None => "Completion rate",
+        };
+        let mut chart = ChartBuilder::on(&areas[2])
+            .caption(caption, (FONT, 20))
+            .margin(10)
+            .x_label_area_size(30)
+            .y_label_area_size(40)
+            .build_cartesian_2d(0..days, 0.0..100.0)
+            .context(error::DrawSnafu)?;
+        chart
+            .configure_mesh()
+            .disable_x_mesh()
+            .x_label_formatter(&label)
+            .y_desc("%")
+            .draw()
+            .context(error::DrawSnafu)?;
+        chart
+            .draw_series(LineSeries::new(rates, &GREEN).point_size(3))
+            .context(error::DrawSnafu)?;
+        if stats.target_completion.is_some() {
+            let attainment = stats
+                .days
+                .iter()
+                .enumerate()
+                .filter_map(|(i, day)| Some((i as i32, day.target_attainment()? * 100.0)))
+                .collect::<Vec<_>>();
+            chart
+                .draw_series(LineSeries::new(attainment, &MAGENTA).point_size(3))
+                .context(error::DrawSnafu)?;
+        }
+
+        root.present().context(error::DrawSnafu)?;
+    }
+
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, WIDTH, HEIGHT);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().context(error::EncodeSnafu)?;
+    writer
+        .write_image_data(&pixels)
+        .context(error::EncodeSnafu)?;
+    writer.finish().context(error::EncodeSnafu)?;
+    Ok(png)
+}
@@ -0,0 +1,214 @@
+//! Brings the application's global commands in line with the ones the bot defines, see
+//! [`crate::register_commands`]
+//!
+//! Overwriting every command at once counts against the rate limits on every start, and Discord
+//! briefly drops the commands while it replaces them. Instead, the registered commands are fetched
+//! and only the ones that differ are created, edited, or deleted, so that an unchanged bot sends
+//! nothing at all.
+
+use serde_json::Value;
+use serenity::http::Http;
+use snafu::{ResultExt, Snafu};
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum Error {
+    #[snafu(display("failed to list the registered commands"))]
+    List { source: serenity::Error },
+    #[snafu(display("failed to serialize registered command {name:?}"))]
+    Serialize {
+        source: serde_json::Error,
+        name: String,
+    },
+    #[snafu(display("failed to create command {name:?}"))]
+    Create {
+        source: serenity::Error,
+        name: String,
+    },
+    #[snafu(display("failed to update command {name:?}"))]
+    Edit {
+        source: serenity::Error,
+        name: String,
+    },
+    #[snafu(display("failed to delete command {name:?}"))]
+    Delete {
+        source: serenity::Error,
+        name: String,
+    },
+}
+
+/// The changes that bring the registered commands in line with the defined ones
+#[derive(Debug, Default, PartialEq)]
+pub struct Plan {
+    pub create: Vec<Value>,
+    /// The IDs of the registered commands to replace, and their new definitions
+    pub edit: Vec<(u64, Value)>,
+    /// The IDs and names of the registered commands that are no longer defined
+    pub delete: Vec<(u64, String)>,
+}
+
+impl Plan {
+    pub fn is_empty(&self) -> bool {
+        self.create.is_empty() && self.edit.is_empty() && self.delete.is_empty()
+    }
+}
+
+/// Commands are told apart by their name and type, since a slash command and a message command
+/// may share a name
+fn key(command: &Value) -> (&str, u64) {
+    (
+        command["name"].as_str().unwrap_or_default(),
+        // Discord treats commands without a type as slash commands
+        command["type"].as_u64().unwrap_or(1),
+    )
+}
+
+/// Works out what to change so that the `registered` commands, as (ID, command), match `defined`
+pub fn plan(registered: &[(u64, Value)], defined: &[Value]) -> Plan {
+    let mut plan = Plan::default();
+    for command in defined {
+        match registered.iter().find(|(_, old)| key(old) == key(command)) {
+            Some((_, old)) if matches(command, old) => (),
+            Some((id, _)) => plan.edit.push((*id, command.clone())),
+            None => plan.create.push(command.clone()),
+        }
+    }
+    for (id, old) in registered {
+        if !defined.iter().any(|command| key(command) == key(old)) {
+            plan.delete.push((*id, key(old).0.to_string()));
+        }
+    }
+    plan
+}
+
+/// Whether the `registered` command (or part of one) already says everything that `defined` does
+///
+/// Discord fills in fields that weren't given, so only the fields of `defined` are compared, and
+/// missing fields count as the same as their empty defaults. Lists must match one for one, so that
+/// removed options are noticed.
+fn matches(defined: &Value, registered: &Value) -> bool {
+    match (defined, registered) {
+        (Value::Object(defined), registered) => defined
+            .iter()
+            .all(|(field, value)| matches(value, registered.get(field).unwrap_or(&Value::Null))),
+        (Value::Array(defined), Value::Array(registered)) => {
+            defined.len() == registered.len()
+                && defined
+                    .iter()
+                    .zip(registered)
+                    .all(|(defined, registered)| matches(defined, registered))
+        }
+        // Discord hands back whole numbers as floats for options such as `min_value`
+        (Value::Number(defined), Value::Number(registered)) => {
+            defined.as_f64() == registered.as_f64()
+        }
+        (defined, Value::Null) => is_empty(defined),
+        (Value::Null, registered) => is_empty(registered),
+        (defined, registered) => defined == registered,
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Bool(false) => true,
+        Value::Array(values) => values.is_empty(),
+        Value::Object(fields) => fields.is_empty(),
+        _ => false,
+    }
+}
+
+/// Applies the changes needed for the application's global commands to match `defined`,
+/// returning what was changed
+pub async fn sync(http: &Http, defined: &[Value]) -> Result<Plan, Error> {
+    let mut registered = Vec::new();
+    for command in http
+        .get_global_application_commands()
+        .await
+        .context(error::ListSnafu)?
+    {
+        let value = serde_json::to_value(&command).context(error::SerializeSnafu {
+            name: &command.name,
+        })?;
+        registered.push((command.id.0, value));
+    }
+    let plan = plan(&registered, defined);
+    for command in &plan.create {
+        http.create_global_application_command(command)
+            .await
+            .context(error::CreateSnafu {
+                name: key(command).0,
+            })?;
+    }
+    for (id, command) in &plan.edit {
+        http.edit_global_application_command(*id, command)
+            .await
+            .context(error::EditSnafu {
+                name: key(command).0,
+            })?;
+    }
+    for (id, name) in &plan.delete {
+        http.delete_global_application_command(*id)
+            .await
+            .context(error::DeleteSnafu { name })?;
+    }
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{plan, Plan};
+
+    fn help(description: &str) -> serde_json::Value {
+        json!({
+            "name": "help",
+            "description": description,
+            "options": [{"name": "page", "type": 3, "required": false}],
+        })
+    }
+
+    #[test]
+    fn unchanged_commands_are_left_alone() {
+        let registered = json!({
+            "id": "1",
+            "name": "help",
+            "type": 1,
+            "description": "Explain the commands",
+            "dm_permission": true,
+            "options": [{"name": "page", "type": 3, "choices": []}],
+        });
+        assert!(plan(&[(1, registered)], &[help("Explain the commands")]).is_empty());
+    }
+
+    #[test]
+    fn only_changed_commands_are_patched() {
+        let timeline = json!({"name": "Show timeline", "type": 3});
+        let registered = [
+            (1, help("Explain the commands")),
+            (
+                2,
+                json!({"name": "scopecreep", "type": 1, "description": "SCOPE CREEP"}),
+            ),
+        ];
+        let defined = [help("Explain every command"), timeline.clone()];
+        assert_eq!(
+            plan(&registered, &defined),
+            Plan {
+                create: vec![timeline],
+                edit: vec![(1, help("Explain every command"))],
+                delete: vec![(2, "scopecreep".to_string())],
+            }
+        );
+    }
+
+    #[test]
+    fn removed_options_are_noticed() {
+        let registered = help("Explain the commands");
+        let defined = json!({"name": "help", "description": "Explain the commands", "options": []});
+        assert_eq!(
+            plan(&[(1, registered)], std::slice::from_ref(&defined)).edit,
+            [(1, defined)]
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! What components act on, carried in their custom IDs
+//!
+//! Components on a request's messages carry the request that they belong to, so that they keep
+//! working wherever the message ends up (such as when it is archived or moved) rather than having
+//! to look the request up by the message that they are attached to. Other components carry whatever
+//! they act on in their argument, such as the report that they resolve.
+//!
+//! Payloads are written as `v2;<component>;<request>;<page>;<arg>`, with empty fields for what the
+//! component doesn't carry. Custom IDs without a version were made before components carried
+//! payloads, as `<component>:<arg>`, and are still understood so that the components on older
+//! messages keep working.
+
+use sea_orm::prelude::Uuid;
+
+/// The version of payloads that is written
+const VERSION: &str = "v2";
+const SEPARATOR: char = ';';
+/// Separates the argument of custom IDs without a version
+const LEGACY_SEPARATOR: char = ':';
+
+/// What a component acts on
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Payload {
+    /// The request that the component belongs to
+    pub request: Option<Uuid>,
+    /// Which of the menus for the same action this is, since they need distinct custom IDs
+    pub page: usize,
+    /// Anything else that the component acts on
+    pub arg: Option<String>,
+}
+
+impl Payload {
+    pub fn request(request: Uuid) -> Self {
+        Self {
+            request: Some(request),
+            ..Self::default()
+        }
+    }
+
+    pub fn arg(arg: impl Into<String>) -> Self {
+        Self {
+            arg: Some(arg.into()),
+            ..Self::default()
+        }
+    }
+}
+
+/// Writes the custom ID of `component` carrying `payload`
+pub fn encode(component: &str, payload: &Payload) -> String {
+    format!(
+        "{VERSION}{SEPARATOR}{component}{SEPARATOR}{}{SEPARATOR}{}{SEPARATOR}{}",
+        payload
+            .request
+            .map_or(String::new(), |request| request.simple().to_string()),
+        payload.page,
+        payload.arg.as_deref().unwrap_or_default()
+    )
+}
+
+/// Reads the component and payload out of a custom ID
+///
+/// Fields that can't be read are left out, the handler decides whether it can do without them.
+pub fn decode(id: &str) -> (&str, Payload) {
+    let mut fields = id.splitn(5, SEPARATOR);
+    if fields.next() != Some(VERSION) {
+        return match id.split_once(LEGACY_SEPARATOR) {
+            Some((component, arg)) => (component, Payload::arg(arg)),
+            None => (id, Payload::default()),
+        };
+    }
+    let component = fields.next().unwrap_or_default();
+    let payload = Payload {
+        request: fields
+            .next()
+            .and_then(|request| Uuid::parse_str(request).ok()),
+        page: fields
+            .next()
+            .and_then(|page| page.parse().ok())
+            .unwrap_or_default(),
+        arg: fields
+            .next()
+            .filter(|arg| !arg.is_empty())
+            .map(str::to_string),
+    };
+    (component, payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::prelude::Uuid;
+
+    use super::{decode, encode, Payload};
+
+    #[test]
+    fn payloads_survive_a_round_trip() {
+        let payload = Payload {
+            request: Some(Uuid::from_u128(0x1234)),
+            page: 1,
+            arg: Some("step:5".to_string()),
+        };
+        let id = encode("ClaimTask", &payload);
+        assert_eq!(id, "v2;ClaimTask;00000000000000000000000000001234;1;step:5");
+        assert!(id.len() <= 100, "custom IDs may be at most 100 characters");
+        assert_eq!(decode(&id), ("ClaimTask", payload));
+        assert_eq!(
+            decode(&encode("AddNote", &Payload::default())),
+            ("AddNote", Payload::default())
+        );
+    }
+
+    #[test]
+    fn ids_without_a_version_are_understood() {
+        assert_eq!(decode("claim-task"), ("claim-task", Payload::default()));
+        assert_eq!(
+            decode("SkipSetupStep:archive:5"),
+            ("SkipSetupStep", Payload::arg("archive:5"))
+        );
+    }
+}
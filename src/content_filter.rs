@@ -0,0 +1,196 @@
+//! Filtering the titles and tasks of requests against each server's own list of words, see
+//! `/server-content-filter`
+//!
+//! Words and phrases match regardless of case, and only as whole words, so that filtering "ass"
+//! leaves "assault" and "bass" alone. Depending on the server's [`Mode`], requests that contain them
+//! are either refused, telling their maker which words to leave out, or go through with the words
+//! masked out. Requests are filtered when they are made and when tasks are added to them.
+
+use std::{collections::BTreeSet, ops::Range, str::FromStr};
+
+use entity::guild_setting;
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait};
+use serenity::model::id::GuildId;
+
+use crate::{discord_ids::ToDiscordId, task_syntax::TaskSpec};
+
+/// What the characters of filtered words are replaced with in [`Mode::Mask`]
+const MASK: char = '#';
+
+/// What happens to requests that contain filtered words
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, strum::AsRefStr, strum::EnumIter, strum::EnumString,
+)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Mode {
+    /// Refuse them, telling their maker which words to leave out
+    #[default]
+    Block,
+    /// Let them through with the words masked out
+    Mask,
+}
+
+/// A server's list of filtered words, and what to do about them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    /// The filtered words and phrases, see [`fold`]
+    words: Vec<Vec<char>>,
+    pub mode: Mode,
+}
+
+/// Lowercases `text` one character at a time, so that positions in it match the original's
+fn fold(text: &str) -> Vec<char> {
+    text.chars()
+        .map(|c| c.to_lowercase().next().unwrap_or(c))
+        .collect()
+}
+
+/// Splits a list of words separated by commas or newlines, as given to `/server-content-filter`
+/// and as stored, into lowercase words
+pub fn parse_words(words: &str) -> impl Iterator<Item = String> + '_ {
+    words
+        .split([',', '\n'])
+        .map(|word| fold(word.trim()).into_iter().collect::<String>())
+        .filter(|word| !word.is_empty())
+}
+
+impl Filter {
+    pub fn new(words: impl IntoIterator<Item = String>, mode: Mode) -> Self {
+        Self {
+            words: words.into_iter().map(|word| fold(&word)).collect(),
+            mode,
+        }
+    }
+
+    /// Finds the filtered words in `text`, as the ranges of characters that they take up
+    fn find(&self, text: &str) -> Vec<(Range<usize>, String)> {
+        let text = fold(text);
+        let is_boundary = |i: usize| text.get(i).map_or(true, |c| !c.is_alphanumeric());
+        let mut found = Vec::new();
+        for word in &self.words {
+            for start in 0..(text.len() + 1).saturating_sub(word.len()) {
+                let end = start + word.len();
+                if text[start..end] == word[..]
+                    && (start == 0 || is_boundary(start - 1))
+                    && is_boundary(end)
+                {
+                    found.push((start..end, word.iter().collect()));
+                }
+            }
+        }
+        found
+    }
+
+    /// Runs the title and tasks of a request past the filter, returning the filtered words that
+    /// they contain
+    ///
+    /// In [`Mode::Mask`], the words are masked out of them as well.
+    pub fn apply(&self, title: &mut String, tasks: &mut [TaskSpec]) -> BTreeSet<String> {
+        let mut caught = BTreeSet::new();
+        let texts = std::iter::once(title).chain(
+            tasks
+                .iter_mut()
+                .flat_map(|task| std::iter::once(&mut task.item).chain(task.section.as_mut())),
+        );
+        for text in texts {
+            let found = self.find(text);
+            if self.mode == Mode::Mask && !found.is_empty() {
+                *text = mask(text, found.iter().map(|(range, _)| range));
+            }
+            caught.extend(found.into_iter().map(|(_, word)| word));
+        }
+        caught
+    }
+}
+
+/// Replaces the characters of `text` in `ranges` with [`MASK`], keeping the spaces between the
+/// words of phrases
+fn mask<'a>(text: &str, ranges: impl IntoIterator<Item = &'a Range<usize>>) -> String {
+    let mut chars = text.chars().collect::<Vec<_>>();
+    for range in ranges {
+        for c in &mut chars[range.clone()] {
+            if !c.is_whitespace() {
+                *c = MASK;
+            }
+        }
+    }
+    chars.into_iter().collect()
+}
+
+/// Lists filtered words for messages, as `` `heck`, `bloody hell` ``
+pub fn list_words<'a>(words: impl IntoIterator<Item = &'a String>) -> String {
+    words
+        .into_iter()
+        .map(|word| format!("`{word}`"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The filtered words and mode that `settings` store
+pub fn from_settings(settings: &guild_setting::Model) -> (Vec<String>, Mode) {
+    let words = settings
+        .content_filter_words
+        .as_deref()
+        .map_or_else(Vec::new, |words| parse_words(words).collect());
+    let mode = settings
+        .content_filter_mode
+        .as_deref()
+        .and_then(|mode| Mode::from_str(mode).ok())
+        .unwrap_or_default();
+    (words, mode)
+}
+
+/// The content filter of `guild`, if it has any words to filter
+pub async fn load(db: &DatabaseConnection, guild: GuildId) -> Result<Option<Filter>, DbErr> {
+    let (words, mode) = guild_setting::Entity::find_by_id(guild.db_id())
+        .one(db)
+        .await?
+        .map(|settings| from_settings(&settings))
+        .unwrap_or_default();
+    Ok((!words.is_empty()).then(|| Filter::new(words, mode)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::task_syntax;
+
+    use super::{parse_words, Filter, Mode};
+
+    fn filter(mode: Mode) -> Filter {
+        Filter::new(parse_words("ass, Heck,\nbloody hell"), mode)
+    }
+
+    #[test]
+    fn only_whole_words_are_caught() {
+        let mut title = "Assault guns for the bass section".to_string();
+        assert!(filter(Mode::Block).apply(&mut title, &mut []).is_empty());
+
+        let mut title = "Get your ASS to the front, what the heck".to_string();
+        assert_eq!(
+            filter(Mode::Block).apply(&mut title, &mut []),
+            ["ass".to_string(), "heck".to_string()].into()
+        );
+        // Blocked requests are refused as they are, so there is nothing to mask
+        assert_eq!(title, "Get your ASS to the front, what the heck");
+    }
+
+    #[test]
+    fn words_are_masked_in_titles_tasks_and_sections() {
+        let mut title = "Bloody hell, more shirts".to_string();
+        let mut tasks = task_syntax::parse("== Heck ==; 40 shirts for ass-kicking; bmats").unwrap();
+        let caught = filter(Mode::Mask).apply(&mut title, &mut tasks);
+        assert_eq!(
+            caught,
+            [
+                "ass".to_string(),
+                "bloody hell".to_string(),
+                "heck".to_string()
+            ]
+            .into()
+        );
+        assert_eq!(title, "###### ####, more shirts");
+        assert_eq!(tasks[0].section.as_deref(), Some("####"));
+        assert_eq!(tasks[0].text(), "40 shirts for ###-kicking");
+        assert_eq!(tasks[1].text(), "bmats");
+    }
+}
@@ -0,0 +1,238 @@
+//! The officers' dashboard, where moderators look over the open requests of their servers across
+//! every channel, edit many of them at once, and see how the servers are doing
+//!
+//! Officers are visitors who can manage messages in a server, going by the servers that Discord
+//! lists for their account when they log in (with the `guilds` OAuth2 scope). The pages are served
+//! by [`crate::web`], this module only deals with the requests themselves.
+
+use std::collections::{HashMap, HashSet};
+
+use entity::{request, task, user};
+use sea_orm::{
+    prelude::Uuid, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+};
+use serde::Deserialize;
+use serenity::model::{id::GuildId, permissions::Permissions};
+use snafu::{ensure, OptionExt, ResultExt, Snafu};
+
+use crate::{
+    discord_api::DiscordApi, discord_ids::ToDiscordId, expire_request, extend_request,
+    request_link, tldr, update_request_messages, EXPIRATION_EXTENSION_HOURS,
+};
+
+/// Whether someone with `permissions` in a server (or who owns it) counts as one of its officers,
+/// the same as the moderator checks of the commands
+pub fn is_officer(owner: bool, permissions: Permissions) -> bool {
+    owner || permissions.administrator() || permissions.manage_messages()
+}
+
+/// A request that hasn't been archived yet, along with how far along it is
+pub struct OpenRequest {
+    pub request: request::Model,
+    pub tasks: usize,
+    pub completed: usize,
+    pub link: Option<String>,
+    /// What the request asks for, see [`tldr`]
+    pub contents: Option<String>,
+    /// [`None`] if the creator was forgotten while the requests were being loaded
+    pub creator: Option<user::Model>,
+}
+
+/// How the dashboard lists requests
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Order {
+    #[default]
+    Oldest,
+    /// The requests whose tasks were last claimed or completed (or that were last edited) first
+    RecentlyActive,
+}
+
+/// Lists the open requests of `guilds` in `order`, after the sticky ones
+pub async fn open_requests(
+    db: &DatabaseConnection,
+    guilds: &[GuildId],
+    order: Order,
+) -> Result<Vec<OpenRequest>, DbErr> {
+    let query = request::Entity::find()
+        .filter(request::Column::DiscordGuildId.is_in(guilds.iter().map(|guild| guild.db_id())))
+        .filter(request::Column::ArchivedOn.is_null())
+        .order_by_desc(request::Column::Sticky);
+    let query = match order {
+        Order::Oldest => query.order_by_asc(request::Column::CreatedAt),
+        Order::RecentlyActive => query.order_by_desc(request::Column::UpdatedAt),
+    };
+    let requests = query.find_with_related(task::Entity).all(db).await?;
+    let creators = user::Entity::find()
+        .filter(
+            user::Column::Id.is_in(
+                requests
+                    .iter()
+                    .map(|(request, _)| request.created_by)
+                    .collect::<HashSet<_>>(),
+            ),
+        )
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|user| (user.id, user))
+        .collect::<HashMap<_, _>>();
+    Ok(requests
+        .into_iter()
+        .map(|(request, tasks)| {
+            let tasks = tasks
+                .iter()
+                .filter(|task| task.moved_to.is_none() && task.removed_at.is_none())
+                .collect::<Vec<_>>();
+            OpenRequest {
+                link: request_link(&request),
+                contents: tldr::summarize(tasks.iter().map(|task| task.task.as_str())),
+                tasks: tasks.len(),
+                completed: tasks
+                    .iter()
+                    .filter(|task| task.completed_at.is_some())
+                    .count(),
+                creator: creators.get(&request.created_by).cloned(),
+                request,
+            }
+        })
+        .collect())
+}
+
+/// Something to do to every request that an officer has picked
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BulkAction {
+    /// Push back when the requests expire, like the buttons under requests
+    Extend { hours: u32 },
+    /// Let the requests expire right away, so that they are archived
+    Expire,
+}
+
+impl BulkAction {
+    /// Every action, along with what it is called in forms
+    pub fn all() -> Vec<(BulkAction, String)> {
+        EXPIRATION_EXTENSION_HOURS
+            .iter()
+            .map(|&hours| BulkAction::Extend { hours })
+            .chain([BulkAction::Expire])
+            .map(|action| (action, action.name()))
+            .collect()
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            BulkAction::Extend { hours } => format!("extend-{hours}"),
+            BulkAction::Expire => "expire".to_string(),
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::all()
+            .into_iter()
+            .find(|(_, action_name)| action_name == name)
+            .map(|(action, _)| action)
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            BulkAction::Extend { hours } => format!("Extend by {hours} hour(s)"),
+            BulkAction::Expire => "Expire now".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum Error {
+    #[snafu(display("the request has already been archived"))]
+    RequestClosed,
+    #[snafu(display("the request doesn't expire"))]
+    NoExpiry,
+    Database {
+        source: DbErr,
+    },
+}
+
+/// Finds the open requests out of `ids` that belong to `guilds`, ignoring any others
+///
+/// Officers may only edit the requests of the servers that they are officers of.
+pub async fn find_requests(
+    db: &DatabaseConnection,
+    ids: &[Uuid],
+    guilds: &[GuildId],
+) -> Result<Vec<request::Model>, DbErr> {
+    request::Entity::find()
+        .filter(request::Column::Id.is_in(ids.iter().copied()))
+        .filter(request::Column::DiscordGuildId.is_in(guilds.iter().map(|guild| guild.db_id())))
+        .filter(request::Column::ArchivedOn.is_null())
+        .order_by_asc(request::Column::CreatedAt)
+        .all(db)
+        .await
+}
+
+/// Applies `action` to `request` on behalf of `officer`, and updates the request's messages to match
+pub async fn apply(
+    db: &DatabaseConnection,
+    api: &dyn DiscordApi,
+    request: &request::Model,
+    officer: &user::Model,
+    action: BulkAction,
+) -> Result<(), Error> {
+    match action {
+        BulkAction::Extend { hours } => {
+            let previous_expires_on = request.expires_on.context(error::NoExpirySnafu)?;
+            extend_request(db, request, previous_expires_on, officer, hours)
+                .await
+                .context(error::DatabaseSnafu)?
+                .context(error::RequestClosedSnafu)?;
+            if let Err(err) = update_request_messages(db, api, request.id, None).await {
+                // The extension is saved either way, the messages catch up on the next change
+                tracing::error!(
+                    error = &err as &dyn std::error::Error,
+                    request.id = %request.id,
+                    "failed to update request messages after extending it from the dashboard"
+                );
+            }
+        }
+        BulkAction::Expire => {
+            let expired = expire_request(db, api, request.id)
+                .await
+                .context(error::DatabaseSnafu)?;
+            ensure!(expired, error::RequestClosedSnafu);
+        }
+    }
+    tracing::info!(
+        request.id = %request.id,
+        user.id = %officer.id,
+        ?action,
+        "edited request from the dashboard"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::model::permissions::Permissions;
+
+    use super::{is_officer, BulkAction};
+
+    #[test]
+    fn officers_can_manage_messages() {
+        assert!(is_officer(false, Permissions::MANAGE_MESSAGES));
+        assert!(is_officer(false, Permissions::ADMINISTRATOR));
+        assert!(is_officer(true, Permissions::empty()));
+        assert!(!is_officer(false, Permissions::SEND_MESSAGES));
+    }
+
+    #[test]
+    fn bulk_actions_round_trip_through_their_names() {
+        for (action, name) in BulkAction::all() {
+            assert_eq!(BulkAction::parse(&name), Some(action));
+        }
+        assert_eq!(
+            BulkAction::parse("extend-6"),
+            Some(BulkAction::Extend { hours: 6 })
+        );
+        assert_eq!(BulkAction::parse("extend-5"), None);
+    }
+}
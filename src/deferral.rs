@@ -0,0 +1,482 @@
+//! The lifecycle of interaction responses, which all interactions that can be deferred go through
+//!
+//! Discord gives up on an interaction ("The application did not respond") if it isn't responded to
+//! within 3 seconds, so [`Deferring`] defers every interaction before its handler runs. After that,
+//! the interaction goes from deferred, to followed up once the handler's first response fills in the
+//! deferred one, with any further responses sent as followups, and edits applied to the original
+//! response throughout. Handlers (and shared helpers like [`crate::archive_request_if_required`])
+//! keep responding as if they were the first, and don't have to know what came before them.
+
+use std::time::{Duration, Instant};
+
+use futures::{lock::Mutex, Future};
+use serenity::{
+    json::Value,
+    model::{
+        application::interaction::InteractionResponseType,
+        channel::ReactionType,
+        id::{ChannelId, EmojiId, GuildId, MessageId, RoleId, UserId},
+        Permissions,
+    },
+};
+
+use crate::{
+    discord_api::{DiscordApi, InteractionRef},
+    user_profile::Profile,
+};
+
+/// Handlers that take longer than this are logged as slow, they'd miss Discord's deadline if they
+/// weren't deferred
+const SLOW_AFTER: Duration = Duration::from_secs(3);
+
+/// Discord's flag for messages that are only visible to the user that triggered the interaction
+const EPHEMERAL: u64 = 1 << 6;
+const CHANNEL_MESSAGE_WITH_SOURCE: u64 = InteractionResponseType::ChannelMessageWithSource as u64;
+const DEFERRED_CHANNEL_MESSAGE_WITH_SOURCE: u64 =
+    InteractionResponseType::DeferredChannelMessageWithSource as u64;
+const DEFERRED_UPDATE_MESSAGE: u64 = InteractionResponseType::DeferredUpdateMessage as u64;
+const UPDATE_MESSAGE: u64 = InteractionResponseType::UpdateMessage as u64;
+
+/// How an interaction is deferred, which has to match how its handler responds
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Deferral {
+    /// The handler responds with a new message
+    Message { ephemeral: bool },
+    /// The handler updates the message that the component is attached to
+    Update,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    /// Not deferred yet, only until [`Deferring::watch`] gets to it
+    Pending,
+    /// Acknowledged, the handler's first response fills in the deferred one
+    Deferred,
+    /// Responded to, any further responses are followups
+    FollowedUp,
+}
+
+/// A [`DiscordApi`] that defers its interaction and keeps track of how it has been responded to
+pub struct Deferring<'a> {
+    api: &'a dyn DiscordApi,
+    interaction: InteractionRef,
+    deferral: Deferral,
+    state: Mutex<State>,
+}
+
+impl<'a> Deferring<'a> {
+    pub fn new(api: &'a dyn DiscordApi, interaction: InteractionRef, deferral: Deferral) -> Self {
+        Self {
+            api,
+            interaction,
+            deferral,
+            state: Mutex::new(State::Pending),
+        }
+    }
+
+    /// Defers the interaction called `name`, and then runs its handler
+    pub async fn watch<T>(&self, name: &str, handler: impl Future<Output = T>) -> T {
+        let started = Instant::now();
+        self.defer(name).await;
+        let output = handler.await;
+        let elapsed = started.elapsed();
+        if elapsed >= SLOW_AFTER {
+            tracing::warn!(interaction = name, ?elapsed, "slow interaction handler");
+        } else {
+            tracing::debug!(interaction = name, ?elapsed, "handled interaction");
+        }
+        output
+    }
+
+    async fn defer(&self, name: &str) {
+        let mut state = self.state.lock().await;
+        let response = match self.deferral {
+            Deferral::Message { ephemeral } => serde_json::json!({
+                "type": DEFERRED_CHANNEL_MESSAGE_WITH_SOURCE,
+                "data": {"flags": if ephemeral { EPHEMERAL } else { 0 }},
+            }),
+            Deferral::Update => serde_json::json!({"type": DEFERRED_UPDATE_MESSAGE}),
+        };
+        match self
+            .api
+            .create_interaction_response(&self.interaction, response)
+            .await
+        {
+            Ok(()) => *state = State::Deferred,
+            // Responses go to Discord as they are then, and it may still be in time for them
+            Err(err) => tracing::warn!(
+                error = &err as &dyn std::error::Error,
+                interaction = name,
+                "failed to defer interaction, ignoring..."
+            ),
+        }
+    }
+
+    /// Sends a new message in response to the interaction
+    async fn respond(&self, state: &mut State, mut data: Value) -> serenity::Result<()> {
+        let ephemeral = data["flags"].as_u64().unwrap_or(0) & EPHEMERAL != 0;
+        match (*state, self.deferral) {
+            (State::Deferred, Deferral::Message { ephemeral: false }) if ephemeral => {
+                // The deferred response is public, so it has to make way for an ephemeral one
+                let original = self
+                    .api
+                    .get_original_interaction_response(&self.interaction)
+                    .await?;
+                self.api
+                    .delete_followup_message(&self.interaction, original)
+                    .await?;
+                self.api
+                    .create_followup_message(&self.interaction, data)
+                    .await?;
+            }
+            (
+                State::Deferred,
+                Deferral::Message {
+                    ephemeral: deferred_ephemeral,
+                },
+            ) => {
+                if deferred_ephemeral && !ephemeral {
+                    tracing::warn!(
+                        "interaction was deferred as ephemeral, its public response won't be"
+                    );
+                }
+                strip_message_only_fields(&mut data);
+                self.api
+                    .edit_original_interaction_response(&self.interaction, data)
+                    .await?;
+            }
+            // Deferred updates leave nothing to fill in
+            (State::Deferred, Deferral::Update) | (State::FollowedUp, _) => {
+                self.api
+                    .create_followup_message(&self.interaction, data)
+                    .await?;
+            }
+            (State::Pending, _) => {
+                self.api
+                    .create_interaction_response(
+                        &self.interaction,
+                        serde_json::json!({"type": CHANNEL_MESSAGE_WITH_SOURCE, "data": data}),
+                    )
+                    .await?;
+            }
+        }
+        *state = State::FollowedUp;
+        Ok(())
+    }
+}
+
+/// Removes the fields of a new message that an edit doesn't accept
+fn strip_message_only_fields(data: &mut Value) {
+    if let Some(data) = data.as_object_mut() {
+        data.remove("flags");
+        data.remove("tts");
+    }
+}
+
+#[serenity::async_trait]
+impl DiscordApi for Deferring<'_> {
+    async fn create_interaction_response(
+        &self,
+        interaction: &InteractionRef,
+        response: Value,
+    ) -> serenity::Result<()> {
+        if interaction.id != self.interaction.id {
+            return self
+                .api
+                .create_interaction_response(interaction, response)
+                .await;
+        }
+        let mut state = self.state.lock().await;
+        let kind = response["type"].as_u64();
+        let mut data = response["data"].clone();
+        match (*state, self.deferral, kind) {
+            (State::Pending, _, _) => {
+                self.api
+                    .create_interaction_response(interaction, response)
+                    .await?;
+                *state = State::FollowedUp;
+                Ok(())
+            }
+            // The handler defers too, which has already been done for it
+            (_, _, Some(DEFERRED_CHANNEL_MESSAGE_WITH_SOURCE | DEFERRED_UPDATE_MESSAGE)) => Ok(()),
+            (_, _, Some(CHANNEL_MESSAGE_WITH_SOURCE)) => self.respond(&mut state, data).await,
+            (_, Deferral::Update, Some(UPDATE_MESSAGE)) => {
+                strip_message_only_fields(&mut data);
+                self.api
+                    .edit_original_interaction_response(interaction, data)
+                    .await
+            }
+            (_, deferral, kind) => {
+                tracing::warn!(
+                    ?deferral,
+                    ?kind,
+                    "response doesn't fit how the interaction was deferred, dropping it"
+                );
+                Ok(())
+            }
+        }
+    }
+
+    async fn edit_original_interaction_response(
+        &self,
+        interaction: &InteractionRef,
+        response: Value,
+    ) -> serenity::Result<()> {
+        if interaction.id == self.interaction.id {
+            let mut state = self.state.lock().await;
+            self.api
+                .edit_original_interaction_response(interaction, response)
+                .await?;
+            // Filling in a deferred message is as good as responding with it
+            if *state == State::Deferred && self.deferral != Deferral::Update {
+                *state = State::FollowedUp;
+            }
+            return Ok(());
+        }
+        self.api
+            .edit_original_interaction_response(interaction, response)
+            .await
+    }
+
+    async fn get_original_interaction_response(
+        &self,
+        interaction: &InteractionRef,
+    ) -> serenity::Result<MessageId> {
+        self.api
+            .get_original_interaction_response(interaction)
+            .await
+    }
+
+    async fn create_followup_message(
+        &self,
+        interaction: &InteractionRef,
+        message: Value,
+    ) -> serenity::Result<()> {
+        if interaction.id == self.interaction.id {
+            let mut state = self.state.lock().await;
+            return self.respond(&mut state, message).await;
+        }
+        self.api.create_followup_message(interaction, message).await
+    }
+
+    async fn create_followup_message_with_file(
+        &self,
+        interaction: &InteractionRef,
+        message: Value,
+        filename: &str,
+        data: Vec<u8>,
+    ) -> serenity::Result<()> {
+        if interaction.id == self.interaction.id {
+            // Edits can't attach files, so this fills in a deferred message the way Discord does it
+            let mut state = self.state.lock().await;
+            self.api
+                .create_followup_message_with_file(interaction, message, filename, data)
+                .await?;
+            *state = State::FollowedUp;
+            return Ok(());
+        }
+        self.api
+            .create_followup_message_with_file(interaction, message, filename, data)
+            .await
+    }
+
+    async fn delete_followup_message(
+        &self,
+        interaction: &InteractionRef,
+        message: MessageId,
+    ) -> serenity::Result<()> {
+        self.api.delete_followup_message(interaction, message).await
+    }
+
+    async fn send_message(
+        &self,
+        channel: ChannelId,
+        message: Value,
+    ) -> serenity::Result<MessageId> {
+        self.api.send_message(channel, message).await
+    }
+
+    async fn edit_message(
+        &self,
+        channel: ChannelId,
+        message: MessageId,
+        edit: Value,
+    ) -> serenity::Result<()> {
+        self.api.edit_message(channel, message, edit).await
+    }
+
+    async fn delete_message(&self, channel: ChannelId, message: MessageId) -> serenity::Result<()> {
+        self.api.delete_message(channel, message).await
+    }
+
+    async fn pin_message(&self, channel: ChannelId, message: MessageId) -> serenity::Result<()> {
+        self.api.pin_message(channel, message).await
+    }
+
+    async fn unpin_message(&self, channel: ChannelId, message: MessageId) -> serenity::Result<()> {
+        self.api.unpin_message(channel, message).await
+    }
+
+    async fn delete_reaction(
+        &self,
+        channel: ChannelId,
+        message: MessageId,
+        user: UserId,
+        emoji: &ReactionType,
+    ) -> serenity::Result<()> {
+        self.api
+            .delete_reaction(channel, message, user, emoji)
+            .await
+    }
+
+    async fn get_channel_guild(&self, channel: ChannelId) -> serenity::Result<Option<GuildId>> {
+        self.api.get_channel_guild(channel).await
+    }
+
+    async fn get_guild_owner(&self, guild: GuildId) -> serenity::Result<UserId> {
+        self.api.get_guild_owner(guild).await
+    }
+
+    async fn get_user_profile(&self, user: UserId) -> serenity::Result<Profile> {
+        self.api.get_user_profile(user).await
+    }
+
+    async fn get_bot_permissions(
+        &self,
+        channel: ChannelId,
+    ) -> serenity::Result<Option<Permissions>> {
+        self.api.get_bot_permissions(channel).await
+    }
+
+    async fn send_direct_message(
+        &self,
+        user: UserId,
+        message: Value,
+    ) -> serenity::Result<MessageId> {
+        self.api.send_direct_message(user, message).await
+    }
+
+    async fn add_member_role(
+        &self,
+        guild: GuildId,
+        user: UserId,
+        role: RoleId,
+    ) -> serenity::Result<()> {
+        self.api.add_member_role(guild, user, role).await
+    }
+
+    async fn get_application_emojis(&self) -> serenity::Result<Vec<(EmojiId, String)>> {
+        self.api.get_application_emojis().await
+    }
+
+    async fn create_application_emoji(&self, name: &str, image: &str) -> serenity::Result<EmojiId> {
+        self.api.create_application_emoji(name, image).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::model::{
+        application::interaction::InteractionResponseType,
+        id::{ChannelId, UserId},
+    };
+
+    use super::{Deferral, Deferring};
+    use crate::{
+        discord_api::{self, DiscordApi, InteractionRef},
+        testing::{command_interaction, component_interaction, RecordingDiscordApi},
+    };
+
+    const CHANNEL: ChannelId = ChannelId(10);
+    const USER: UserId = UserId(20);
+
+    async fn respond(
+        api: &dyn DiscordApi,
+        interaction: &InteractionRef,
+        content: &str,
+        ephemeral: bool,
+    ) {
+        api.create_interaction_response(
+            interaction,
+            discord_api::interaction_response(|r| {
+                r.interaction_response_data(|d| d.ephemeral(ephemeral).content(content))
+            }),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn responses_fill_in_the_deferred_message() {
+        let api = RecordingDiscordApi::default();
+        let cmd = command_interaction(USER, CHANNEL);
+        let deferring = Deferring::new(&api, cmd.clone(), Deferral::Message { ephemeral: false });
+        deferring
+            .watch("test", respond(&deferring, &cmd, "Done", false))
+            .await;
+        let original = api.get_original_interaction_response(&cmd).await.unwrap();
+        assert_eq!(api.message(original).content(), "Done");
+        assert_eq!(api.live_messages_in(CHANNEL).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn further_responses_are_followups() {
+        let api = RecordingDiscordApi::default();
+        let cmd = command_interaction(USER, CHANNEL);
+        let deferring = Deferring::new(&api, cmd.clone(), Deferral::Message { ephemeral: false });
+        deferring
+            .watch("test", async {
+                respond(&deferring, &cmd, "First", false).await;
+                respond(&deferring, &cmd, "Second", false).await;
+                respond(&deferring, &cmd, "Third", true).await;
+            })
+            .await;
+        let contents = api
+            .live_messages_in(CHANNEL)
+            .into_iter()
+            .map(|(_, msg)| msg.content().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(contents, ["First", "Second"]);
+        assert_eq!(api.ephemeral_responses()[0]["content"], "Third");
+    }
+
+    #[tokio::test]
+    async fn ephemeral_responses_replace_public_deferrals() {
+        let api = RecordingDiscordApi::default();
+        let cmd = command_interaction(USER, CHANNEL);
+        let deferring = Deferring::new(&api, cmd.clone(), Deferral::Message { ephemeral: false });
+        deferring
+            .watch("test", respond(&deferring, &cmd, "Done", true))
+            .await;
+        assert!(api.live_messages_in(CHANNEL).is_empty());
+        assert_eq!(api.ephemeral_responses()[0]["content"], "Done");
+    }
+
+    #[tokio::test]
+    async fn component_messages_become_followups_and_updates_edits() {
+        let api = RecordingDiscordApi::default();
+        let message = api
+            .send_message(CHANNEL, serde_json::json!({"content": "Request"}))
+            .await
+            .unwrap();
+        let comp = component_interaction(USER, CHANNEL, message, Vec::new());
+        let deferring = Deferring::new(&api, comp.clone(), Deferral::Update);
+        deferring
+            .watch("test", async {
+                respond(&deferring, &comp, "Done", true).await;
+                deferring
+                    .create_interaction_response(
+                        &comp,
+                        discord_api::interaction_response(|r| {
+                            r.kind(InteractionResponseType::UpdateMessage)
+                                .interaction_response_data(|d| d.content("Updated"))
+                        }),
+                    )
+                    .await
+                    .unwrap();
+            })
+            .await;
+        assert_eq!(api.message(message).content(), "Updated");
+        assert_eq!(api.ephemeral_responses()[0]["content"], "Done");
+    }
+}
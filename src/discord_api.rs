@@ -0,0 +1,485 @@
+use std::collections::HashMap;
+
+use serenity::{
+    builder::{
+        CreateAutocompleteResponse, CreateInteractionResponse, CreateInteractionResponseFollowup,
+        CreateMessage, EditInteractionResponse, EditMessage,
+    },
+    http::{Http, HttpError},
+    json::{self, Value},
+    model::{
+        application::{
+            command::CommandType,
+            interaction::{
+                application_command::ApplicationCommandInteraction,
+                autocomplete::AutocompleteInteraction,
+                message_component::MessageComponentInteraction, modal::ModalSubmitInteraction,
+                InteractionResponseType,
+            },
+        },
+        channel::{AttachmentType, ReactionType},
+        id::{ChannelId, EmojiId, GuildId, InteractionId, MessageId, RoleId, UserId},
+        Permissions,
+    },
+};
+
+use crate::user_profile::Profile;
+
+/// The subset of the Discord HTTP API that the bot uses
+///
+/// Payloads are passed around as raw JSON, see [`interaction_response`] and friends for building them.
+#[serenity::async_trait]
+pub trait DiscordApi: Send + Sync {
+    async fn create_interaction_response(
+        &self,
+        interaction: &InteractionRef,
+        response: Value,
+    ) -> serenity::Result<()>;
+    async fn edit_original_interaction_response(
+        &self,
+        interaction: &InteractionRef,
+        response: Value,
+    ) -> serenity::Result<()>;
+    async fn get_original_interaction_response(
+        &self,
+        interaction: &InteractionRef,
+    ) -> serenity::Result<MessageId>;
+    async fn create_followup_message(
+        &self,
+        interaction: &InteractionRef,
+        message: Value,
+    ) -> serenity::Result<()>;
+    /// Like [`Self::create_followup_message`], but with a file attached
+    async fn create_followup_message_with_file(
+        &self,
+        interaction: &InteractionRef,
+        message: Value,
+        filename: &str,
+        data: Vec<u8>,
+    ) -> serenity::Result<()>;
+    async fn delete_followup_message(
+        &self,
+        interaction: &InteractionRef,
+        message: MessageId,
+    ) -> serenity::Result<()>;
+    async fn send_message(&self, channel: ChannelId, message: Value)
+        -> serenity::Result<MessageId>;
+    async fn edit_message(
+        &self,
+        channel: ChannelId,
+        message: MessageId,
+        edit: Value,
+    ) -> serenity::Result<()>;
+    async fn delete_message(&self, channel: ChannelId, message: MessageId) -> serenity::Result<()>;
+    async fn pin_message(&self, channel: ChannelId, message: MessageId) -> serenity::Result<()>;
+    async fn unpin_message(&self, channel: ChannelId, message: MessageId) -> serenity::Result<()>;
+    /// Removes a user's reaction from a message
+    async fn delete_reaction(
+        &self,
+        channel: ChannelId,
+        message: MessageId,
+        user: UserId,
+        emoji: &ReactionType,
+    ) -> serenity::Result<()>;
+    /// Returns the guild that the channel belongs to, or [`None`] for DMs and other non-guild channels
+    async fn get_channel_guild(&self, channel: ChannelId) -> serenity::Result<Option<GuildId>>;
+    async fn get_guild_owner(&self, guild: GuildId) -> serenity::Result<UserId>;
+    async fn get_user_profile(&self, user: UserId) -> serenity::Result<Profile>;
+    /// The bot's own permissions in the channel, or [`None`] for DMs and other non-guild channels
+    async fn get_bot_permissions(
+        &self,
+        channel: ChannelId,
+    ) -> serenity::Result<Option<Permissions>>;
+    /// Sends a message to the user's DMs, opening the DM channel if needed
+    async fn send_direct_message(
+        &self,
+        user: UserId,
+        message: Value,
+    ) -> serenity::Result<MessageId>;
+    /// Grants the role to a member of the guild
+    async fn add_member_role(
+        &self,
+        guild: GuildId,
+        user: UserId,
+        role: RoleId,
+    ) -> serenity::Result<()>;
+    /// The bot application's own emojis, as (id, name)
+    async fn get_application_emojis(&self) -> serenity::Result<Vec<(EmojiId, String)>>;
+    /// Uploads an emoji to the bot application, `image` is a data URI
+    async fn create_application_emoji(&self, name: &str, image: &str) -> serenity::Result<EmojiId>;
+}
+
+#[serenity::async_trait]
+impl DiscordApi for Http {
+    async fn create_interaction_response(
+        &self,
+        interaction: &InteractionRef,
+        response: Value,
+    ) -> serenity::Result<()> {
+        Http::create_interaction_response(self, interaction.id.0, &interaction.token, &response)
+            .await
+    }
+
+    async fn edit_original_interaction_response(
+        &self,
+        interaction: &InteractionRef,
+        response: Value,
+    ) -> serenity::Result<()> {
+        Http::edit_original_interaction_response(self, &interaction.token, &response).await?;
+        Ok(())
+    }
+
+    async fn get_original_interaction_response(
+        &self,
+        interaction: &InteractionRef,
+    ) -> serenity::Result<MessageId> {
+        Ok(
+            Http::get_original_interaction_response(self, &interaction.token)
+                .await?
+                .id,
+        )
+    }
+
+    async fn create_followup_message(
+        &self,
+        interaction: &InteractionRef,
+        message: Value,
+    ) -> serenity::Result<()> {
+        Http::create_followup_message(self, &interaction.token, &message).await?;
+        Ok(())
+    }
+
+    async fn create_followup_message_with_file(
+        &self,
+        interaction: &InteractionRef,
+        message: Value,
+        filename: &str,
+        data: Vec<u8>,
+    ) -> serenity::Result<()> {
+        Http::create_followup_message_with_files(
+            self,
+            &interaction.token,
+            &message,
+            [AttachmentType::Bytes {
+                data: data.into(),
+                filename: filename.to_string(),
+            }],
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn delete_followup_message(
+        &self,
+        interaction: &InteractionRef,
+        message: MessageId,
+    ) -> serenity::Result<()> {
+        Http::delete_followup_message(self, &interaction.token, message.0).await
+    }
+
+    async fn send_message(
+        &self,
+        channel: ChannelId,
+        message: Value,
+    ) -> serenity::Result<MessageId> {
+        Ok(Http::send_message(self, channel.0, &message).await?.id)
+    }
+
+    async fn edit_message(
+        &self,
+        channel: ChannelId,
+        message: MessageId,
+        edit: Value,
+    ) -> serenity::Result<()> {
+        Http::edit_message(self, channel.0, message.0, &edit).await?;
+        Ok(())
+    }
+
+    async fn delete_message(&self, channel: ChannelId, message: MessageId) -> serenity::Result<()> {
+        Http::delete_message(self, channel.0, message.0).await
+    }
+
+    async fn pin_message(&self, channel: ChannelId, message: MessageId) -> serenity::Result<()> {
+        Http::pin_message(self, channel.0, message.0, None).await
+    }
+
+    async fn unpin_message(&self, channel: ChannelId, message: MessageId) -> serenity::Result<()> {
+        Http::unpin_message(self, channel.0, message.0, None).await
+    }
+
+    async fn delete_reaction(
+        &self,
+        channel: ChannelId,
+        message: MessageId,
+        user: UserId,
+        emoji: &ReactionType,
+    ) -> serenity::Result<()> {
+        Http::delete_reaction(self, channel.0, message.0, Some(user.0), emoji).await
+    }
+
+    async fn get_channel_guild(&self, channel: ChannelId) -> serenity::Result<Option<GuildId>> {
+        Ok(Http::get_channel(self, channel.0)
+            .await?
+            .guild()
+            .map(|channel| channel.guild_id))
+    }
+
+    async fn get_guild_owner(&self, guild: GuildId) -> serenity::Result<UserId> {
+        Ok(Http::get_guild(self, guild.0).await?.owner_id)
+    }
+
+    async fn get_user_profile(&self, user: UserId) -> serenity::Result<Profile> {
+        Ok(Profile::from(&Http::get_user(self, user.0).await?))
+    }
+
+    async fn get_bot_permissions(
+        &self,
+        channel: ChannelId,
+    ) -> serenity::Result<Option<Permissions>> {
+        let Some(channel) = Http::get_channel(self, channel.0).await?.guild() else {
+            return Ok(None);
+        };
+        let guild = Http::get_guild(self, channel.guild_id.0).await?;
+        let me = Http::get_current_user(self).await?;
+        let member = Http::get_member(self, channel.guild_id.0, me.id.0).await?;
+        guild.user_permissions_in(&channel, &member).map(Some)
+    }
+
+    async fn send_direct_message(
+        &self,
+        user: UserId,
+        message: Value,
+    ) -> serenity::Result<MessageId> {
+        let channel = Http::create_private_channel(
+            self,
+            &serde_json::json!({ "recipient_id": user.0.to_string() }),
+        )
+        .await?;
+        Ok(Http::send_message(self, channel.id.0, &message).await?.id)
+    }
+
+    async fn add_member_role(
+        &self,
+        guild: GuildId,
+        user: UserId,
+        role: RoleId,
+    ) -> serenity::Result<()> {
+        Http::add_member_role(self, guild.0, user.0, role.0, None).await
+    }
+
+    async fn get_application_emojis(&self) -> serenity::Result<Vec<(EmojiId, String)>> {
+        #[derive(serde::Deserialize)]
+        struct Emojis {
+            items: Vec<ApplicationEmoji>,
+        }
+        let emojis: Emojis = application_emojis_request(self, reqwest::Method::GET, None).await?;
+        Ok(emojis
+            .items
+            .into_iter()
+            .map(|emoji| (emoji.id, emoji.name))
+            .collect())
+    }
+
+    async fn create_application_emoji(&self, name: &str, image: &str) -> serenity::Result<EmojiId> {
+        let emoji: ApplicationEmoji = application_emojis_request(
+            self,
+            reqwest::Method::POST,
+            Some(serde_json::json!({ "name": name, "image": image })),
+        )
+        .await?;
+        Ok(emoji.id)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ApplicationEmoji {
+    id: EmojiId,
+    name: String,
+}
+
+/// Application emojis are newer than serenity 0.11, so they are requested without its help
+async fn application_emojis_request<T: serde::de::DeserializeOwned>(
+    http: &Http,
+    method: reqwest::Method,
+    body: Option<Value>,
+) -> serenity::Result<T> {
+    let application = http
+        .application_id()
+        .ok_or(HttpError::ApplicationIdMissing)?;
+    let mut request = reqwest::Client::new()
+        .request(
+            method,
+            format!("https://discord.com/api/v10/applications/{application}/emojis"),
+        )
+        .header(reqwest::header::AUTHORIZATION, &http.token);
+    if let Some(body) = body {
+        request = request.json(&body);
+    }
+    let response = request.send().await?;
+    if !response.status().is_success() {
+        return Err(HttpError::from_response(response).await.into());
+    }
+    Ok(response.json().await?)
+}
+
+/// The parts of an interaction that the handlers care about
+#[derive(Clone, Debug)]
+pub struct InteractionRef {
+    pub id: InteractionId,
+    pub token: String,
+    pub user: UserId,
+    pub channel: ChannelId,
+    pub guild: Option<GuildId>,
+    /// The invoking member's permissions in the channel, [`None`] outside of guilds
+    pub permissions: Option<Permissions>,
+    /// The invoking member's roles, empty outside of guilds
+    pub roles: Vec<RoleId>,
+    /// The message that the component (or the component that opened the modal) is attached to, or
+    /// that the message command was used on, [`None`] for slash commands and autocompletion
+    pub message: Option<MessageId>,
+    /// The values selected in a select menu
+    pub values: Vec<String>,
+}
+
+impl From<&ApplicationCommandInteraction> for InteractionRef {
+    fn from(cmd: &ApplicationCommandInteraction) -> Self {
+        Self {
+            id: cmd.id,
+            token: cmd.token.clone(),
+            user: cmd.user.id,
+            channel: cmd.channel_id,
+            guild: cmd.guild_id,
+            permissions: cmd.member.as_ref().and_then(|member| member.permissions),
+            roles: cmd
+                .member
+                .as_ref()
+                .map_or_else(Vec::new, |member| member.roles.clone()),
+            message: cmd
+                .data
+                .target_id
+                .filter(|_| cmd.data.kind == CommandType::Message)
+                .map(|target| target.to_message_id()),
+            values: Vec::new(),
+        }
+    }
+}
+
+impl From<&AutocompleteInteraction> for InteractionRef {
+    fn from(autocomplete: &AutocompleteInteraction) -> Self {
+        Self {
+            id: autocomplete.id,
+            token: autocomplete.token.clone(),
+            user: autocomplete.user.id,
+            channel: autocomplete.channel_id,
+            guild: autocomplete.guild_id,
+            permissions: autocomplete
+                .member
+                .as_ref()
+                .and_then(|member| member.permissions),
+            roles: autocomplete
+                .member
+                .as_ref()
+                .map_or_else(Vec::new, |member| member.roles.clone()),
+            message: None,
+            values: Vec::new(),
+        }
+    }
+}
+
+impl From<&MessageComponentInteraction> for InteractionRef {
+    fn from(comp: &MessageComponentInteraction) -> Self {
+        Self {
+            id: comp.id,
+            token: comp.token.clone(),
+            user: comp.user.id,
+            channel: comp.channel_id,
+            guild: comp.guild_id,
+            permissions: comp.member.as_ref().and_then(|member| member.permissions),
+            roles: comp
+                .member
+                .as_ref()
+                .map_or_else(Vec::new, |member| member.roles.clone()),
+            message: Some(comp.message.id),
+            values: comp.data.values.clone(),
+        }
+    }
+}
+
+impl From<&ModalSubmitInteraction> for InteractionRef {
+    fn from(modal: &ModalSubmitInteraction) -> Self {
+        Self {
+            id: modal.id,
+            token: modal.token.clone(),
+            user: modal.user.id,
+            channel: modal.channel_id,
+            guild: modal.guild_id,
+            permissions: modal.member.as_ref().and_then(|member| member.permissions),
+            roles: modal
+                .member
+                .as_ref()
+                .map_or_else(Vec::new, |member| member.roles.clone()),
+            message: modal.message.as_ref().map(|message| message.id),
+            values: Vec::new(),
+        }
+    }
+}
+
+fn to_json(map: HashMap<&'static str, Value>) -> Value {
+    Value::from(json::hashmap_to_json_map(map))
+}
+
+pub fn interaction_response<'a>(
+    f: impl for<'b> FnOnce(
+        &'b mut CreateInteractionResponse<'a>,
+    ) -> &'b mut CreateInteractionResponse<'a>,
+) -> Value {
+    let mut builder = CreateInteractionResponse::default();
+    f(&mut builder);
+    to_json(builder.0)
+}
+
+/// Suggestions for the option that the user is typing, which [`CreateInteractionResponse`] has no builder for
+pub fn autocomplete_response(
+    f: impl FnOnce(&mut CreateAutocompleteResponse) -> &mut CreateAutocompleteResponse,
+) -> Value {
+    let mut builder = CreateAutocompleteResponse::default();
+    f(&mut builder);
+    serde_json::json!({
+        "type": InteractionResponseType::Autocomplete as u8,
+        "data": to_json(builder.0),
+    })
+}
+
+pub fn edit_interaction_response(
+    f: impl FnOnce(&mut EditInteractionResponse) -> &mut EditInteractionResponse,
+) -> Value {
+    let mut builder = EditInteractionResponse::default();
+    f(&mut builder);
+    to_json(builder.0)
+}
+
+pub fn followup_message<'a>(
+    f: impl for<'b> FnOnce(
+        &'b mut CreateInteractionResponseFollowup<'a>,
+    ) -> &'b mut CreateInteractionResponseFollowup<'a>,
+) -> Value {
+    let mut builder = CreateInteractionResponseFollowup::default();
+    f(&mut builder);
+    to_json(builder.0)
+}
+
+pub fn create_message<'a>(
+    f: impl for<'b> FnOnce(&'b mut CreateMessage<'a>) -> &'b mut CreateMessage<'a>,
+) -> Value {
+    let mut builder = CreateMessage::default();
+    f(&mut builder);
+    to_json(builder.0)
+}
+
+pub fn edit_message<'a>(
+    f: impl for<'b> FnOnce(&'b mut EditMessage<'a>) -> &'b mut EditMessage<'a>,
+) -> Value {
+    let mut builder = EditMessage::default();
+    f(&mut builder);
+    to_json(builder.0)
+}
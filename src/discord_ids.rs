@@ -0,0 +1,73 @@
+//! Conversions between serenity's IDs and the [`DiscordId`]s that the database stores
+
+use entity::discord_id::{kind, DiscordId};
+use serenity::model::id::{ApplicationId, ChannelId, EmojiId, GuildId, MessageId, RoleId, UserId};
+
+/// A serenity ID that can be stored in the database
+pub trait ToDiscordId: Copy {
+    type Kind;
+
+    fn db_id(self) -> DiscordId<Self::Kind>;
+}
+
+/// A stored ID that can be turned back into the serenity ID that it was made from
+pub trait FromDiscordId: Copy {
+    type Id;
+
+    fn discord(self) -> Self::Id;
+}
+
+macro_rules! conversions {
+    ($($kind:ident <=> $id:ident,)*) => {$(
+        impl ToDiscordId for $id {
+            type Kind = kind::$kind;
+
+            fn db_id(self) -> DiscordId<kind::$kind> {
+                DiscordId::new(self.0)
+            }
+        }
+
+        impl FromDiscordId for DiscordId<kind::$kind> {
+            type Id = $id;
+
+            fn discord(self) -> $id {
+                $id(self.get())
+            }
+        }
+    )*};
+}
+
+conversions! {
+    Application <=> ApplicationId,
+    Channel <=> ChannelId,
+    Emoji <=> EmojiId,
+    Guild <=> GuildId,
+    Message <=> MessageId,
+    Role <=> RoleId,
+    User <=> UserId,
+}
+
+#[cfg(test)]
+mod tests {
+    use entity::discord_id::{kind, DiscordId};
+    use sea_orm::{sea_query::ValueType, Value};
+    use serenity::model::id::ChannelId;
+
+    use super::{FromDiscordId, ToDiscordId};
+
+    #[test]
+    fn ids_survive_the_database() {
+        let channel = ChannelId(1_157_336_434_539_053_086);
+        let stored = Value::from(channel.db_id());
+        assert_eq!(stored, Value::BigInt(Some(1_157_336_434_539_053_086)));
+        let loaded = <DiscordId<kind::Channel> as ValueType>::try_from(stored).unwrap();
+        assert_eq!(loaded.discord(), channel);
+    }
+
+    #[test]
+    fn negative_ids_are_rejected() {
+        assert!(
+            <DiscordId<kind::Channel> as ValueType>::try_from(Value::BigInt(Some(-1))).is_err()
+        );
+    }
+}
@@ -0,0 +1,203 @@
+//! Portable dumps of the bot's data, for moving it to another host without access to `pg_dump`
+//!
+//! A dump is a JSON document with every row of every table. Postgres converts the rows to and from
+//! JSON itself (with `jsonb_agg` and `jsonb_populate_recordset`), so every column is carried over
+//! without the bot having to know about it. A dump can only be imported into a database that has
+//! the same migrations applied as the one that it was made from, and that has no data of its own.
+
+use std::collections::BTreeMap;
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, DbErr, Statement, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use snafu::{ensure, ResultExt, Snafu};
+
+/// Bumped whenever the layout of [`Dump`] itself changes
+pub const FORMAT: u32 = 1;
+
+/// The tables that are dumped, each after the tables that it refers to, so that they can be
+/// imported in this order
+///
+/// Web sessions are left out, since they are only of use on the host that they were made on.
+const TABLES: &[&str] = &[
+    "application_emoji",
+    "approval_channel",
+    "archive_rule",
+    "badge_role",
+    "bump_channel",
+    "feature_flag",
+    "guild_setting",
+    "item_emoji",
+    "metrics_export",
+    "mirror_rule",
+    "pin_channel",
+    "ping_role",
+    "preset",
+    "quip",
+    "request_channel",
+    "season",
+    "user",
+    "badge",
+    "guild_ban",
+    "spam_event",
+    "stockpile_item",
+    "request",
+    "task",
+    "task_override",
+    "notification",
+    "claim_link",
+    "delivery",
+    "delivery_item",
+    "outbox_message",
+    "pending_request",
+    "request_attachment",
+    "request_extension",
+    "request_message",
+    "request_mirror",
+    "request_note",
+    "request_render_history",
+    "request_report",
+];
+
+#[derive(Serialize, Deserialize)]
+pub struct Dump {
+    pub format: u32,
+    /// The last migration that had been applied to the dumped database
+    pub schema: String,
+    /// The rows of each table, by table name
+    pub tables: BTreeMap<String, Vec<Value>>,
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum ImportError {
+    #[snafu(display("dump has format {format}, but only format {FORMAT} is supported"))]
+    UnsupportedFormat { format: u32 },
+    #[snafu(display("dump was made at migration {dump}, but the database is at {database}"))]
+    SchemaMismatch { dump: String, database: String },
+    #[snafu(display("dump has unknown table {table:?}"))]
+    UnknownTable { table: String },
+    #[snafu(display(
+        "table {table:?} already has data, dumps can only be imported into an empty database"
+    ))]
+    NotEmpty { table: String },
+    #[snafu(display("failed to find which migrations have been applied to the database"))]
+    ReadSchema { source: DbErr },
+    #[snafu(display("failed to import table {table:?}"))]
+    ImportTable { source: DbErr, table: String },
+    #[snafu(display("failed to commit the import"))]
+    Commit { source: DbErr },
+}
+
+/// Dumps every table in [`TABLES`]
+pub async fn dump(db: &DatabaseConnection) -> Result<Dump, DbErr> {
+    // Read everything from one snapshot, so that rows don't refer to rows that weren't dumped
+    let txn = db.begin().await?;
+    txn.execute_unprepared("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+        .await?;
+    let mut tables = BTreeMap::new();
+    for &table in TABLES {
+        let rows = txn
+            .query_one(Statement::from_string(
+                DbBackend::Postgres,
+                format!(r#"SELECT coalesce(jsonb_agg(t), '[]') AS rows FROM "{table}" t"#),
+            ))
+            .await?
+            .map(|row| row.try_get::<Value>("", "rows"))
+            .transpose()?
+            .unwrap_or_default();
+        let rows = match rows {
+            Value::Array(rows) => rows,
+            _ => Vec::new(),
+        };
+        tables.insert(table.to_string(), rows);
+    }
+    let schema = schema_version(&txn).await?;
+    txn.commit().await?;
+    Ok(Dump {
+        format: FORMAT,
+        schema,
+        tables,
+    })
+}
+
+/// Imports `dump` into an empty database, returning the number of rows imported
+pub async fn import(db: &DatabaseConnection, dump: Dump) -> Result<usize, ImportError> {
+    use import_error::*;
+    ensure!(
+        dump.format == FORMAT,
+        UnsupportedFormatSnafu {
+            format: dump.format
+        }
+    );
+    let txn = db.begin().await.context(ReadSchemaSnafu)?;
+    let database = schema_version(&txn).await.context(ReadSchemaSnafu)?;
+    ensure!(
+        dump.schema == database,
+        SchemaMismatchSnafu {
+            dump: dump.schema,
+            database,
+        }
+    );
+    if let Some(table) = dump
+        .tables
+        .keys()
+        .find(|table| !TABLES.contains(&table.as_str()))
+    {
+        return UnknownTableSnafu { table }.fail();
+    }
+    let mut imported = 0;
+    for &table in TABLES {
+        let not_empty = txn
+            .query_one(Statement::from_string(
+                DbBackend::Postgres,
+                format!(r#"SELECT EXISTS (SELECT FROM "{table}") AS not_empty"#),
+            ))
+            .await
+            .and_then(|row| {
+                row.map(|row| row.try_get::<bool>("", "not_empty"))
+                    .transpose()
+            })
+            .context(ImportTableSnafu { table })?
+            .unwrap_or(false);
+        ensure!(!not_empty, NotEmptySnafu { table });
+        let Some(rows) = dump.tables.get(table).filter(|rows| !rows.is_empty()) else {
+            continue;
+        };
+        // Triggers would touch the rows' timestamps as they are imported, so they are turned off
+        // until the table has been filled in
+        txn.execute_unprepared(&format!(r#"ALTER TABLE "{table}" DISABLE TRIGGER USER"#))
+            .await
+            .context(ImportTableSnafu { table })?;
+        // Rows that refer to other rows of the same table are fine, since Postgres only checks
+        // foreign keys once the whole statement is done
+        txn.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!(
+                r#"INSERT INTO "{table}" SELECT * FROM jsonb_populate_recordset(NULL::"{table}", $1)"#
+            ),
+            [Value::Array(rows.clone()).into()],
+        ))
+        .await
+        .context(ImportTableSnafu { table })?;
+        txn.execute_unprepared(&format!(r#"ALTER TABLE "{table}" ENABLE TRIGGER USER"#))
+            .await
+            .context(ImportTableSnafu { table })?;
+        imported += rows.len();
+    }
+    txn.commit().await.context(CommitSnafu)?;
+    Ok(imported)
+}
+
+/// The last migration that has been applied to the database
+async fn schema_version(db: &impl ConnectionTrait) -> Result<String, DbErr> {
+    db.query_one(Statement::from_string(
+        DbBackend::Postgres,
+        r#"SELECT max("version") AS version FROM "seaql_migrations""#,
+    ))
+    .await?
+    .map(|row| row.try_get::<Option<String>>("", "version"))
+    .transpose()?
+    .flatten()
+    .ok_or_else(|| DbErr::RecordNotFound("no migrations have been applied".to_string()))
+}
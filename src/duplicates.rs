@@ -0,0 +1,142 @@
+//! Spotting requests that are likely duplicates of a request that is being made
+//!
+//! Requests are compared by their titles and by the items of their tasks, ignoring the amounts
+//! (`300 bmats` and `100 bmats` ask for the same thing). Text is compared by the trigrams that
+//! it shares, so that typos and small rewordings still match.
+
+use std::collections::HashSet;
+
+/// How similar two requests must be to be considered duplicates, between 0 and 1
+pub const DUPLICATE_THRESHOLD: f64 = 0.6;
+
+/// How similar two task items must be to be considered the same item
+const SAME_ITEM_THRESHOLD: f64 = 0.6;
+
+/// The parts of a request that duplicates are detected by
+pub struct Candidate<'a> {
+    pub title: &'a str,
+    pub tasks: Vec<&'a str>,
+}
+
+/// Lowercases `text` and collapses everything that isn't a letter or a number into single spaces
+fn normalize(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The item that a task asks for, without its amount
+fn item(task: &str) -> String {
+    normalize(task)
+        .split(' ')
+        .filter(|word| !word.chars().all(|c| c.is_ascii_digit()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn trigrams(text: &str) -> HashSet<[char; 3]> {
+    let padded = format!("  {text} ").chars().collect::<Vec<_>>();
+    padded
+        .windows(3)
+        .map(|window| [window[0], window[1], window[2]])
+        .collect()
+}
+
+/// How similar `a` and `b` are, from 0 (nothing in common) to 1 (the same, once normalized)
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (normalize(a), normalize(b));
+    text_similarity(&a, &b)
+}
+
+fn text_similarity(a: &str, b: &str) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return if a == b { 1.0 } else { 0.0 };
+    }
+    let (a, b) = (trigrams(a), trigrams(b));
+    let shared = a.intersection(&b).count();
+    2.0 * shared as f64 / (a.len() + b.len()) as f64
+}
+
+/// The share of the items of `a` and `b` that the other request also asks for, from 0 to 1
+fn item_overlap(a: &[&str], b: &[&str]) -> f64 {
+    let a = a.iter().map(|task| item(task)).collect::<HashSet<_>>();
+    let b = b.iter().map(|task| item(task)).collect::<HashSet<_>>();
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let matched = |items: &HashSet<String>, others: &HashSet<String>| {
+        items
+            .iter()
+            .filter(|item| {
+                others
+                    .iter()
+                    .any(|other| text_similarity(item, other) >= SAME_ITEM_THRESHOLD)
+            })
+            .count()
+    };
+    (matched(&a, &b) + matched(&b, &a)) as f64 / (a.len() + b.len()) as f64
+}
+
+/// How likely it is that `a` and `b` are the same request, from 0 to 1
+pub fn score(a: &Candidate, b: &Candidate) -> f64 {
+    (similarity(a.title, b.title) + item_overlap(&a.tasks, &b.tasks)) / 2.0
+}
+
+/// Finds the request out of `existing` that `new` most likely duplicates, if any are similar enough
+pub fn find_duplicate<'a, K>(
+    new: &Candidate,
+    existing: impl IntoIterator<Item = (K, Candidate<'a>)>,
+) -> Option<(K, f64)> {
+    existing
+        .into_iter()
+        .map(|(key, candidate)| (key, score(new, &candidate)))
+        .filter(|(_, score)| *score >= DUPLICATE_THRESHOLD)
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_duplicate, similarity, Candidate};
+
+    fn candidate<'a>(title: &'a str, tasks: &[&'a str]) -> Candidate<'a> {
+        Candidate {
+            title,
+            tasks: tasks.to_vec(),
+        }
+    }
+
+    #[test]
+    fn similarity_ignores_case_and_punctuation() {
+        assert_eq!(
+            similarity("Shirts for the front!", "shirts for  the front"),
+            1.0
+        );
+        assert!(similarity("Shirts for the front", "Shirt for front") > 0.6);
+        assert!(similarity("Shirts for the front", "Fuel for the base") < 0.5);
+    }
+
+    #[test]
+    fn duplicates_match_by_title_and_items() {
+        let new = candidate("Shirts for the front", &["300 shirts", "100 bmats"]);
+        let existing = [
+            (1, candidate("Fuel run", &["diesel", "petrol"])),
+            (
+                2,
+                candidate("shirts for front", &["200 shirts", "bmats ~2"]),
+            ),
+            (3, candidate("Front shirts", &["shirts"])),
+        ];
+        let (key, score) = find_duplicate(&new, existing).unwrap();
+        assert_eq!(key, 2);
+        assert!(score > 0.9);
+    }
+
+    #[test]
+    fn different_requests_are_not_duplicates() {
+        let new = candidate("Shirts for the front", &["300 shirts"]);
+        let existing = [(1, candidate("Fuel run", &["diesel", "petrol"]))];
+        assert!(find_duplicate(&new, existing).is_none());
+    }
+}
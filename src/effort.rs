@@ -0,0 +1,29 @@
+//! Balancing estimated task effort between volunteers
+
+/// Divides `items` into `shares` groups with roughly even total effort
+///
+/// This uses the greedy "largest first" heuristic, which is not optimal but good enough for
+/// suggesting who does what. Items without an estimate should be given some nominal effort by the caller.
+pub fn balance<T>(items: impl IntoIterator<Item = (T, u32)>, shares: usize) -> Vec<Share<T>> {
+    let mut items = items.into_iter().collect::<Vec<_>>();
+    // Stable, so equal items stay in their original order
+    items.sort_by_key(|(_, effort)| std::cmp::Reverse(*effort));
+    let mut result = (0..shares)
+        .map(|_| Share {
+            effort: 0,
+            items: Vec::new(),
+        })
+        .collect::<Vec<_>>();
+    for (item, effort) in items {
+        if let Some(share) = result.iter_mut().min_by_key(|share| share.effort) {
+            share.effort += effort;
+            share.items.push(item);
+        }
+    }
+    result
+}
+
+pub struct Share<T> {
+    pub effort: u32,
+    pub items: Vec<T>,
+}
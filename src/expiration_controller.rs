@@ -1,32 +1,244 @@
-use std::time::Duration;
+//! Archives requests once they expire
+//!
+//! Expired requests are claimed in batches with `FOR UPDATE SKIP LOCKED`, which marks them as
+//! attempted, so that replicas share the work without archiving anything twice. A request that
+//! fails to archive is retried with exponential backoff (tracked by its `archive_attempts`), rather
+//! than holding up the rest of the sweep. After [`MAX_ARCHIVE_ATTEMPTS`] failures the request is
+//! marked as failed and left alone, and the server's owner is told about it, see `/problems`.
+//!
+//! Whole wars end at once, so a batch's tasks are loaded in one go, and several requests are
+//! archived at a time. Serenity waits out Discord's rate limits by itself, so archiving more at once
+//! mostly saves the time spent waiting on each message edit in turn.
 
-use entity::request;
-use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
-use serenity::CacheAndHttp;
+use std::{collections::HashMap, time::Duration};
+
+use entity::{request, task};
+use futures::{stream, StreamExt, TryStreamExt};
+use sea_orm::{
+    sea_query::{Expr, LockBehavior, LockType},
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, TransactionTrait, Value,
+};
+use serenity::model::id::{ApplicationId, UserId};
+use snafu::Report;
 use time::OffsetDateTime;
 
-use crate::archive_request_if_required;
+use crate::{
+    archive_loaded_request_if_required,
+    backoff::{self, Backoff},
+    discord_api::DiscordApi,
+    discord_ids::{FromDiscordId, ToDiscordId},
+    get_user_by_discord,
+    notifier::{self, Event},
+    request_link,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// Number of requests claimed at a time
+const BATCH_SIZE: u64 = 20;
+/// Number of requests of a batch that are archived at the same time
+const CONCURRENT_ARCHIVALS: usize = 5;
+/// How long to wait before retrying a request that failed to archive for the first time
+const RETRY_DELAY: Duration = Duration::from_secs(30);
+/// The longest that a request that keeps failing to archive waits between attempts
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60 * 60);
+/// Number of failed attempts after which a request is given up on
+pub const MAX_ARCHIVE_ATTEMPTS: i32 = 10;
+/// Whether a request's backoff has passed, given [`RETRY_DELAY`], [`MAX_RETRY_DELAY`], and the current time
+const RETRY_DUE_SQL: &str = r#""request"."archive_attempted_at"
+    + LEAST(
+        make_interval(secs => $1 * power(2, "request"."archive_attempts" - 1)),
+        make_interval(secs => $2)
+    ) <= $3"#;
+
+/// The slice of requests that a single bot is responsible for
+#[derive(Clone, Copy)]
+pub struct Partition {
+    pub application_id: ApplicationId,
+    /// Whether to also take care of requests that were created before they were tagged with an application
+    pub include_unassigned: bool,
+}
+
+impl Partition {
+    pub(crate) fn condition(&self) -> Condition {
+        self.condition_on(request::Column::DiscordApplicationId)
+    }
+
+    /// [`Self::condition`], for other tables that are tagged with an application in `column`
+    pub(crate) fn condition_on(&self, column: impl ColumnTrait) -> Condition {
+        let condition = Condition::any().add(column.eq(self.application_id.db_id()));
+        if self.include_unassigned {
+            condition.add(column.is_null())
+        } else {
+            condition
+        }
+    }
+}
+
+pub async fn run(db: &DatabaseConnection, discord: &dyn DiscordApi, partition: &Partition) {
+    let mut backoff = Backoff::new(POLL_INTERVAL, backoff::MAX_RETRY_DELAY);
+    loop {
+        let delay = match run_turn(db, discord, partition).await {
+            Ok(()) => {
+                backoff.succeeded();
+                POLL_INTERVAL
+            }
+            Err(err) => {
+                let delay = backoff.failed();
+                tracing::error!(error = &err as &dyn std::error::Error, retry_in = ?delay, "failed to find expired requests, retrying...");
+                delay
+            }
+        };
+        tokio::time::sleep(delay).await;
+    }
+}
 
-pub async fn run(db: &DatabaseConnection, discord: &CacheAndHttp) {
+pub(crate) async fn run_turn(
+    db: &DatabaseConnection,
+    discord: &dyn DiscordApi,
+    partition: &Partition,
+) -> Result<(), DbErr> {
     loop {
-        run_turn(db, discord).await;
-        tokio::time::sleep(Duration::from_secs(10)).await;
+        let batch = claim_batch(db, partition, OffsetDateTime::now_utc()).await?;
+        if batch.is_empty() {
+            return Ok(());
+        }
+        let mut tasks = HashMap::<_, Vec<_>>::new();
+        for task in task::Entity::find()
+            .filter(task::Column::Request.is_in(batch.iter().map(|req| req.id)))
+            .filter(task::Column::RemovedAt.is_null())
+            .all(db)
+            .await?
+        {
+            tasks.entry(task.request).or_default().push(task);
+        }
+        stream::iter(batch)
+            .map(|req| {
+                let tasks = tasks.remove(&req.id).unwrap_or_default();
+                archive(db, discord, req, tasks)
+            })
+            .buffer_unordered(CONCURRENT_ARCHIVALS)
+            .try_collect::<()>()
+            .await?;
     }
 }
 
-async fn run_turn(db: &DatabaseConnection, discord: &CacheAndHttp) {
-    let expiring_requests = request::Entity::find()
+async fn archive(
+    db: &DatabaseConnection,
+    discord: &dyn DiscordApi,
+    req: request::Model,
+    tasks: Vec<task::Model>,
+) -> Result<(), DbErr> {
+    if let Err(err) =
+        archive_loaded_request_if_required(db, req.clone(), tasks, None, discord).await
+    {
+        tracing::error!(error = &err as &dyn std::error::Error, request.id = %req.id, attempts = req.archive_attempts + 1, "failed to process request expiration, retrying later...");
+        record_failure(db, discord, &req, Report::from_error(err).to_string()).await?;
+    }
+    Ok(())
+}
+
+/// Remembers why the latest attempt to archive `req` failed, giving up once it has failed too often
+async fn record_failure(
+    db: &DatabaseConnection,
+    discord: &dyn DiscordApi,
+    req: &request::Model,
+    error: String,
+) -> Result<(), DbErr> {
+    // The claim has already counted this attempt
+    let give_up = req.archive_attempts + 1 >= MAX_ARCHIVE_ATTEMPTS;
+    request::Entity::update_many()
+        .set(request::ActiveModel {
+            archive_error: Set(Some(error.clone())),
+            archive_failed_at: if give_up {
+                Set(Some(OffsetDateTime::now_utc()))
+            } else {
+                NotSet
+            },
+            ..Default::default()
+        })
+        .filter(request::Column::Id.eq(req.id))
+        .exec(db)
+        .await?;
+    if give_up {
+        tracing::warn!(request.id = %req.id, attempts = req.archive_attempts + 1, "giving up on archiving request");
+        if let Some(guild) = req.discord_guild_id {
+            match discord.get_guild_owner(guild.discord()).await {
+                Ok(owner) => notify_owner(db, owner, req, &error).await?,
+                Err(err) => {
+                    tracing::error!(error = &err as &dyn std::error::Error, request.id = %req.id, "failed to find the server owner to tell about the failed request");
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn notify_owner(
+    db: &DatabaseConnection,
+    owner: UserId,
+    req: &request::Model,
+    error: &str,
+) -> Result<(), DbErr> {
+    let owner = get_user_by_discord(db, owner).await?;
+    let request = match request_link(req) {
+        Some(link) => format!("**{}** ({link})", req.title),
+        None => format!("**{}**", req.title),
+    };
+    let content = format!(
+        "I have given up on archiving the request {request} after {MAX_ARCHIVE_ATTEMPTS} attempts: {error}\n\
+        Once the problem has been fixed, use `/problems retry:True` in the server to try again."
+    );
+    notifier::notify_user(db, &owner, req, Event::ArchiveFailed, content).await
+}
+
+/// Claims up to [`BATCH_SIZE`] expired requests that are due for an archival attempt
+///
+/// The requests are marked as attempted before they are archived, so a request that can't be
+/// archived is only retried once its backoff has passed, and other replicas skip it meanwhile.
+async fn claim_batch(
+    db: &DatabaseConnection,
+    partition: &Partition,
+    now: OffsetDateTime,
+) -> Result<Vec<request::Model>, DbErr> {
+    let txn = db.begin().await?;
+    let batch = request::Entity::find()
+        .filter(request::Column::ArchivedOn.is_null())
+        .filter(request::Column::ArchiveFailedAt.is_null())
+        .filter(request::Column::ExpiresOn.lt(Some(now)))
+        .filter(partition.condition())
         .filter(
-            request::Column::ArchivedOn
-                .is_null()
-                .and(request::Column::ExpiresOn.lt(Some(OffsetDateTime::now_utc()))),
+            Condition::any()
+                .add(request::Column::ArchiveAttemptedAt.is_null())
+                .add(Expr::cust_with_values(
+                    RETRY_DUE_SQL,
+                    [
+                        Value::from(RETRY_DELAY.as_secs_f64()),
+                        Value::from(MAX_RETRY_DELAY.as_secs_f64()),
+                        Value::from(now),
+                    ],
+                )),
         )
-        .all(db)
-        .await
-        .unwrap();
-    for req in expiring_requests {
-        if let Err(err) = archive_request_if_required(db, req.id, None, discord).await {
-            tracing::error!(error = &err as &dyn std::error::Error, request.id = %req.id, "failed to process request expiration, ignoring...");
-        }
+        .order_by_asc(request::Column::ExpiresOn)
+        .limit(BATCH_SIZE)
+        .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+        .all(&txn)
+        .await?;
+    if !batch.is_empty() {
+        request::Entity::update_many()
+            .set(request::ActiveModel {
+                archive_attempted_at: Set(Some(now)),
+                ..Default::default()
+            })
+            .col_expr(
+                request::Column::ArchiveAttempts,
+                Expr::col(request::Column::ArchiveAttempts).add(1),
+            )
+            .filter(request::Column::Id.is_in(batch.iter().map(|req| req.id)))
+            .exec(&txn)
+            .await?;
     }
+    txn.commit().await?;
+    Ok(batch)
 }
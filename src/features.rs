@@ -0,0 +1,100 @@
+//! Switches for subsystems that each server can turn on or off without a redeploy, see `/features`
+//!
+//! New subsystems that might misbehave in some servers should get a [`Feature`] of their own, and
+//! check [`is_enabled`] before doing anything.
+
+use entity::feature_flag;
+use sea_orm::{
+    sea_query::OnConflict, ActiveValue::Set, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter,
+};
+use serenity::model::id::GuildId;
+use strum::IntoEnumIterator;
+
+use crate::discord_ids::ToDiscordId;
+
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, strum::AsRefStr, strum::EnumIter, strum::EnumString,
+)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Feature {
+    /// Claiming and completing tasks by reacting to requests, see `/server-quick-claim`
+    ReactionClaiming,
+    /// Asking before posting a request that looks like a duplicate of an open one
+    DuplicateDetection,
+}
+
+impl Feature {
+    /// Whether the feature is on in servers that haven't turned it on or off themselves
+    pub fn enabled_by_default(self) -> bool {
+        match self {
+            // These predate feature flags, so turning them off by default would surprise servers
+            Feature::ReactionClaiming | Feature::DuplicateDetection => true,
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Feature::ReactionClaiming => "claiming and completing tasks by reacting to requests",
+            Feature::DuplicateDetection => {
+                "asking before posting requests that look like duplicates of open ones"
+            }
+        }
+    }
+}
+
+/// Whether `feature` is turned on in `guild`
+pub async fn is_enabled(
+    db: &DatabaseConnection,
+    guild: GuildId,
+    feature: Feature,
+) -> Result<bool, DbErr> {
+    Ok(
+        feature_flag::Entity::find_by_id((guild.db_id(), feature.as_ref().to_string()))
+            .one(db)
+            .await?
+            .map_or(feature.enabled_by_default(), |flag| flag.enabled),
+    )
+}
+
+/// Every feature, and whether it is turned on in `guild`
+pub async fn all(db: &DatabaseConnection, guild: GuildId) -> Result<Vec<(Feature, bool)>, DbErr> {
+    let flags = feature_flag::Entity::find()
+        .filter(feature_flag::Column::DiscordGuildId.eq(guild.db_id()))
+        .all(db)
+        .await?;
+    Ok(Feature::iter()
+        .map(|feature| {
+            let enabled = flags
+                .iter()
+                .find(|flag| flag.feature == feature.as_ref())
+                .map_or(feature.enabled_by_default(), |flag| flag.enabled);
+            (feature, enabled)
+        })
+        .collect())
+}
+
+/// Turns `feature` on or off in `guild`
+pub async fn set(
+    db: &DatabaseConnection,
+    guild: GuildId,
+    feature: Feature,
+    enabled: bool,
+) -> Result<(), DbErr> {
+    feature_flag::Entity::insert(feature_flag::ActiveModel {
+        discord_guild_id: Set(guild.db_id()),
+        feature: Set(feature.as_ref().to_string()),
+        enabled: Set(enabled),
+    })
+    .on_conflict(
+        OnConflict::columns([
+            feature_flag::Column::DiscordGuildId,
+            feature_flag::Column::Feature,
+        ])
+        .update_column(feature_flag::Column::Enabled)
+        .to_owned(),
+    )
+    .exec(db)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,232 @@
+//! A server-wide feed of requests, for keeping an eye on every request channel at once
+//!
+//! When a server has picked a feed channel with `/server-feed-channel`, every request that is
+//! made in the server gets a one-line summary there, listing what it asks for (see [`tldr`]) and
+//! linking to the request. The summary is kept
+//! up to date whenever the request is re-rendered, and struck through once it is archived.
+//! Requests that are open for longer than the server's target time to completion are flagged, and
+//! sticky requests (see `/sticky-request`) are marked so that they stand out.
+
+use entity::{guild_setting, request, task};
+use sea_orm::{
+    prelude::Uuid, ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection, DbErr,
+    EntityTrait, ModelTrait, QueryFilter,
+};
+use serenity::model::id::{ChannelId, MessageId};
+use snafu::{OptionExt, ResultExt, Snafu};
+use time::OffsetDateTime;
+
+use crate::{
+    discord_api,
+    discord_api::DiscordApi,
+    discord_ids::{FromDiscordId, ToDiscordId},
+    request_link, stats, tldr,
+};
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum Error {
+    Database {
+        source: DbErr,
+    },
+    #[snafu(display("request {request} not found"))]
+    RequestNotFound {
+        request: Uuid,
+    },
+    #[snafu(display("failed to post feed summary in {channel}"))]
+    SendSummary {
+        source: serenity::Error,
+        channel: ChannelId,
+    },
+    #[snafu(display("failed to update feed summary {message}"))]
+    EditSummary {
+        source: serenity::Error,
+        message: MessageId,
+    },
+}
+
+/// Posts or updates the request's summary in its server's feed channel
+///
+/// Requests that were archived before the feed channel was picked are left alone, and requests that
+/// are restricted to a role stay out of the feed, which everyone in the server can read.
+pub async fn sync(
+    db: &DatabaseConnection,
+    api: &dyn DiscordApi,
+    request_id: Uuid,
+) -> Result<(), Error> {
+    let request = request::Entity::find_by_id(request_id)
+        .one(db)
+        .await
+        .context(error::DatabaseSnafu)?
+        .context(error::RequestNotFoundSnafu {
+            request: request_id,
+        })?;
+    let tasks = request
+        .find_related(task::Entity)
+        .filter(task::Column::MovedTo.is_null())
+        .filter(task::Column::RemovedAt.is_null())
+        .all(db)
+        .await
+        .context(error::DatabaseSnafu)?;
+    let completed = tasks
+        .iter()
+        .filter(|task| task.completed_at.is_some())
+        .count();
+    let target = match request.discord_guild_id {
+        Some(guild) => stats::target_completion(db, guild.discord())
+            .await
+            .context(error::DatabaseSnafu)?,
+        None => None,
+    };
+    let overdue = request.archived_on.is_none()
+        && completed < tasks.len()
+        && target.is_some_and(|target| OffsetDateTime::now_utc() - request.created_at > target);
+    let contents = tldr::summarize(tasks.iter().map(|task| task.task.as_str()));
+    let content = summary(
+        &request,
+        contents.as_deref(),
+        completed,
+        tasks.len(),
+        overdue,
+    );
+    if let Some((channel, message)) = request
+        .discord_feed_channel_id
+        .zip(request.discord_feed_message_id)
+    {
+        let message = message.discord();
+        api.edit_message(
+            channel.discord(),
+            message,
+            discord_api::edit_message(|msg| msg.content(content)),
+        )
+        .await
+        .context(error::EditSummarySnafu { message })?;
+        return Ok(());
+    }
+    let (Some(guild), Some(_), None, None) = (
+        request.discord_guild_id,
+        request.discord_message_id,
+        request.archived_on,
+        request.restricted_to_role,
+    ) else {
+        return Ok(());
+    };
+    let feed_channel = guild_setting::Entity::find_by_id(guild)
+        .one(db)
+        .await
+        .context(error::DatabaseSnafu)?
+        .and_then(|settings| settings.feed_channel)
+        // The request itself is already in the feed channel
+        .filter(|channel| Some(*channel) != request.discord_channel_id);
+    let Some(feed_channel) = feed_channel else {
+        return Ok(());
+    };
+    let channel = feed_channel.discord();
+    let message = api
+        .send_message(
+            channel,
+            discord_api::create_message(|msg| {
+                msg.content(content)
+                    .allowed_mentions(|mentions| mentions.empty_parse())
+            }),
+        )
+        .await
+        .context(error::SendSummarySnafu { channel })?;
+    request::ActiveModel {
+        id: sea_orm::ActiveValue::Unchanged(request_id),
+        discord_feed_channel_id: Set(Some(feed_channel)),
+        discord_feed_message_id: Set(Some(message.db_id())),
+        ..Default::default()
+    }
+    .update(db)
+    .await
+    .context(error::DatabaseSnafu)?;
+    Ok(())
+}
+
+/// The one-line summary of a request asking for `contents`, with `completed` out of its `total`
+/// tasks done
+fn summary(
+    request: &request::Model,
+    contents: Option<&str>,
+    completed: usize,
+    total: usize,
+    overdue: bool,
+) -> String {
+    let contents = contents.map_or(String::new(), |contents| format!(" · {contents}"));
+    let description = format!(
+        "**{}** ({}){contents} · {completed}/{total} done",
+        request.title, request.kind
+    );
+    let description = if request.sticky {
+        format!("📌 {description}")
+    } else {
+        description
+    };
+    let link = request_link(request).unwrap_or_default();
+    match request.archived_on {
+        Some(_) => format!("~~{description}~~ · archived {link}"),
+        None if overdue => format!("{description} · ⌛ overdue · {link}"),
+        None => format!("{description} · {link}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use entity::{discord_id::DiscordId, request};
+    use sea_orm::prelude::Uuid;
+    use time::OffsetDateTime;
+
+    use super::summary;
+    use crate::testing;
+
+    fn request(archived: bool) -> request::Model {
+        request_with(archived, false)
+    }
+
+    fn request_with(archived: bool, sticky: bool) -> request::Model {
+        request::Model {
+            created_by: Uuid::from_u128(2),
+            discord_message_id: Some(DiscordId::new(30)),
+            title: "Shirts for the front".to_string(),
+            discord_channel_id: Some(DiscordId::new(20)),
+            archived_on: archived.then_some(OffsetDateTime::UNIX_EPOCH),
+            discord_guild_id: Some(DiscordId::new(10)),
+            kind: "Truck".to_string(),
+            sticky,
+            ..testing::request(1)
+        }
+    }
+
+    #[test]
+    fn summaries_link_to_the_request() {
+        assert_eq!(
+            summary(&request(false), Some("40 shirts, 2 flatbeds"), 1, 3, false),
+            "**Shirts for the front** (Truck) · 40 shirts, 2 flatbeds · 1/3 done · https://discord.com/channels/10/20/30"
+        );
+    }
+
+    #[test]
+    fn archived_summaries_are_struck_through() {
+        assert_eq!(
+            summary(&request(true), None, 3, 3, false),
+            "~~**Shirts for the front** (Truck) · 3/3 done~~ · archived https://discord.com/channels/10/20/30"
+        );
+    }
+
+    #[test]
+    fn overdue_summaries_are_flagged() {
+        assert_eq!(
+            summary(&request(false), None, 1, 3, true),
+            "**Shirts for the front** (Truck) · 1/3 done · ⌛ overdue · https://discord.com/channels/10/20/30"
+        );
+    }
+
+    #[test]
+    fn sticky_summaries_are_marked() {
+        assert_eq!(
+            summary(&request_with(false, true), None, 0, 1, false),
+            "📌 **Shirts for the front** (Truck) · 0/1 done · https://discord.com/channels/10/20/30"
+        );
+    }
+}
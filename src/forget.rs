@@ -0,0 +1,145 @@
+//! Forgetting users who ask for it, see `/forget-me`
+//!
+//! The servers that a forgotten user took part in still rely on their requests and tasks, so those
+//! are credited to a shared tombstone user instead. Everything else about them is deleted, along
+//! with the user itself.
+
+use std::collections::BTreeSet;
+
+use entity::{
+    badge, claim_link, delivery,
+    discord_id::{kind, DiscordId},
+    guild_ban, notification, pending_request, request, request_attachment, request_extension,
+    request_note, request_render_history, request_report, spam_event, task, task_override, user,
+    web_session,
+};
+use sea_orm::{
+    prelude::Uuid,
+    sea_query::{Expr, OnConflict},
+    ActiveValue::Set,
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, ModelTrait, QueryFilter,
+    QuerySelect, TransactionTrait,
+};
+use serenity::model::id::UserId;
+
+use crate::discord_ids::ToDiscordId;
+
+/// The user that forgotten users' requests and tasks are credited to
+///
+/// Discord never hands out the snowflake 0, so it can't clash with a real user.
+pub const TOMBSTONE: UserId = UserId(0);
+
+/// Forgets `user`, returning the requests that mentioned them, which need to be re-rendered
+pub async fn forget_user(db: &DatabaseConnection, user: user::Model) -> Result<Vec<Uuid>, DbErr> {
+    let txn = db.begin().await?;
+    let tombstone = user::Entity::insert(user::ActiveModel {
+        discord_user_id: Set(TOMBSTONE.db_id()),
+        ..Default::default()
+    })
+    .on_conflict(
+        OnConflict::column(user::Column::DiscordUserId)
+            // No-op update clause means the user is still returned by the upsert RETURNING
+            .update_column(user::Column::DiscordUserId)
+            .to_owned(),
+    )
+    .exec_with_returning(&txn)
+    .await?;
+
+    let mut requests = request::Entity::find()
+        .select_only()
+        .column(request::Column::Id)
+        .filter(request::Column::CreatedBy.eq(user.id))
+        .into_tuple::<Uuid>()
+        .all(&txn)
+        .await?
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+    requests.extend(
+        task::Entity::find()
+            .select_only()
+            .column(task::Column::Request)
+            .filter(
+                task::Column::AssignedTo
+                    .eq(user.id)
+                    .or(task::Column::CompletedBy.eq(user.id))
+                    .or(task::Column::AllowedUserId.eq(user.discord_user_id)),
+            )
+            .into_tuple::<Uuid>()
+            .all(&txn)
+            .await?,
+    );
+
+    let (from, to) = (user.id, tombstone.id);
+    reassign::<request::Entity>(&txn, request::Column::CreatedBy, from, to).await?;
+    reassign::<task::Entity>(&txn, task::Column::AssignedTo, from, to).await?;
+    reassign::<task::Entity>(&txn, task::Column::CompletedBy, from, to).await?;
+    reassign::<task::Entity>(&txn, task::Column::RemovedBy, from, to).await?;
+    reassign::<request_note::Entity>(&txn, request_note::Column::CreatedBy, from, to).await?;
+    reassign::<request_attachment::Entity>(&txn, request_attachment::Column::CreatedBy, from, to)
+        .await?;
+    reassign::<request_extension::Entity>(&txn, request_extension::Column::ExtendedBy, from, to)
+        .await?;
+    reassign::<request_report::Entity>(&txn, request_report::Column::ReportedBy, from, to).await?;
+    reassign::<request_report::Entity>(&txn, request_report::Column::ResolvedBy, from, to).await?;
+    reassign::<delivery::Entity>(&txn, delivery::Column::CreatedBy, from, to).await?;
+    reassign::<claim_link::Entity>(&txn, claim_link::Column::CreatedBy, from, to).await?;
+    reassign::<guild_ban::Entity>(&txn, guild_ban::Column::BannedBy, from, to).await?;
+    reassign::<task_override::Entity>(&txn, task_override::Column::OverriddenBy, from, to).await?;
+    reassign::<task_override::Entity>(&txn, task_override::Column::Assignee, from, to).await?;
+
+    // Reservations name their Discord account rather than the user, so they are lifted instead
+    task::Entity::update_many()
+        .col_expr(
+            task::Column::AllowedUserId,
+            Expr::value(None::<DiscordId<kind::User>>),
+        )
+        .filter(task::Column::AllowedUserId.eq(user.discord_user_id))
+        .exec(&txn)
+        .await?;
+
+    // Past renders of their requests still mention them, so the history starts over from the
+    // re-render that follows
+    request_render_history::Entity::delete_many()
+        .filter(request_render_history::Column::Request.is_in(requests.iter().copied()))
+        .exec(&txn)
+        .await?;
+
+    // Drafts, sessions, notifications, badges, and spam history are only of use to the user themselves
+    notification::Entity::delete_many()
+        .filter(notification::Column::Recipient.eq(user.id))
+        .exec(&txn)
+        .await?;
+    pending_request::Entity::delete_many()
+        .filter(pending_request::Column::CreatedBy.eq(user.id))
+        .exec(&txn)
+        .await?;
+    web_session::Entity::delete_many()
+        .filter(web_session::Column::User.eq(user.id))
+        .exec(&txn)
+        .await?;
+    spam_event::Entity::delete_many()
+        .filter(spam_event::Column::User.eq(user.id))
+        .exec(&txn)
+        .await?;
+    badge::Entity::delete_many()
+        .filter(badge::Column::User.eq(user.id))
+        .exec(&txn)
+        .await?;
+    user.delete(&txn).await?;
+    txn.commit().await?;
+    Ok(requests.into_iter().collect())
+}
+
+async fn reassign<E: EntityTrait>(
+    db: &impl ConnectionTrait,
+    column: E::Column,
+    from: Uuid,
+    to: Uuid,
+) -> Result<(), DbErr> {
+    E::update_many()
+        .col_expr(column, Expr::value(to))
+        .filter(column.eq(from))
+        .exec(db)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,194 @@
+//! Flags or archives the open requests for hexes that fall to the enemy, so that request channels
+//! follow the actual front line
+//!
+//! Servers opt in with `/server-region-loss`, naming their faction. Every request remembers who
+//! held its hex when it was last looked at (see [`crate::war_map::holder`]), so a request for a hex that
+//! was already held by the enemy (such as for partisan operations) is left alone, and only a hex
+//! that changes hands to the enemy afterwards counts as lost.
+
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    time::Duration,
+};
+
+use entity::{guild_setting, request};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter,
+};
+use time::OffsetDateTime;
+
+use crate::{
+    backoff::{self, Backoff},
+    discord_api::DiscordApi,
+    expiration_controller::Partition,
+    expire_request, leader, update_request_messages,
+    war_map::{Team, WarApi},
+};
+
+/// The War API updates every few minutes, and hexes don't change hands any faster
+const POLL_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// What [`request::Model::location_held_by`] says for a hex that nobody holds
+const NOBODY: &str = "Nobody";
+
+/// What happens to the open requests for a hex once it is lost
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, strum::AsRefStr, strum::EnumIter, strum::EnumString,
+)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Action {
+    /// The request is marked as lost, but stays open
+    Flag,
+    /// The request is expired, so that it is archived
+    Archive,
+}
+
+pub async fn run(
+    db: &DatabaseConnection,
+    discord: &dyn DiscordApi,
+    war_api: &WarApi,
+    partition: &Partition,
+) {
+    let mut backoff = Backoff::new(POLL_INTERVAL, backoff::MAX_RETRY_DELAY);
+    loop {
+        let delay = match run_turn(db, discord, war_api, partition).await {
+            Ok(()) => {
+                backoff.succeeded();
+                POLL_INTERVAL
+            }
+            Err(err) => {
+                let delay = backoff.failed();
+                tracing::error!(error = &err as &dyn std::error::Error, retry_in = ?delay, "failed to check the front line, retrying...");
+                delay
+            }
+        };
+        tokio::time::sleep(delay).await;
+    }
+}
+
+async fn run_turn(
+    db: &DatabaseConnection,
+    discord: &dyn DiscordApi,
+    war_api: &WarApi,
+    partition: &Partition,
+) -> Result<(), DbErr> {
+    let Some(leadership) =
+        leader::try_lead(db, leader::Controller::Front, partition.application_id.0).await?
+    else {
+        return Ok(());
+    };
+    let rules = guild_setting::Entity::find()
+        .filter(guild_setting::Column::RegionLoss.is_not_null())
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|settings| {
+            let faction = Team::from_str(settings.faction.as_deref()?).ok()?;
+            let action = Action::from_str(settings.region_loss.as_deref()?).ok()?;
+            Some((settings.discord_guild_id, (faction, action)))
+        })
+        .collect::<HashMap<_, _>>();
+    if rules.is_empty() {
+        return leadership.release().await;
+    }
+    let requests = request::Entity::find()
+        .filter(request::Column::ArchivedOn.is_null())
+        .filter(request::Column::LocationHex.is_not_null())
+        .filter(request::Column::LocationLostAt.is_null())
+        .filter(request::Column::DiscordGuildId.is_in(rules.keys().copied()))
+        .filter(partition.condition())
+        .all(db)
+        .await?;
+
+    let mut holders = HashMap::new();
+    for hex in requests
+        .iter()
+        .filter_map(|req| req.location_hex.as_deref())
+        .collect::<HashSet<_>>()
+    {
+        match war_api.holder(hex).await {
+            Ok(holder) => {
+                holders.insert(hex, holder);
+            }
+            // The other hexes can still be checked
+            Err(err) => {
+                tracing::warn!(
+                    error = &err as &dyn std::error::Error,
+                    hex,
+                    "failed to find out who holds hex, retrying later..."
+                );
+            }
+        }
+    }
+
+    let now = OffsetDateTime::now_utc();
+    for req in &requests {
+        let (Some(hex), Some(guild)) = (req.location_hex.as_deref(), req.discord_guild_id) else {
+            continue;
+        };
+        let (Some(&holder), Some(&(faction, action))) = (holders.get(hex), rules.get(&guild))
+        else {
+            continue;
+        };
+        let held_by = holder.as_ref().map_or(NOBODY, |team| team.as_ref());
+        if req.location_held_by.as_deref() == Some(held_by) {
+            continue;
+        }
+        let lost = is_lost(req.location_held_by.as_deref(), holder, faction);
+        request::ActiveModel {
+            id: sea_orm::ActiveValue::Unchanged(req.id),
+            location_held_by: Set(Some(held_by.to_string())),
+            location_lost_at: Set(lost.then_some(now)),
+            ..Default::default()
+        }
+        .update(db)
+        .await?;
+        if !lost {
+            continue;
+        }
+        tracing::info!(request.id = %req.id, hex, ?action, "request's hex was lost to the enemy");
+        match action {
+            Action::Flag => {
+                if let Err(err) = update_request_messages(db, discord, req.id, None).await {
+                    // The refresh controller doesn't know about lost hexes, but the next change does
+                    tracing::error!(error = &err as &dyn std::error::Error, request.id = %req.id, "failed to flag request as lost");
+                }
+            }
+            Action::Archive => {
+                expire_request(db, discord, req.id).await?;
+            }
+        }
+    }
+    leadership.release().await
+}
+
+/// Whether a request's hex was just lost, now that `holder` holds it after `previous` did
+///
+/// `previous` is [`None`] the first time that the request is looked at, which can't tell whether
+/// the hex has changed hands.
+fn is_lost(previous: Option<&str>, holder: Option<Team>, faction: Team) -> bool {
+    let Some(previous) = previous else {
+        return false;
+    };
+    holder.is_some_and(|holder| holder != faction && previous != holder.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_lost, NOBODY};
+    use crate::war_map::Team;
+
+    #[test]
+    fn hexes_are_lost_when_the_enemy_takes_them() {
+        let faction = Team::Wardens;
+        assert!(is_lost(Some("Wardens"), Some(Team::Colonials), faction));
+        assert!(is_lost(Some(NOBODY), Some(Team::Colonials), faction));
+        // Still theirs, or taken back
+        assert!(!is_lost(Some("Colonials"), Some(Team::Colonials), faction));
+        assert!(!is_lost(Some("Colonials"), Some(Team::Wardens), faction));
+        assert!(!is_lost(Some("Wardens"), None, faction));
+        // Requests behind enemy lines aren't lost when they are first looked at
+        assert!(!is_lost(None, Some(Team::Colonials), faction));
+    }
+}
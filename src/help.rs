@@ -0,0 +1,384 @@
+//! The pages of `/help`, which are generated from the command definitions that are registered
+//! with Discord so that they can't fall out of date
+//!
+//! Alongside a page for every command there is one for the task syntax, which several commands share.
+
+use serde_json::Value;
+
+use crate::{limits, task_syntax::MAX_MULTIPLIER};
+
+/// The page about the task syntax, other pages are named after their command
+pub const TASK_SYNTAX_PAGE: &str = "task-syntax";
+
+/// Commands with options that are written in the task syntax
+const TASK_SYNTAX_COMMANDS: &[&str] = &["request", "request-multi", "request-presets", "mpf"];
+
+/// Examples of how to use the commands that aren't obvious, by command name
+const EXAMPLES: &[(&str, &[&str])] = &[
+    (
+        "request",
+        &[
+            "/request title:Shirts for the front kind:Truck tasks:{3x} 40 shirts; 300 bmats ~2",
+            "/request title:Restock kind:Truck preset:Frontline expires_in:6 hours",
+            "/request title:Bunker upgrade kind:General tasks:500 concrete confirm_completion:True",
+            "/request title:Partisan drop kind:Truck tasks:{2x} 40 shirts restricted_to:@Officers",
+            "/request title:Bunker supplies kind:Truck tasks:500 bmats hex:Dead Lands grid:G7k3",
+        ],
+    ),
+    (
+        "request-multi",
+        &["/request-multi title:Shirt run kind:Truck tasks:{2x} 40 shirts | 300 bmats; flatbed"],
+    ),
+    ("mpf", &["/mpf items:9 7.62mm; {2x} 5 bandages"]),
+    (
+        "add-tasks",
+        &["/add-tasks request:https://discord.com/channels/… tasks:flatbed; {2x} 40 shirts"],
+    ),
+    (
+        "remove-tasks",
+        &["/remove-tasks request:https://discord.com/channels/… tasks:2 4"],
+    ),
+    (
+        "move-request",
+        &["/move-request request:https://discord.com/channels/… channel:#frontline"],
+    ),
+    (
+        "claim",
+        &["/claim request:https://discord.com/channels/… task:3"],
+    ),
+    (
+        "complete",
+        &["/complete request:https://discord.com/channels/… task:3"],
+    ),
+    (
+        "admin-set-task-state",
+        &["/admin-set-task-state request:https://discord.com/channels/… task:3 state:Completed assignee:@Hauler reason:Forgot to press the button"],
+    ),
+    (
+        "admin-rerender",
+        &[
+            "/admin-rerender request:https://discord.com/channels/…",
+            "/admin-rerender request:6f1c2d3e-4b5a-4c6d-8e7f-901a2b3c4d5e",
+        ],
+    ),
+    (
+        "split-request",
+        &["/split-request request:https://discord.com/channels/… tasks:1-4 7"],
+    ),
+    (
+        "reorder-tasks",
+        &["/reorder-tasks request:https://discord.com/channels/… tasks:5 7 position:1"],
+    ),
+    (
+        "task-deadline",
+        &[
+            "/task-deadline request:https://discord.com/channels/… tasks:1-3 by:18:00",
+            "/task-deadline request:https://discord.com/channels/… tasks:4 by:none",
+        ],
+    ),
+    (
+        "sticky-request",
+        &[
+            "/sticky-request request:https://discord.com/channels/…",
+            "/sticky-request request:https://discord.com/channels/… sticky:false",
+        ],
+    ),
+    (
+        "suggest-split",
+        &["/suggest-split request:https://discord.com/channels/… volunteers:3"],
+    ),
+    (
+        "request-presets",
+        &[
+            "/request-presets add:Frontline tasks:{5x} 40 shirts; 300 bmats",
+            "/request-presets remove:Frontline",
+            "/request-presets share:Frontline",
+            "/request-presets import:{\"format\":1,\"name\":\"Frontline\",\"tasks\":\"…\"}",
+        ],
+    ),
+    (
+        "request-mirrors",
+        &[
+            "/request-mirrors share:#requests partner_channel:123456789012345678",
+            "/request-mirrors receive:#allied-requests partner_channel:123456789012345678",
+        ],
+    ),
+    (
+        "request-bumps",
+        &[
+            "/request-bumps add:#requests days:2",
+            "/request-bumps remove:#requests",
+        ],
+    ),
+    (
+        "request-approvals",
+        &[
+            "/request-approvals require:#requests queue:#mod-queue",
+            "/request-approvals stop:#requests",
+        ],
+    ),
+    (
+        "report-request",
+        &["/report-request request:https://discord.com/channels/… reason:Spam"],
+    ),
+    ("timezone", &["/timezone zone:Europe/Stockholm"]),
+    (
+        "notifications",
+        &["/notifications via:webhook address:https://example.com/hooks/requests"],
+    ),
+    ("forget-me", &["/forget-me confirm:True"]),
+    ("server-plain-rendering", &["/server-plain-rendering enabled:True"]),
+    (
+        "server-stockpile",
+        &[
+            "/server-stockpile name:Seaport",
+            "/server-stockpile item:shirts count:120",
+        ],
+    ),
+    (
+        "item-emoji",
+        &[
+            "/item-emoji item:bmats emoji::bmats:",
+            "/item-emoji item:shirts off:True",
+        ],
+    ),
+    (
+        "server-notification-webhook",
+        &["/server-notification-webhook url:https://discord.com/api/webhooks/…"],
+    ),
+    (
+        "server-backlog-warning",
+        &["/server-backlog-warning open_requests:30 oldest:2 days"],
+    ),
+    ("server-claim-capacity", &["/server-claim-capacity tasks:5"]),
+    ("server-seasons", &["/server-seasons length:weekly"]),
+    (
+        "server-quips",
+        &[
+            "/server-quips add:Happy holidays, haulers! pack:holidays from:12-20 until:01-02",
+            "/server-quips packs:default,holidays",
+        ],
+    ),
+    (
+        "freeze",
+        &["/freeze notice:Op in progress, requests reopen at 23:00"],
+    ),
+    ("unfreeze", &["/unfreeze"]),
+    (
+        "announce-contributors",
+        &["/announce-contributors message:Op tonight at 20:00, we need haulers days:3"],
+    ),
+    (
+        "server-badges",
+        &[
+            "/server-badges announce:True",
+            "/server-badges badge:hundred-tasks role:@Veteran",
+        ],
+    ),
+    ("leaderboard", &["/leaderboard", "/leaderboard season:3"]),
+    (
+        "server-idle-alert",
+        &["/server-idle-alert after:2 hours", "/server-idle-alert off:True"],
+    ),
+    (
+        "server-region-loss",
+        &["/server-region-loss faction:Wardens action:archive"],
+    ),
+    (
+        "server-content-filter",
+        &[
+            "/server-content-filter add:heck, bloody hell",
+            "/server-content-filter remove:heck mode:mask",
+        ],
+    ),
+    (
+        "features",
+        &["/features action:disable feature:duplicate-detection"],
+    ),
+];
+
+/// A page of help, which is shown as an embed
+#[derive(Debug, PartialEq, Eq)]
+pub struct Page {
+    pub title: String,
+    pub description: String,
+}
+
+fn name(command: &Value) -> &str {
+    command["name"].as_str().unwrap_or_default()
+}
+
+fn description(command: &Value) -> &str {
+    command["description"].as_str().unwrap_or_default()
+}
+
+/// Describes what an option expects, going by its Discord option type
+fn option_kind(option: &Value) -> &'static str {
+    match option["type"].as_u64() {
+        Some(3) => "text",
+        Some(4) => "whole number",
+        Some(5) => "true or false",
+        Some(6) => "user",
+        Some(7) => "channel",
+        Some(8) => "role",
+        Some(9) => "user or role",
+        Some(10) => "number",
+        Some(11) => "file",
+        _ => "value",
+    }
+}
+
+/// Lists every command, for the first page that `/help` shows
+pub fn overview(commands: &[Value]) -> Page {
+    let description = commands.iter().fold(
+        "Pick a command below for its options and examples, or *Task syntax* for how to write tasks.\n"
+            .to_string(),
+        |description, command| {
+            description + &format!("\n**/{}**: {}", name(command), self::description(command))
+        },
+    );
+    Page {
+        title: "Commands".to_string(),
+        description: limits::truncate(&description, limits::EMBED_DESCRIPTION),
+    }
+}
+
+/// Renders the page called `page`, see [`TASK_SYNTAX_PAGE`]
+pub fn page(commands: &[Value], page: &str) -> Option<Page> {
+    if page == TASK_SYNTAX_PAGE {
+        return Some(task_syntax());
+    }
+    let command = commands.iter().find(|command| name(command) == page)?;
+    let mut description = description(command).to_string();
+    let options = command["options"].as_array().map_or(&[][..], Vec::as_slice);
+    if !options.is_empty() {
+        description += "\n\n**Options**";
+    }
+    for option in options {
+        let mut kind = option_kind(option).to_string();
+        if let Some(choices) = option["choices"].as_array() {
+            let choices = choices
+                .iter()
+                .filter_map(|choice| choice["name"].as_str())
+                .collect::<Vec<_>>();
+            kind += &format!(", one of {}", choices.join(", "));
+        }
+        if option["required"].as_bool() != Some(true) {
+            kind += ", optional";
+        }
+        description += &format!(
+            "\n- `{}` ({kind}): {}",
+            name(option),
+            self::description(option)
+        );
+    }
+    if TASK_SYNTAX_COMMANDS.contains(&page) {
+        description +=
+            "\n\nTasks are written in the task syntax, pick *Task syntax* below for how it works.";
+    }
+    if let Some((_, examples)) = EXAMPLES.iter().find(|(command, _)| *command == page) {
+        description += &format!("\n\n**Examples**\n```\n{}\n```", examples.join("\n"));
+    }
+    Some(Page {
+        title: format!("/{page}"),
+        description: limits::truncate(&description, limits::EMBED_DESCRIPTION),
+    })
+}
+
+fn task_syntax() -> Page {
+    Page {
+        title: "Task syntax".to_string(),
+        description: format!(
+            "Tasks are separated by `;`, such as `300 bmats; flatbed`.\n\
+            - Start a task with an amount: `300 bmats`\n\
+            - Repeat a task with a multiplier: `{{3x}} 40 shirts` makes three copies (at most {MAX_MULTIPLIER})\n\
+            - Reserve a task for someone by mentioning them or a role before it: `@Someone: flatbed`, only they can claim or complete it\n\
+            - End a task with an effort estimate, in whatever unit your group uses (crates, trips, minutes...): `flatbed ~2`\n\
+            - End a task with a deadline, a time of day in your time zone (see /timezone) or how long from now: `40 shirts {{by 18:00}}` or `flatbed {{by 2h}}`\n\
+            - Anything after a `#` is a comment and is left out: `bmats # for the trucks`\n\
+            - Group the tasks after it under a header: `== Shirts ==; 40 shirts; 40 bandages`, and end the group with `====`\n\
+            - /request-multi makes a separate request out of each list of tasks between `|`s: `40 shirts | 300 bmats`\n\
+            - To use `;`, `#`, `{{`, `~`, `|` or `<` in a task, wrap it in double quotes (`\"fuel; diesel\"`) or put a backslash in front of it (`fuel\\; diesel`)\n\n\
+            **Example**\n```\n{{2x}} 300 bmats ~2; \"fuel; diesel\" # for the trucks; flatbed ~1\n```\n\
+            Commands that act on the tasks of an existing request, such as /split-request, refer to them by their numbers instead: `3`, `1-4`, or `1, 3-5`."
+        ),
+    }
+}
+
+/// The pages that can be picked, as (page, label, description)
+pub fn page_choices(commands: &[Value]) -> Vec<(String, String, String)> {
+    std::iter::once((
+        TASK_SYNTAX_PAGE.to_string(),
+        "Task syntax".to_string(),
+        "How to write the tasks of a request".to_string(),
+    ))
+    .chain(commands.iter().map(|command| {
+        (
+            name(command).to_string(),
+            format!("/{}", name(command)),
+            limits::truncate(description(command), limits::SELECT_OPTION_DESCRIPTION),
+        )
+    }))
+    .take(limits::SELECT_OPTIONS)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{overview, page, page_choices, TASK_SYNTAX_PAGE};
+
+    fn commands() -> Vec<serde_json::Value> {
+        vec![
+            json!({
+                "name": "request",
+                "description": "Make a new request",
+                "options": [
+                    {"name": "title", "description": "A summary of the request", "type": 3, "required": true},
+                    {
+                        "name": "kind",
+                        "description": "The kind of request",
+                        "type": 3,
+                        "required": true,
+                        "choices": [{"name": "Truck", "value": "Truck"}, {"name": "Plane", "value": "Plane"}],
+                    },
+                    {"name": "blocked_by", "description": "Link to a request", "type": 3},
+                ],
+            }),
+            json!({"name": "scopecreep", "description": "SCOPE CREEP"}),
+        ]
+    }
+
+    #[test]
+    fn documents_commands_and_their_options() {
+        let commands = commands();
+        assert!(overview(&commands)
+            .description
+            .ends_with("\n**/request**: Make a new request\n**/scopecreep**: SCOPE CREEP"));
+        let request = page(&commands, "request").unwrap();
+        assert_eq!(request.title, "/request");
+        assert!(request.description.starts_with(
+            "Make a new request\n\n**Options**\n\
+            - `title` (text): A summary of the request\n\
+            - `kind` (text, one of Truck, Plane): The kind of request\n\
+            - `blocked_by` (text, optional): Link to a request\n\n\
+            Tasks are written in the task syntax"
+        ));
+        assert!(request.description.contains("**Examples**"));
+        assert_eq!(
+            page(&commands, "scopecreep").unwrap().description,
+            "SCOPE CREEP"
+        );
+        assert!(page(&commands, "unknown").is_none());
+        assert!(page(&commands, TASK_SYNTAX_PAGE)
+            .unwrap()
+            .description
+            .contains("`{3x} 40 shirts`"));
+        let choices = page_choices(&commands)
+            .into_iter()
+            .map(|(page, _, _)| page)
+            .collect::<Vec<_>>();
+        assert_eq!(choices, [TASK_SYNTAX_PAGE, "request", "scopecreep"]);
+    }
+}
@@ -0,0 +1,155 @@
+//! Icons for the request kinds, which are shown as the thumbnails of requests
+//!
+//! Discord expires attachment links after a while, so the icons are shipped alongside the bot and
+//! uploaded to each Discord application as application emojis when it starts. Requests store the
+//! name of their icon, and link the image of the emoji when they are rendered.
+
+use std::path::Path;
+
+use entity::{
+    application_emoji,
+    discord_id::{kind, DiscordId},
+};
+use sea_orm::{
+    sea_query::OnConflict, ActiveValue::Set, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter,
+};
+use serenity::model::id::ApplicationId;
+use snafu::{ResultExt, Snafu};
+
+use crate::{discord_api::DiscordApi, discord_ids::ToDiscordId};
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum Error {
+    #[snafu(display("failed to list the application's emojis"))]
+    ListEmojis { source: serenity::Error },
+    #[snafu(display("failed to upload icon {name:?}"))]
+    UploadIcon {
+        source: serenity::Error,
+        name: String,
+    },
+    #[snafu(display("failed to save the application's emojis"))]
+    Database { source: DbErr },
+}
+
+/// Uploads the `icons` that `application` doesn't have an emoji for yet, from `{dir}/{icon}.png`
+///
+/// Icons without an image are skipped with a warning, requests with that icon are then shown
+/// without a thumbnail.
+pub async fn sync(
+    db: &DatabaseConnection,
+    api: &dyn DiscordApi,
+    application: ApplicationId,
+    dir: &Path,
+    icons: &[&str],
+) -> Result<(), Error> {
+    let mut emojis = api
+        .get_application_emojis()
+        .await
+        .context(error::ListEmojisSnafu)?;
+    for &icon in icons {
+        if emojis.iter().any(|(_, name)| name == icon) {
+            continue;
+        }
+        let path = dir.join(format!("{icon}.png"));
+        let image = match std::fs::read(&path) {
+            Ok(image) => image,
+            Err(err) => {
+                tracing::warn!(
+                    error = &err as &dyn std::error::Error,
+                    path = %path.display(),
+                    "failed to read icon, requests with it will have no thumbnail"
+                );
+                continue;
+            }
+        };
+        let emoji = api
+            .create_application_emoji(icon, &png_data_uri(&image))
+            .await
+            .context(error::UploadIconSnafu { name: icon })?;
+        emojis.push((emoji, icon.to_string()));
+    }
+    for (emoji, name) in emojis {
+        if !icons.contains(&name.as_str()) {
+            continue;
+        }
+        application_emoji::Entity::insert(application_emoji::ActiveModel {
+            discord_application_id: Set(application.db_id()),
+            name: Set(name),
+            discord_emoji_id: Set(emoji.db_id()),
+        })
+        .on_conflict(
+            OnConflict::columns([
+                application_emoji::Column::DiscordApplicationId,
+                application_emoji::Column::Name,
+            ])
+            .update_column(application_emoji::Column::DiscordEmojiId)
+            .to_owned(),
+        )
+        .exec(db)
+        .await
+        .context(error::DatabaseSnafu)?;
+    }
+    Ok(())
+}
+
+/// Links the image of `icon`, as uploaded to `application`
+///
+/// Requests made before multi-bot support have no application, they use any application's copy.
+pub async fn url(
+    db: &DatabaseConnection,
+    application: Option<DiscordId<kind::Application>>,
+    icon: &str,
+) -> Result<Option<String>, DbErr> {
+    let mut query =
+        application_emoji::Entity::find().filter(application_emoji::Column::Name.eq(icon));
+    if let Some(application) = application {
+        query = query.filter(application_emoji::Column::DiscordApplicationId.eq(application));
+    }
+    Ok(query.one(db).await?.map(|emoji| {
+        format!(
+            "https://cdn.discordapp.com/emojis/{}.png",
+            emoji.discord_emoji_id
+        )
+    }))
+}
+
+/// Encodes a PNG image as a data URI, which is how Discord takes uploaded emojis
+fn png_data_uri(image: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut uri = "data:image/png;base64,".to_string();
+    for chunk in image.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let bits = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                uri.push(ALPHABET[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                uri.push('=');
+            }
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::png_data_uri;
+
+    #[test]
+    fn encodes_images_as_base64() {
+        assert_eq!(png_data_uri(b""), "data:image/png;base64,");
+        assert_eq!(png_data_uri(b"f"), "data:image/png;base64,Zg==");
+        assert_eq!(png_data_uri(b"fo"), "data:image/png;base64,Zm8=");
+        assert_eq!(png_data_uri(b"foo"), "data:image/png;base64,Zm9v");
+        assert_eq!(
+            png_data_uri(b"foobar\xff"),
+            "data:image/png;base64,Zm9vYmFy/w=="
+        );
+    }
+}
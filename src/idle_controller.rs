@@ -0,0 +1,120 @@
+//! Alerts about requests that nobody has taken on, so that urgent requests don't silently rot
+//!
+//! Servers pick how long a request may go without any of its tasks being claimed (or completed)
+//! with `/server-idle-alert`. Requests that go over it are posted about in the server's feed
+//! channel (or its report channel, if it has no feed), and their creators are notified, see
+//! [`notifier`]. Each request is only alerted about once, until the limit is changed.
+
+use std::{collections::HashSet, time::Duration};
+
+use entity::{guild_setting, request};
+use sea_orm::{prelude::Uuid, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter};
+use time::OffsetDateTime;
+
+use crate::{
+    backoff::{self, Backoff},
+    discord_api::{self, DiscordApi},
+    discord_ids::FromDiscordId,
+    expiration_controller::Partition,
+    leader,
+    notifier::{self, Event},
+    request_link, stats,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+pub async fn run(db: &DatabaseConnection, discord: &dyn DiscordApi, partition: &Partition) {
+    // Keyed by the limit too, so that changing it alerts again
+    let mut alerted = HashSet::new();
+    let mut backoff = Backoff::new(POLL_INTERVAL, backoff::MAX_RETRY_DELAY);
+    loop {
+        let delay = match run_turn(db, discord, partition, &mut alerted).await {
+            Ok(()) => {
+                backoff.succeeded();
+                POLL_INTERVAL
+            }
+            Err(err) => {
+                let delay = backoff.failed();
+                tracing::error!(error = &err as &dyn std::error::Error, retry_in = ?delay, "failed to find idle requests, retrying...");
+                delay
+            }
+        };
+        tokio::time::sleep(delay).await;
+    }
+}
+
+async fn run_turn(
+    db: &DatabaseConnection,
+    discord: &dyn DiscordApi,
+    partition: &Partition,
+    alerted: &mut HashSet<(Uuid, i64)>,
+) -> Result<(), DbErr> {
+    let Some(leadership) =
+        leader::try_lead(db, leader::Controller::Idle, partition.application_id.0).await?
+    else {
+        return Ok(());
+    };
+    let now = OffsetDateTime::now_utc();
+    let settings = guild_setting::Entity::find()
+        .filter(guild_setting::Column::IdleAlertSecs.is_not_null())
+        .all(db)
+        .await?;
+    let mut still_idle = HashSet::new();
+    for settings in settings {
+        let Some(idle_secs) = settings.idle_alert_secs else {
+            continue;
+        };
+        // Alerts saved before they were capped may reach past the start of time, then no request
+        // can have been idle for that long
+        let Some(idle_since) = now.checked_sub(time::Duration::seconds(idle_secs)) else {
+            continue;
+        };
+        let channel = settings.feed_channel.or(settings.report_channel);
+        let idle_requests = request::Entity::find()
+            .filter(request::Column::ArchivedOn.is_null())
+            .filter(request::Column::FirstClaimedAt.is_null())
+            .filter(request::Column::DiscordGuildId.eq(settings.discord_guild_id))
+            .filter(request::Column::CreatedAt.lt(idle_since))
+            .filter(partition.condition())
+            .all(db)
+            .await?;
+        for req in idle_requests {
+            let key = (req.id, idle_secs);
+            still_idle.insert(key);
+            if alerted.contains(&key) {
+                continue;
+            }
+            let content = alert(&req, idle_secs);
+            if let Some(channel) = channel {
+                let channel = channel.discord();
+                if let Err(err) = discord
+                    .send_message(
+                        channel,
+                        discord_api::create_message(|msg| {
+                            msg.content(&content)
+                                .allowed_mentions(|mentions| mentions.empty_parse())
+                        }),
+                    )
+                    .await
+                {
+                    tracing::error!(error = &err as &dyn std::error::Error, request.id = %req.id, %channel, "failed to alert about idle request, retrying later...");
+                    continue;
+                }
+            }
+            alerted.insert(key);
+            notifier::notify_request(db, &req, Event::Idle, content).await?;
+        }
+    }
+    // Requests that were taken on or archived don't need to be remembered anymore
+    alerted.retain(|key| still_idle.contains(key));
+    leadership.release().await
+}
+
+fn alert(request: &request::Model, idle_secs: i64) -> String {
+    format!(
+        "💤 Nobody has taken on {} since it was made <t:{}:R>, longer than the {} allowed",
+        request_link(request).unwrap_or_else(|| request.title.clone()),
+        request.created_at.unix_timestamp(),
+        stats::format_time_to_completion(time::Duration::seconds(idle_secs))
+    )
+}
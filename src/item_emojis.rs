@@ -0,0 +1,151 @@
+//! Custom emojis that servers pick for the items of the catalog, see `/item-emoji`
+//!
+//! Tasks that are nothing but an amount of an item (such as `40 shirts` or `300 bmats`) are
+//! prefixed with the item's emoji when rendered, so that large requests can be scanned at a glance.
+//! Besides the items that `/mpf` knows (see [`production`]), emojis can be picked for the
+//! materials that they are made from.
+
+use std::collections::HashMap;
+
+use entity::item_emoji;
+use sea_orm::{
+    sea_query::OnConflict, ActiveValue::Set, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder,
+};
+use serenity::model::{channel::ReactionType, id::GuildId};
+
+use crate::{discord_ids::ToDiscordId, production, task_syntax};
+
+/// Materials that items are made from, and other names that they are commonly known by
+const MATERIALS: &[(&str, &[&str])] = &[
+    ("bmats", &["bmat", "basic materials"]),
+    ("rmats", &["rmat", "refined materials"]),
+    ("emats", &["emat", "explosive materials"]),
+    ("hemats", &["hemat", "heavy explosive materials"]),
+];
+
+/// The name of the catalog item or material that `name` refers to, ignoring case
+pub fn find(name: &str) -> Option<&'static str> {
+    let name = name.trim();
+    production::find(name).map(|item| item.name).or_else(|| {
+        MATERIALS
+            .iter()
+            .find(|(material, aliases)| {
+                material.eq_ignore_ascii_case(name)
+                    || aliases.iter().any(|alias| alias.eq_ignore_ascii_case(name))
+            })
+            .map(|(material, _)| *material)
+    })
+}
+
+/// Every name that an emoji can be picked for
+pub fn names() -> impl Iterator<Item = &'static str> {
+    MATERIALS
+        .iter()
+        .map(|(material, _)| *material)
+        .chain(production::items().iter().map(|item| item.name))
+}
+
+/// The catalog item or material that a task delivers, if it is nothing but an amount of one
+pub fn task_item(task: &str) -> Option<&'static str> {
+    let specs = task_syntax::parse(task).ok()?;
+    let [spec] = specs.as_slice() else {
+        return None;
+    };
+    find(&spec.item)
+}
+
+/// Parses an emoji that can be picked for an item, only custom emojis are accepted
+pub fn parse_emoji(emoji: &str) -> Option<ReactionType> {
+    match emoji.trim().parse().ok()? {
+        emoji @ ReactionType::Custom { .. } => Some(emoji),
+        _ => None,
+    }
+}
+
+/// The emoji of every item that the server has picked one for, by item name
+pub async fn emojis(
+    db: &DatabaseConnection,
+    guild: GuildId,
+) -> Result<HashMap<String, String>, DbErr> {
+    Ok(list(db, guild)
+        .await?
+        .into_iter()
+        .map(|item| (item.item, item.emoji))
+        .collect())
+}
+
+/// The server's emojis, by item name
+pub async fn list(
+    db: &DatabaseConnection,
+    guild: GuildId,
+) -> Result<Vec<item_emoji::Model>, DbErr> {
+    item_emoji::Entity::find()
+        .filter(item_emoji::Column::DiscordGuildId.eq(guild.db_id()))
+        .order_by_asc(item_emoji::Column::Item)
+        .all(db)
+        .await
+}
+
+/// Picks `emoji` for `item`, replacing the one picked before
+pub async fn set(
+    db: &DatabaseConnection,
+    guild: GuildId,
+    item: &str,
+    emoji: &ReactionType,
+) -> Result<(), DbErr> {
+    item_emoji::Entity::insert(item_emoji::ActiveModel {
+        discord_guild_id: Set(guild.db_id()),
+        item: Set(item.to_string()),
+        emoji: Set(emoji.to_string()),
+    })
+    .on_conflict(
+        OnConflict::columns([item_emoji::Column::DiscordGuildId, item_emoji::Column::Item])
+            .update_column(item_emoji::Column::Emoji)
+            .to_owned(),
+    )
+    .exec(db)
+    .await?;
+    Ok(())
+}
+
+/// Stops prefixing the tasks of `item` with an emoji, returning whether it had one
+pub async fn remove(db: &DatabaseConnection, guild: GuildId, item: &str) -> Result<bool, DbErr> {
+    let deleted = item_emoji::Entity::delete_many()
+        .filter(item_emoji::Column::DiscordGuildId.eq(guild.db_id()))
+        .filter(item_emoji::Column::Item.eq(item))
+        .exec(db)
+        .await?;
+    Ok(deleted.rows_affected > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find, parse_emoji, task_item};
+
+    #[test]
+    fn items_and_materials_are_found_by_any_name() {
+        assert_eq!(find("shirts"), Some("Soldier Supplies"));
+        assert_eq!(find(" Basic Materials "), Some("bmats"));
+        assert_eq!(find("HEMATS"), Some("hemats"));
+        assert_eq!(find("flatbed"), None);
+    }
+
+    #[test]
+    fn only_tasks_of_a_single_item_have_one() {
+        assert_eq!(task_item("300 bmats"), Some("bmats"));
+        assert_eq!(task_item("{2x} 40 shirts"), Some("Soldier Supplies"));
+        assert_eq!(task_item("shirts; bmats"), None);
+        assert_eq!(task_item("pick up the trucks"), None);
+    }
+
+    #[test]
+    fn only_custom_emojis_can_be_picked() {
+        assert_eq!(
+            parse_emoji("<:bmats:123>").map(|emoji| emoji.to_string()),
+            Some("<:bmats:123>".to_string())
+        );
+        assert_eq!(parse_emoji("📦"), None);
+        assert_eq!(parse_emoji("bmats"), None);
+    }
+}
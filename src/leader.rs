@@ -0,0 +1,60 @@
+//! Leader election between replicas of the bot, so that each controller's work is only done once
+//!
+//! Each turn of a controller runs while holding a transaction-scoped Postgres advisory lock, which
+//! Postgres releases when the transaction ends, including when the replica holding it dies.
+//! Replicas that can't take the lock skip their turn, since another replica is doing the work.
+
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbBackend, DbErr, Statement,
+    TransactionTrait,
+};
+
+/// The work that a lock is taken for, which namespaces the lock keys
+#[derive(Clone, Copy)]
+pub enum Controller {
+    // 1 was the expiration controller, which claims requests with `SKIP LOCKED` instead
+    Reminder = 2,
+    MetricsExport = 3,
+    UserProfile = 4,
+    Refresh = 5,
+    Front = 6,
+    Backlog = 7,
+    Notification = 8,
+    Idle = 9,
+    Season = 10,
+    Badge = 11,
+    Bump = 12,
+    Outbox = 13,
+}
+
+/// Proof of being the leader, which lasts until it is released or dropped
+pub struct Leadership(DatabaseTransaction);
+
+impl Leadership {
+    pub async fn release(self) -> Result<(), DbErr> {
+        self.0.commit().await
+    }
+}
+
+/// Tries to become the leader for `controller` within `scope` (such as a bot's application ID)
+///
+/// Returns [`None`] if another replica is the leader.
+pub async fn try_lead(
+    db: &DatabaseConnection,
+    controller: Controller,
+    scope: u64,
+) -> Result<Option<Leadership>, DbErr> {
+    let txn = db.begin().await?;
+    let locked = txn
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT pg_try_advisory_xact_lock($1, $2) AS locked",
+            // Postgres' two-key locks are 32-bit, the low bits of a snowflake are unique enough
+            [(controller as i32).into(), (scope as i32).into()],
+        ))
+        .await?
+        .map(|row| row.try_get::<bool>("", "locked"))
+        .transpose()?
+        .unwrap_or(false);
+    Ok(locked.then_some(Leadership(txn)))
+}
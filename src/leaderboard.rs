@@ -0,0 +1,226 @@
+//! Who completed the most tasks in a server, see `/leaderboard`
+//!
+//! Servers that pick a season length with `/server-seasons` have their leaderboard start over every
+//! week or every war, see [`crate::season_controller`]. Past seasons are kept, so that their
+//! leaderboards can still be looked up by number. Servers without seasons have a single
+//! leaderboard of all time.
+
+use std::collections::HashMap;
+
+use entity::{
+    discord_id::{kind, DiscordId},
+    request, season, task, user,
+};
+use sea_orm::{
+    prelude::Uuid, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect,
+};
+use serenity::model::id::GuildId;
+use time::OffsetDateTime;
+
+use crate::{discord_ids::ToDiscordId, forget};
+
+/// How many contributors a leaderboard shows
+pub const SIZE: usize = 10;
+
+/// How long a server's seasons last
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, strum::AsRefStr, strum::EnumIter, strum::EnumString,
+)]
+#[strum(serialize_all = "kebab-case")]
+pub enum SeasonLength {
+    /// A week from when the season started
+    Weekly,
+    /// Until the next war starts
+    War,
+}
+
+/// A contributor's place on a leaderboard
+#[derive(Debug, PartialEq, Eq)]
+pub struct Standing {
+    pub user: DiscordId<kind::User>,
+    /// How many tasks they completed
+    pub completed: usize,
+}
+
+/// The season of `guild` that is going on, if it has seasons
+pub async fn current_season(
+    db: &DatabaseConnection,
+    guild: GuildId,
+) -> Result<Option<season::Model>, DbErr> {
+    season::Entity::find()
+        .filter(season::Column::DiscordGuildId.eq(guild.db_id()))
+        .filter(season::Column::EndedAt.is_null())
+        .order_by_desc(season::Column::Number)
+        .one(db)
+        .await
+}
+
+/// Ranks who completed the tasks of `guild`'s requests within `season`, or ever if there is none
+///
+/// Forgotten users are left out, since they are all credited to the same tombstone.
+pub async fn standings(
+    db: &DatabaseConnection,
+    guild: GuildId,
+    season: Option<&season::Model>,
+) -> Result<Vec<Standing>, DbErr> {
+    let mut completers = task::Entity::find()
+        .select_only()
+        .column(task::Column::CompletedBy)
+        .inner_join(request::Entity)
+        .filter(request::Column::DiscordGuildId.eq(guild.db_id()))
+        .filter(request::Column::MergedInto.is_null())
+        .filter(task::Column::CompletedBy.is_not_null())
+        .filter(task::Column::CompletedAt.is_not_null())
+        .filter(task::Column::MovedTo.is_null())
+        .filter(task::Column::RemovedAt.is_null());
+    if let Some(season) = season {
+        completers = completers.filter(task::Column::CompletedAt.gte(season.started_at));
+        if let Some(ended_at) = season.ended_at {
+            completers = completers.filter(task::Column::CompletedAt.lt(ended_at));
+        }
+    }
+    let ranked = rank(completers.into_tuple::<Uuid>().all(db).await?);
+    let users = user::Entity::find()
+        .filter(user::Column::Id.is_in(ranked.iter().map(|(id, _)| *id)))
+        .filter(user::Column::DiscordUserId.ne(forget::TOMBSTONE.db_id()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|user| (user.id, user.discord_user_id))
+        .collect::<HashMap<_, _>>();
+    Ok(ranked
+        .into_iter()
+        .filter_map(|(id, completed)| {
+            Some(Standing {
+                user: *users.get(&id)?,
+                completed,
+            })
+        })
+        .collect())
+}
+
+/// Counts how many tasks each of `completers` completed, most first
+///
+/// Ties are broken by user ID, so that tied contributors are listed the same way every time.
+fn rank(completers: impl IntoIterator<Item = Uuid>) -> Vec<(Uuid, usize)> {
+    let mut counts = HashMap::new();
+    for completer in completers {
+        *counts.entry(completer).or_insert(0) += 1;
+    }
+    let mut ranked = counts.into_iter().collect::<Vec<_>>();
+    ranked.sort_by(|(a_id, a), (b_id, b)| b.cmp(a).then(a_id.cmp(b_id)));
+    ranked
+}
+
+/// Renders the top of a leaderboard, one contributor per line
+pub fn format(standings: &[Standing], size: usize) -> String {
+    standings
+        .iter()
+        .take(size)
+        .enumerate()
+        .map(|(i, standing)| {
+            let place = match i {
+                0 => "🥇".to_string(),
+                1 => "🥈".to_string(),
+                2 => "🥉".to_string(),
+                _ => format!("{}.", i + 1),
+            };
+            format!(
+                "{place} <@{}>: {} task(s)\n",
+                standing.user, standing.completed
+            )
+        })
+        .collect()
+}
+
+/// Whether `season` is over, now that `war` is being fought (if it is known)
+///
+/// Seasons per war that don't know which war they were started in yet never end, see
+/// [`crate::season_controller`].
+pub fn is_over(
+    season: &season::Model,
+    length: SeasonLength,
+    now: OffsetDateTime,
+    war: Option<i32>,
+) -> bool {
+    match length {
+        SeasonLength::Weekly => now - season.started_at >= time::Duration::weeks(1),
+        SeasonLength::War => season
+            .war_number
+            .zip(war)
+            .is_some_and(|(season_war, war)| season_war != war),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use entity::{discord_id::DiscordId, season};
+    use sea_orm::prelude::Uuid;
+    use time::{Duration, OffsetDateTime};
+
+    use super::{format, is_over, rank, SeasonLength, Standing};
+
+    #[test]
+    fn contributors_are_ranked_by_completed_tasks() {
+        let (a, b, c) = (Uuid::from_u128(1), Uuid::from_u128(2), Uuid::from_u128(3));
+        assert_eq!(rank([c, a, b, b, a, b]), [(b, 3), (a, 2), (c, 1)]);
+        // Ties are broken the same way every time
+        assert_eq!(rank([b, a]), [(a, 1), (b, 1)]);
+        assert!(rank([]).is_empty());
+    }
+
+    #[test]
+    fn leaderboards_award_medals_to_the_top_three() {
+        let standings = (1..=5)
+            .map(|i| Standing {
+                user: DiscordId::new(i),
+                completed: 10 - i as usize,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            format(&standings, 4),
+            "🥇 <@1>: 9 task(s)\n🥈 <@2>: 8 task(s)\n🥉 <@3>: 7 task(s)\n4. <@4>: 6 task(s)\n"
+        );
+    }
+
+    #[test]
+    fn seasons_end_after_a_week_or_with_the_war() {
+        let started_at = OffsetDateTime::UNIX_EPOCH;
+        let season = season::Model {
+            discord_guild_id: DiscordId::new(10),
+            number: 1,
+            started_at,
+            ended_at: None,
+            war_number: Some(110),
+        };
+        let weekly = SeasonLength::Weekly;
+        assert!(!is_over(
+            &season,
+            weekly,
+            started_at + Duration::days(6),
+            None
+        ));
+        assert!(is_over(
+            &season,
+            weekly,
+            started_at + Duration::days(7),
+            None
+        ));
+        let war = SeasonLength::War;
+        assert!(!is_over(
+            &season,
+            war,
+            started_at + Duration::days(30),
+            Some(110)
+        ));
+        assert!(is_over(&season, war, started_at, Some(111)));
+        // The War API might be down
+        assert!(!is_over(&season, war, started_at, None));
+        let season = season::Model {
+            war_number: None,
+            ..season
+        };
+        assert!(!is_over(&season, war, started_at, Some(111)));
+    }
+}
@@ -0,0 +1,203 @@
+//! Discord's limits on message size, and validation that requests stay within them
+//!
+//! See <https://discord.com/developers/docs/resources/channel#embed-object-embed-limits>
+//! and <https://discord.com/developers/docs/interactions/message-components>.
+
+use std::time::Duration;
+
+use snafu::{ensure, ResultExt, Snafu};
+
+/// Maximum length of a message's content
+pub const MESSAGE_CONTENT: usize = 2000;
+/// Maximum length of a single embed's title
+pub const EMBED_TITLE: usize = 256;
+/// Maximum length of a single embed's description
+pub const EMBED_DESCRIPTION: usize = 4096;
+/// Maximum total length of all embeds in a single message
+pub const EMBED_TOTAL: usize = 6000;
+/// Maximum number of embeds in a single message
+pub const EMBEDS: usize = 10;
+/// Maximum number of fields in a single embed
+pub const EMBED_FIELDS: usize = 25;
+/// Maximum length of an embed field's name
+pub const EMBED_FIELD_NAME: usize = 256;
+/// Maximum length of an embed field's value
+pub const EMBED_FIELD_VALUE: usize = 1024;
+/// Maximum number of action rows in a single message
+pub const ACTION_ROWS: usize = 5;
+/// Maximum number of options in a single select menu
+pub const SELECT_OPTIONS: usize = 25;
+/// Maximum length of a select menu option's label
+pub const SELECT_OPTION_LABEL: usize = 100;
+/// Maximum length of a select menu option's description
+pub const SELECT_OPTION_DESCRIPTION: usize = 100;
+/// Maximum length of a component's custom ID, which carries its payload
+pub const CUSTOM_ID: usize = 100;
+/// Maximum length of a modal's title
+pub const MODAL_TITLE: usize = 45;
+/// Maximum number of choices suggested while autocompleting an option
+pub const AUTOCOMPLETE_CHOICES: usize = 25;
+/// Maximum length of an autocompletion choice's name and value
+pub const AUTOCOMPLETE_CHOICE: usize = 100;
+
+/// Maximum length of a request title, leaving room in the content for the expiry and archival lines
+pub const REQUEST_TITLE: usize = 256;
+/// Maximum number of tasks rendered in a single message
+///
+/// Every uncompleted task appears in the completion menu and in either the claim or unclaim menu,
+/// and every completed task in the un-completion menu. All of their pages must fit in [`ACTION_ROWS`],
+/// which only holds for a single page of each: any more and the claim, unclaim, and completion menus
+/// could take up two rows each.
+pub const TASKS_PER_MESSAGE: usize = SELECT_OPTIONS;
+/// Number of task select menus in a message, see [`TASKS_PER_MESSAGE`]
+const TASK_MENUS: usize = 4;
+/// Number of action rows for buttons, such as adding notes or repeating the request
+const BUTTON_ROWS: usize = 1;
+const _: () =
+    assert!(TASK_MENUS * TASKS_PER_MESSAGE.div_ceil(SELECT_OPTIONS) + BUTTON_ROWS <= ACTION_ROWS);
+/// Maximum number of messages that a request may be split across
+pub const REQUEST_MESSAGES: usize = 10;
+/// Maximum number of tasks in a request
+pub const REQUEST_TASKS: usize = TASKS_PER_MESSAGE * REQUEST_MESSAGES;
+/// Maximum number of requests made at once with `/request-multi`
+pub const MULTI_REQUESTS: usize = 10;
+/// Maximum size of a file imported with `/request-import`, which is plenty for [`REQUEST_TASKS`] rows
+pub const IMPORT_FILE_SIZE: usize = 64 * 1024;
+/// Maximum length of a preset's name, which is suggested as an autocompletion choice
+pub const PRESET_NAME: usize = AUTOCOMPLETE_CHOICE;
+/// Maximum number of crates of a single item that `/mpf` works out the cost of
+pub const MPF_CRATES: u32 = 1000;
+/// Maximum length of a request note
+pub const NOTE_LENGTH: usize = 200;
+/// Maximum number of notes on a request, so that they all fit in a single message
+pub const REQUEST_NOTES: usize = 20;
+/// Maximum number of delivery screenshots on a request, each is shown in its own embed
+pub const REQUEST_ATTACHMENTS: usize = EMBEDS;
+/// Worst-case length of everything but the text in a rendered note line
+const NOTE_LINE_OVERHEAD: usize = 60;
+const _: () = assert!(REQUEST_NOTES * (NOTE_LENGTH + NOTE_LINE_OVERHEAD) <= EMBED_TOTAL);
+/// Maximum number of repeats that a request's message links to, the rest are only counted so that
+/// the message stays within [`MESSAGE_CONTENT`]
+pub const REPEATS_LISTED: usize = 5;
+/// Maximum length of a quip, which is shown in the footer of a request
+pub const QUIP: usize = 100;
+/// Maximum length of the notice shown while a server's requests are frozen
+pub const FREEZE_NOTICE: usize = 200;
+/// Maximum length of the comment that a volunteer leaves when claiming a task
+pub const CLAIM_COMMENT: usize = 50;
+/// Worst-case length of everything but the task text in a rendered task line, including the claim's ETA and comment
+/// and the task's deadline
+const TASK_LINE_OVERHEAD: usize = 135 + CLAIM_COMMENT;
+/// Worst-case length of what only the lines of open tasks show: the claim's ETA and comment, and
+/// the task's deadline (`, ETA <t:…:R>: comment, due <t:…:R>`)
+const OPEN_TASK_EXTRAS: usize = 22 + 2 + CLAIM_COMMENT + 22;
+/// Maximum length of the screenshot link or note that a task was completed with, which takes the
+/// place of the [`OPEN_TASK_EXTRAS`] on the lines of completed tasks (as `, [📎](link)`)
+pub const COMPLETION_EVIDENCE: usize = OPEN_TASK_EXTRAS - 7;
+/// Worst-case length of the embed title, footer, and requester line
+const EMBED_OVERHEAD: usize = 200 + QUIP;
+
+/// Longest duration that the bot accepts (a year, as humantime counts it), so that it can always be
+/// added to the current time
+pub const DURATION: Duration = Duration::from_secs(31_557_600);
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum Error {
+    #[snafu(display("the title is {len} characters long, but may be at most {REQUEST_TITLE}"))]
+    TitleTooLong { len: usize },
+    #[snafu(display("the request has {count} tasks, but may have at most {REQUEST_TASKS}"))]
+    TooManyTasks { count: usize },
+    #[snafu(display(
+        "tasks {first}-{last} are {len} characters long in total, but may be at most {max} characters"
+    ))]
+    TasksTooLong {
+        first: usize,
+        last: usize,
+        len: usize,
+        max: usize,
+    },
+}
+
+/// Checks that a request with the given title and tasks can be rendered without exceeding Discord's limits
+pub fn validate_request<'a>(
+    title: &str,
+    tasks: impl IntoIterator<Item = &'a str>,
+) -> Result<(), Error> {
+    let title_len = title.chars().count();
+    ensure!(
+        title_len <= REQUEST_TITLE,
+        error::TitleTooLongSnafu { len: title_len }
+    );
+    let task_lens = tasks
+        .into_iter()
+        .map(|task| task.chars().count())
+        .collect::<Vec<_>>();
+    let count = task_lens.len();
+    ensure!(count <= REQUEST_TASKS, error::TooManyTasksSnafu { count });
+    // Each message has its own embed limits
+    for (page, page_lens) in task_lens.chunks(TASKS_PER_MESSAGE).enumerate() {
+        let len = page_lens.iter().sum::<usize>();
+        let max = EMBED_TOTAL - EMBED_OVERHEAD - page_lens.len() * TASK_LINE_OVERHEAD;
+        let first = page * TASKS_PER_MESSAGE + 1;
+        ensure!(
+            len <= max,
+            error::TasksTooLongSnafu {
+                first,
+                last: first + page_lens.len() - 1,
+                len,
+                max,
+            }
+        );
+    }
+    Ok(())
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum ParseDurationError {
+    #[snafu(display("{text:?} is not a duration"))]
+    Invalid {
+        text: String,
+        source: humantime::DurationError,
+    },
+    #[snafu(display("{text:?} is too long, durations may be at most a year"))]
+    TooLong { text: String },
+}
+
+/// Parses `text` as a humantime duration of at most [`DURATION`]
+pub fn parse_duration(text: &str) -> Result<Duration, ParseDurationError> {
+    let duration =
+        humantime::parse_duration(text).context(parse_duration_error::InvalidSnafu { text })?;
+    ensure!(
+        duration <= DURATION,
+        parse_duration_error::TooLongSnafu { text }
+    );
+    Ok(duration)
+}
+
+/// Truncates `text` to at most `max` characters, marking it with an ellipsis if anything was cut
+pub fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        text.to_string()
+    } else {
+        text.chars().take(max - 1).chain(['…']).collect::<String>()
+    }
+}
+
+/// Splits `lines` into chunks of at most `max` characters, without breaking up lines
+///
+/// Lines that are longer than `max` on their own are truncated.
+pub fn chunk_lines<'a>(lines: impl IntoIterator<Item = &'a str>, max: usize) -> Vec<String> {
+    let mut chunks = vec![String::new()];
+    for line in lines {
+        let line = truncate(line, max);
+        let chunk = chunks.last_mut().unwrap();
+        if !chunk.is_empty() && chunk.chars().count() + line.chars().count() > max {
+            chunks.push(line);
+        } else {
+            chunk.push_str(&line);
+        }
+    }
+    chunks
+}
@@ -0,0 +1,49 @@
+//! Links to Discord messages, as copied by "Copy Message Link"
+
+use std::str::FromStr;
+
+use regex::Regex;
+use serenity::model::id::{ChannelId, GuildId, MessageId};
+use snafu::{OptionExt, Snafu};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageLink {
+    /// [`None`] for DMs
+    pub guild: Option<GuildId>,
+    pub channel: ChannelId,
+    pub message: MessageId,
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(display("{link:?} is not a Discord message link"))]
+pub struct ParseMessageLinkError {
+    link: String,
+}
+
+impl FromStr for MessageLink {
+    type Err = ParseMessageLinkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let link_regex = Regex::new(
+            r"^<?https://(?:(?:ptb|canary)\.)?discord(?:app)?\.com/channels/(@me|\d+)/(\d+)/(\d+)/?>?$",
+        )
+        .unwrap();
+        let (_, [guild, channel, message]) = link_regex
+            .captures(s.trim())
+            .context(ParseMessageLinkSnafu { link: s })?
+            .extract();
+        let id = |id: &str| {
+            id.parse::<u64>()
+                .ok()
+                .context(ParseMessageLinkSnafu { link: s })
+        };
+        Ok(Self {
+            guild: match guild {
+                "@me" => None,
+                guild => Some(GuildId(id(guild)?)),
+            },
+            channel: ChannelId(id(channel)?),
+            message: MessageId(id(message)?),
+        })
+    }
+}
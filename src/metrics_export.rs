@@ -0,0 +1,510 @@
+//! Pushes weekly per-guild metrics to external trackers, for regiments that track logistics quotas
+//! outside of Discord
+//!
+//! Each complete week (Monday to Monday, UTC) is exported once, as one row per guild, to a webhook
+//! (as CSV) and/or a Google Sheets spreadsheet (appended to, using a service account). Weeks that
+//! were missed, such as while the bot was down or a target was unreachable, are caught up on.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use entity::{
+    discord_id::{kind, DiscordId},
+    metrics_export, request, task, user,
+};
+use sea_orm::{
+    ActiveValue::Set, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use time::OffsetDateTime;
+
+use crate::{leader, stats, user_profile};
+
+/// Number of contributors listed per guild
+const TOP_CONTRIBUTORS: usize = 5;
+/// How long export targets may take to connect and to answer, so that a stuck one can't hold up
+/// the export loop forever
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const GOOGLE_TOKEN_SCOPE: &str = "https://www.googleapis.com/auth/spreadsheets";
+const CSV_HEADER: [&str; 7] = [
+    "week_start",
+    "guild_id",
+    "requests_created",
+    "requests_completed",
+    "tasks_completed",
+    "top_contributors",
+    "top_contributor_names",
+];
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum Error {
+    #[snafu(display("failed to load metrics"))]
+    Load { source: DbErr },
+    #[snafu(display("failed to push metrics to webhook"))]
+    Webhook { source: reqwest::Error },
+    #[snafu(display("failed to read Google service account key from {path:?}"))]
+    ReadServiceAccount {
+        source: std::io::Error,
+        path: PathBuf,
+    },
+    #[snafu(display("failed to parse Google service account key from {path:?}"))]
+    ParseServiceAccount {
+        source: serde_json::Error,
+        path: PathBuf,
+    },
+    #[snafu(display("failed to sign Google access token request"))]
+    SignToken { source: jsonwebtoken::errors::Error },
+    #[snafu(display("failed to get Google access token"))]
+    GetToken { source: reqwest::Error },
+    #[snafu(display("failed to append metrics to Google Sheet"))]
+    AppendSheet { source: reqwest::Error },
+    #[snafu(display("failed to record export"))]
+    RecordExport { source: DbErr },
+}
+
+/// A Google Sheets spreadsheet that metrics are appended to
+pub struct GoogleSheet {
+    pub spreadsheet_id: String,
+    /// The sheet (tab) to append to
+    pub sheet: String,
+    /// Path to the service account key, as downloaded from the Google Cloud console
+    pub service_account: PathBuf,
+}
+
+/// Where metrics should be exported to, see [`run`]
+pub struct Targets {
+    pub webhook_url: Option<String>,
+    pub google_sheet: Option<GoogleSheet>,
+}
+
+impl Targets {
+    pub fn is_empty(&self) -> bool {
+        self.webhook_url.is_none() && self.google_sheet.is_none()
+    }
+}
+
+/// Metrics for one guild over one week
+#[derive(Debug, PartialEq)]
+pub struct GuildMetrics {
+    pub week_start: OffsetDateTime,
+    pub guild: DiscordId<kind::Guild>,
+    pub requests_created: usize,
+    pub requests_completed: usize,
+    pub tasks_completed: usize,
+    /// Most tasks first
+    pub top_contributors: Vec<Contributor>,
+}
+
+/// Someone who completed tasks in a guild
+#[derive(Debug, PartialEq)]
+pub struct Contributor {
+    pub user: DiscordId<kind::User>,
+    /// See [`user_profile::display_name`]
+    pub name: String,
+    pub tasks: usize,
+}
+
+impl GuildMetrics {
+    /// Finds the metrics for `guild`, starting from zero if there are none yet
+    fn entry(
+        guilds: &mut BTreeMap<DiscordId<kind::Guild>, Self>,
+        week_start: OffsetDateTime,
+        guild: DiscordId<kind::Guild>,
+    ) -> &mut Self {
+        guilds.entry(guild).or_insert_with(|| Self {
+            week_start,
+            guild,
+            requests_created: 0,
+            requests_completed: 0,
+            tasks_completed: 0,
+            top_contributors: Vec::new(),
+        })
+    }
+
+    fn to_row(&self) -> [String; 7] {
+        [
+            self.week_start.date().to_string(),
+            self.guild.to_string(),
+            self.requests_created.to_string(),
+            self.requests_completed.to_string(),
+            self.tasks_completed.to_string(),
+            self.top_contributors
+                .iter()
+                .map(|contributor| format!("{}:{}", contributor.user, contributor.tasks))
+                .collect::<Vec<_>>()
+                .join(" "),
+            // Names can contain spaces, so they get their own column rather than replacing the IDs
+            self.top_contributors
+                .iter()
+                .map(|contributor| contributor.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ]
+    }
+}
+
+pub async fn run(db: &DatabaseConnection, targets: &Targets) {
+    let http = reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .expect("failed to build the metrics export client");
+    loop {
+        if let Err(err) = run_turn(db, &http, targets).await {
+            tracing::error!(
+                error = &err as &dyn std::error::Error,
+                "failed to export metrics, retrying later..."
+            );
+        }
+        tokio::time::sleep(Duration::from_secs(60 * 60)).await;
+    }
+}
+
+async fn run_turn(
+    db: &DatabaseConnection,
+    http: &reqwest::Client,
+    targets: &Targets,
+) -> Result<(), Error> {
+    let Some(leadership) = leader::try_lead(db, leader::Controller::MetricsExport, 0)
+        .await
+        .context(error::LoadSnafu)?
+    else {
+        return Ok(());
+    };
+    let last_exported = metrics_export::Entity::find()
+        .order_by_desc(metrics_export::Column::PeriodStart)
+        .one(db)
+        .await
+        .context(error::LoadSnafu)?
+        .map(|export| export.period_start);
+    // Weeks are only recorded once they are exported, so a failed week is retried on the next turn
+    // before any of the weeks after it
+    for week_start in weeks_to_export(last_exported, OffsetDateTime::now_utc()) {
+        export_week(db, http, targets, week_start).await?;
+    }
+    leadership.release().await.context(error::RecordExportSnafu)
+}
+
+/// The start of the week (Monday, UTC) that `at` is in
+fn week_start(at: OffsetDateTime) -> OffsetDateTime {
+    (at.date() - time::Duration::days(at.weekday().number_days_from_monday().into()))
+        .midnight()
+        .assume_utc()
+}
+
+/// The starts of the complete weeks before `now` that haven't been exported yet, oldest first
+///
+/// Weeks that were missed (such as while the bot was down) are caught up on, starting after the
+/// last exported week. The first export only covers the previous week.
+fn weeks_to_export(
+    last_exported: Option<OffsetDateTime>,
+    now: OffsetDateTime,
+) -> Vec<OffsetDateTime> {
+    let this_week = week_start(now);
+    let mut week = match last_exported {
+        Some(last_exported) => week_start(last_exported) + time::Duration::weeks(1),
+        None => this_week - time::Duration::weeks(1),
+    };
+    let mut weeks = Vec::new();
+    while week < this_week {
+        weeks.push(week);
+        week += time::Duration::weeks(1);
+    }
+    weeks
+}
+
+/// Exports the metrics of the week starting at `week_start` to `targets`, and records that it was
+async fn export_week(
+    db: &DatabaseConnection,
+    http: &reqwest::Client,
+    targets: &Targets,
+    week_start: OffsetDateTime,
+) -> Result<(), Error> {
+    let metrics = weekly_metrics(db, week_start, week_start + time::Duration::weeks(1))
+        .await
+        .context(error::LoadSnafu)?;
+    tracing::info!(week.start = %week_start, guilds = metrics.len(), "exporting weekly metrics");
+    if let Some(url) = &targets.webhook_url {
+        http.post(url)
+            .header(reqwest::header::CONTENT_TYPE, "text/csv")
+            .body(to_csv(&metrics))
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .context(error::WebhookSnafu)?;
+    }
+    if let Some(sheet) = &targets.google_sheet {
+        append_to_google_sheet(http, sheet, &metrics).await?;
+    }
+    metrics_export::Entity::insert(metrics_export::ActiveModel {
+        period_start: Set(week_start),
+        ..Default::default()
+    })
+    .exec(db)
+    .await
+    .context(error::RecordExportSnafu)?;
+    Ok(())
+}
+
+/// Gathers metrics for every guild that had any activity between `start` and `end`
+pub async fn weekly_metrics(
+    db: &DatabaseConnection,
+    start: OffsetDateTime,
+    end: OffsetDateTime,
+) -> Result<Vec<GuildMetrics>, DbErr> {
+    let mut guilds = BTreeMap::<_, GuildMetrics>::new();
+    let created = request::Entity::find()
+        .filter(request::Column::CreatedAt.gte(start))
+        .filter(request::Column::CreatedAt.lt(end))
+        .filter(request::Column::MergedInto.is_null())
+        .all(db)
+        .await?;
+    for guild_id in created
+        .iter()
+        .filter_map(|request| request.discord_guild_id)
+    {
+        GuildMetrics::entry(&mut guilds, start, guild_id).requests_created += 1;
+    }
+
+    let completed_tasks = task::Entity::find()
+        .filter(task::Column::CompletedAt.gte(start))
+        .filter(task::Column::CompletedAt.lt(end))
+        .filter(task::Column::RemovedAt.is_null())
+        .find_also_related(request::Entity)
+        .all(db)
+        .await?;
+    let users = user::Entity::find()
+        .filter(
+            user::Column::Id.is_in(
+                completed_tasks
+                    .iter()
+                    .filter_map(|(task, _)| task.completed_by)
+                    .collect::<HashSet<_>>(),
+            ),
+        )
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|user| (user.id, user))
+        .collect::<HashMap<_, _>>();
+    let mut contributors = HashMap::<_, usize>::new();
+    let mut touched_requests = HashSet::new();
+    for (task, request) in &completed_tasks {
+        let Some(guild_id) = request
+            .as_ref()
+            .and_then(|request| request.discord_guild_id)
+        else {
+            continue;
+        };
+        GuildMetrics::entry(&mut guilds, start, guild_id).tasks_completed += 1;
+        touched_requests.insert(task.request);
+        if let Some(user) = task.completed_by.and_then(|user| users.get(&user)) {
+            *contributors.entry((guild_id, user.id)).or_default() += 1;
+        }
+    }
+    for ((guild_id, user), tasks) in contributors {
+        let user = &users[&user];
+        GuildMetrics::entry(&mut guilds, start, guild_id)
+            .top_contributors
+            .push(Contributor {
+                user: user.discord_user_id,
+                name: user_profile::display_name(user),
+                tasks,
+            });
+    }
+
+    // A request is completed in the week that its last task is
+    let touched_requests = request::Entity::find()
+        .filter(request::Column::Id.is_in(touched_requests))
+        .find_with_related(task::Entity)
+        .all(db)
+        .await?;
+    for (request, tasks) in &touched_requests {
+        let Some(guild_id) = request.discord_guild_id else {
+            continue;
+        };
+        let completed_at = stats::completed_at(request, tasks);
+        if completed_at.is_some_and(|completed_at| completed_at >= start && completed_at < end) {
+            GuildMetrics::entry(&mut guilds, start, guild_id).requests_completed += 1;
+        }
+    }
+
+    Ok(guilds
+        .into_values()
+        .map(|mut metrics| {
+            metrics
+                .top_contributors
+                .sort_by(|a, b| b.tasks.cmp(&a.tasks).then(a.user.cmp(&b.user)));
+            metrics.top_contributors.truncate(TOP_CONTRIBUTORS);
+            metrics
+        })
+        .collect())
+}
+
+/// Writes out `metrics` as CSV, with a header row
+pub fn to_csv(metrics: &[GuildMetrics]) -> String {
+    let mut csv = CSV_HEADER.join(",") + "\r\n";
+    for metrics in metrics {
+        let row = metrics.to_row().map(|field| {
+            if field.contains([',', '"', '\r', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        });
+        csv += &row.join(",");
+        csv += "\r\n";
+    }
+    csv
+}
+
+#[derive(Deserialize)]
+struct ServiceAccountKey {
+    client_email: String,
+    private_key: String,
+    token_uri: String,
+}
+
+#[derive(Serialize)]
+struct TokenClaims<'a> {
+    iss: &'a str,
+    scope: &'a str,
+    aud: &'a str,
+    iat: i64,
+    exp: i64,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+/// Exchanges the service account key for a short-lived access token
+///
+/// See <https://developers.google.com/identity/protocols/oauth2/service-account#httprest>.
+async fn google_access_token(http: &reqwest::Client, path: &Path) -> Result<String, Error> {
+    let key = tokio::fs::read(path)
+        .await
+        .context(error::ReadServiceAccountSnafu { path })?;
+    let key: ServiceAccountKey =
+        serde_json::from_slice(&key).context(error::ParseServiceAccountSnafu { path })?;
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let assertion = jsonwebtoken::encode(
+        &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::RS256),
+        &TokenClaims {
+            iss: &key.client_email,
+            scope: GOOGLE_TOKEN_SCOPE,
+            aud: &key.token_uri,
+            iat: now,
+            exp: now + 60 * 60,
+        },
+        &jsonwebtoken::EncodingKey::from_rsa_pem(key.private_key.as_bytes())
+            .context(error::SignTokenSnafu)?,
+    )
+    .context(error::SignTokenSnafu)?;
+    let response: TokenResponse = http
+        .post(&key.token_uri)
+        .form(&[
+            ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+            ("assertion", &assertion),
+        ])
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .context(error::GetTokenSnafu)?
+        .json()
+        .await
+        .context(error::GetTokenSnafu)?;
+    Ok(response.access_token)
+}
+
+async fn append_to_google_sheet(
+    http: &reqwest::Client,
+    sheet: &GoogleSheet,
+    metrics: &[GuildMetrics],
+) -> Result<(), Error> {
+    if metrics.is_empty() {
+        return Ok(());
+    }
+    let token = google_access_token(http, &sheet.service_account).await?;
+    let mut url = reqwest::Url::parse("https://sheets.googleapis.com/v4/spreadsheets/")
+        .expect("Google Sheets API URL must be valid");
+    url.path_segments_mut()
+        .expect("Google Sheets API URL must have a path")
+        .pop_if_empty()
+        .push(&sheet.spreadsheet_id)
+        .push("values")
+        .push(&format!("{}:append", sheet.sheet));
+    http.post(url)
+        .query(&[("valueInputOption", "RAW")])
+        .bearer_auth(token)
+        .json(&serde_json::json!({
+            "values": metrics.iter().map(GuildMetrics::to_row).collect::<Vec<_>>(),
+        }))
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .context(error::AppendSheetSnafu)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use entity::discord_id::DiscordId;
+    use time::{macros::datetime, OffsetDateTime};
+
+    use super::{to_csv, weeks_to_export, Contributor, GuildMetrics};
+
+    #[test]
+    fn csv_rows() {
+        let metrics = GuildMetrics {
+            // 2024-07-15
+            week_start: OffsetDateTime::from_unix_timestamp(1721001600).unwrap(),
+            guild: DiscordId::new(42),
+            requests_created: 3,
+            requests_completed: 2,
+            tasks_completed: 7,
+            top_contributors: vec![
+                Contributor {
+                    user: DiscordId::new(1),
+                    name: "Alice".to_string(),
+                    tasks: 5,
+                },
+                Contributor {
+                    user: DiscordId::new(2),
+                    name: "Bob".to_string(),
+                    tasks: 2,
+                },
+            ],
+        };
+        assert_eq!(
+            to_csv(&[metrics]),
+            "week_start,guild_id,requests_created,requests_completed,tasks_completed,top_contributors,top_contributor_names\r\n\
+             2024-07-15,42,3,2,7,1:5 2:2,\"Alice, Bob\"\r\n"
+        );
+    }
+
+    #[test]
+    fn missed_weeks_are_caught_up_on() {
+        // A Wednesday
+        let now = datetime!(2026-10-14 12:00 UTC);
+        assert_eq!(
+            weeks_to_export(None, now),
+            [datetime!(2026-10-05 00:00 UTC)]
+        );
+        assert_eq!(
+            weeks_to_export(Some(datetime!(2026-09-21 00:00 UTC)), now),
+            [
+                datetime!(2026-09-28 00:00 UTC),
+                datetime!(2026-10-05 00:00 UTC)
+            ]
+        );
+        assert!(weeks_to_export(Some(datetime!(2026-10-05 00:00 UTC)), now).is_empty());
+    }
+}
@@ -0,0 +1,219 @@
+//! Mirrors of requests in the channels of partner servers, such as those of allied regiments
+//!
+//! A [`mirror_rule`] copies the requests made in one channel into a channel of another server,
+//! once admins of both servers have approved it with `/request-mirrors`. Mirrors show the first
+//! page of the request without its menus, linking to the original for claiming tasks instead, and
+//! are re-rendered whenever the original is.
+
+use entity::{mirror_rule, request, request_mirror};
+use sea_orm::{
+    prelude::Uuid, ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection, DbErr,
+    EntityTrait, ModelTrait, QueryFilter,
+};
+use serenity::{
+    builder::CreateComponents,
+    model::{
+        application::component::ButtonStyle,
+        id::{ChannelId, GuildId, MessageId},
+    },
+};
+use snafu::{OptionExt, ResultExt, Snafu};
+
+use crate::{
+    discord_api,
+    discord_api::DiscordApi,
+    discord_ids::{FromDiscordId, ToDiscordId},
+    render_request, request_link, RenderedRequest,
+};
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum Error {
+    Database {
+        source: DbErr,
+    },
+    #[snafu(display("request {request} not found"))]
+    RequestNotFound {
+        request: Uuid,
+    },
+    #[snafu(display("failed to post mirror in {channel}"))]
+    SendMirror {
+        source: serenity::Error,
+        channel: ChannelId,
+    },
+    #[snafu(display("failed to update mirror {message}"))]
+    EditMirror {
+        source: serenity::Error,
+        message: MessageId,
+    },
+}
+
+/// Posts or re-renders the mirrors of a request, in every channel that its channel is mirrored into
+///
+/// Requests that were archived before their channel was mirrored are left alone, and requests that
+/// are restricted to a role are never mirrored, since the partner server can't tell who has it.
+pub async fn sync(
+    db: &DatabaseConnection,
+    api: &dyn DiscordApi,
+    request_id: Uuid,
+) -> Result<(), Error> {
+    let request = request::Entity::find_by_id(request_id)
+        .one(db)
+        .await
+        .context(error::DatabaseSnafu)?
+        .context(error::RequestNotFoundSnafu {
+            request: request_id,
+        })?;
+    let (Some(channel), Some(_), None) = (
+        request.discord_channel_id,
+        request.discord_message_id,
+        request.restricted_to_role,
+    ) else {
+        return Ok(());
+    };
+    let rules = mirror_rule::Entity::find()
+        .filter(mirror_rule::Column::FromChannel.eq(channel))
+        .filter(mirror_rule::Column::ApprovedBySource.eq(true))
+        .filter(mirror_rule::Column::ApprovedByTarget.eq(true))
+        .all(db)
+        .await
+        .context(error::DatabaseSnafu)?;
+    if rules.is_empty() {
+        return Ok(());
+    }
+    let mirrors = request
+        .find_related(request_mirror::Entity)
+        .all(db)
+        .await
+        .context(error::DatabaseSnafu)?;
+    let rendered = mirror(
+        &request,
+        render_request(db, request_id)
+            .await
+            .into_iter()
+            .next()
+            .expect("request rendered no messages"),
+    );
+    for rule in rules {
+        let to_channel = rule.to_channel.discord();
+        match mirrors
+            .iter()
+            .find(|mirror| mirror.discord_channel_id == rule.to_channel)
+        {
+            Some(mirror) => {
+                let message = mirror.discord_message_id.discord();
+                let rendered = rendered.clone();
+                api.edit_message(
+                    to_channel,
+                    message,
+                    discord_api::edit_message(|r| rendered.edit_message(r)),
+                )
+                .await
+                .context(error::EditMirrorSnafu { message })?;
+            }
+            None if request.archived_on.is_none() => {
+                let rendered = rendered.clone();
+                let message = api
+                    .send_message(
+                        to_channel,
+                        discord_api::create_message(|msg| rendered.create_message(msg)),
+                    )
+                    .await
+                    .context(error::SendMirrorSnafu {
+                        channel: to_channel,
+                    })?;
+                request_mirror::ActiveModel {
+                    request: Set(request_id),
+                    discord_channel_id: Set(rule.to_channel),
+                    discord_message_id: Set(message.db_id()),
+                }
+                .insert(db)
+                .await
+                .context(error::DatabaseSnafu)?;
+            }
+            None => {}
+        }
+    }
+    Ok(())
+}
+
+/// Swaps the menus of a rendered request for a link to the original, which is where tasks are claimed
+fn mirror(request: &request::Model, mut rendered: RenderedRequest) -> RenderedRequest {
+    let mut components = CreateComponents::default();
+    if let Some(link) = request_link(request).filter(|_| request.archived_on.is_none()) {
+        components.create_action_row(|row| {
+            row.create_button(|button| {
+                button
+                    .style(ButtonStyle::Link)
+                    .url(link)
+                    .label("Claim tasks")
+            })
+        });
+    }
+    rendered.components = components;
+    rendered
+}
+
+/// Describes a mirror rule from the point of view of `guild`, which is on one side of it
+pub fn describe(rule: &mirror_rule::Model, guild: GuildId) -> String {
+    let (description, approved_here, approved_there) = if rule.from_guild == guild.db_id() {
+        (
+            format!(
+                "Requests in <#{}> are mirrored into channel `{}` of a partner server",
+                rule.from_channel, rule.to_channel
+            ),
+            rule.approved_by_source,
+            rule.approved_by_target,
+        )
+    } else {
+        (
+            format!(
+                "Requests in channel `{}` of a partner server are mirrored into <#{}>",
+                rule.from_channel, rule.to_channel
+            ),
+            rule.approved_by_target,
+            rule.approved_by_source,
+        )
+    };
+    match (approved_here, approved_there) {
+        (true, true) => description,
+        (_, false) => format!("{description} (waiting for the partner server to approve)"),
+        (false, true) => format!("{description} (waiting for this server to approve)"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use entity::{discord_id::DiscordId, mirror_rule};
+    use serenity::model::id::GuildId;
+
+    use super::describe;
+
+    #[test]
+    fn describes_rules_from_either_side() {
+        let rule = mirror_rule::Model {
+            from_channel: DiscordId::new(10),
+            to_channel: DiscordId::new(20),
+            from_guild: DiscordId::new(1),
+            to_guild: DiscordId::new(2),
+            approved_by_source: true,
+            approved_by_target: false,
+        };
+        assert_eq!(
+            describe(&rule, GuildId(1)),
+            "Requests in <#10> are mirrored into channel `20` of a partner server (waiting for the partner server to approve)"
+        );
+        assert_eq!(
+            describe(&rule, GuildId(2)),
+            "Requests in channel `10` of a partner server are mirrored into <#20> (waiting for this server to approve)"
+        );
+        let rule = mirror_rule::Model {
+            approved_by_target: true,
+            ..rule
+        };
+        assert_eq!(
+            describe(&rule, GuildId(2)),
+            "Requests in channel `10` of a partner server are mirrored into <#20>"
+        );
+    }
+}
@@ -0,0 +1,153 @@
+//! Delivers queued notifications, see [`crate::notifier`]
+//!
+//! Notifications are delivered by the bot that they were queued for, since a bot can only DM users
+//! that it shares a server with. A notification that fails to be delivered is retried on the next
+//! turns, until it has failed [`MAX_ATTEMPTS`] times.
+//!
+//! Announcements to a server's contributors are delivered a few at a time, in their own batch, so
+//! that a large server's announcement neither floods Discord with DMs nor holds up notifications
+//! about requests.
+
+use std::{str::FromStr, time::Duration};
+
+use entity::{guild_setting, notification, user};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    ModelTrait, QueryFilter, QueryOrder, QuerySelect,
+};
+use snafu::Report;
+use time::OffsetDateTime;
+
+use crate::{
+    backoff::{self, Backoff},
+    discord_api::DiscordApi,
+    expiration_controller::Partition,
+    leader,
+    notifier::{self, Backends, Event, Target},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// Number of notifications delivered per turn
+const BATCH_SIZE: u64 = 50;
+/// Number of announcements delivered per turn, on top of [`BATCH_SIZE`]
+const ANNOUNCEMENT_BATCH_SIZE: u64 = 10;
+/// Number of failed deliveries after which a notification is given up on
+pub const MAX_ATTEMPTS: i32 = 5;
+
+pub async fn run(
+    db: &DatabaseConnection,
+    discord: &dyn DiscordApi,
+    backends: &Backends,
+    partition: &Partition,
+) {
+    let mut backoff = Backoff::new(POLL_INTERVAL, backoff::MAX_RETRY_DELAY);
+    loop {
+        let delay = match run_turn(db, discord, backends, partition).await {
+            Ok(()) => {
+                backoff.succeeded();
+                POLL_INTERVAL
+            }
+            Err(err) => {
+                let delay = backoff.failed();
+                tracing::error!(error = &err as &dyn std::error::Error, retry_in = ?delay, "failed to deliver notifications, retrying...");
+                delay
+            }
+        };
+        tokio::time::sleep(delay).await;
+    }
+}
+
+pub(crate) async fn run_turn(
+    db: &DatabaseConnection,
+    discord: &dyn DiscordApi,
+    backends: &Backends,
+    partition: &Partition,
+) -> Result<(), DbErr> {
+    let Some(leadership) = leader::try_lead(
+        db,
+        leader::Controller::Notification,
+        partition.application_id.0,
+    )
+    .await?
+    else {
+        return Ok(());
+    };
+    let pending = || {
+        notification::Entity::find()
+            .filter(notification::Column::SentAt.is_null())
+            .filter(notification::Column::Attempts.lt(MAX_ATTEMPTS))
+            .filter(partition.condition_on(notification::Column::DiscordApplicationId))
+            .order_by_asc(notification::Column::CreatedAt)
+    };
+    let mut pending_notifications = pending()
+        .filter(notification::Column::Event.ne(Event::Announcement.as_ref()))
+        .limit(BATCH_SIZE)
+        .all(db)
+        .await?;
+    pending_notifications.extend(
+        pending()
+            .filter(notification::Column::Event.eq(Event::Announcement.as_ref()))
+            .limit(ANNOUNCEMENT_BATCH_SIZE)
+            .all(db)
+            .await?,
+    );
+    for notification in pending_notifications {
+        let Ok(event) = Event::from_str(&notification.event) else {
+            // Such as from a newer version of the bot, which would deliver it itself
+            tracing::warn!(notification.id = %notification.id, event = notification.event, "skipping notification of unknown event");
+            continue;
+        };
+        let Some(target) = target(db, &notification).await? else {
+            // The recipient doesn't want to be notified anymore
+            notification.delete(db).await?;
+            continue;
+        };
+        let delivered = match backends.notifier(discord, &target) {
+            Some(notifier) => notifier.notify(event, &notification.content).await,
+            None => Err(notifier::Error::Unavailable),
+        };
+        let update = match delivered {
+            Ok(()) => notification::ActiveModel {
+                sent_at: Set(Some(OffsetDateTime::now_utc())),
+                ..Default::default()
+            },
+            Err(err) => {
+                tracing::warn!(error = &err as &dyn std::error::Error, notification.id = %notification.id, attempts = notification.attempts + 1, "failed to deliver notification");
+                notification::ActiveModel {
+                    attempts: Set(notification.attempts + 1),
+                    error: Set(Some(Report::from_error(err).to_string())),
+                    ..Default::default()
+                }
+            }
+        };
+        notification::ActiveModel {
+            id: sea_orm::ActiveValue::Unchanged(notification.id),
+            ..update
+        }
+        .update(db)
+        .await?;
+    }
+    leadership.release().await
+}
+
+/// Where `notification` is delivered to: its recipient's pick, or its server's webhook
+async fn target(
+    db: &DatabaseConnection,
+    notification: &notification::Model,
+) -> Result<Option<Target>, DbErr> {
+    Ok(
+        match (notification.recipient, notification.discord_guild_id) {
+            (Some(recipient), _) => user::Entity::find_by_id(recipient)
+                .one(db)
+                .await?
+                .as_ref()
+                .and_then(notifier::user_target),
+            (None, Some(guild)) => guild_setting::Entity::find_by_id(guild)
+                .one(db)
+                .await?
+                .and_then(|settings| settings.notify_webhook_url)
+                .map(Target::Webhook),
+            (None, None) => None,
+        },
+    )
+}
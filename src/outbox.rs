@@ -0,0 +1,172 @@
+//! Messages that couldn't be posted because their channel was unavailable, kept to be posted later
+//!
+//! When Discord is having trouble (a 5xx) or the bot has lost access to a channel for a while, the
+//! rendered message is stored with when to try it again, rather than being lost. So far this is
+//! only used for moving requests to their archive channel: the request is archived in place in the
+//! meantime, and [`crate::outbox_controller`] finishes the move once the channel is reachable again.
+
+use entity::{outbox_message, request};
+use sea_orm::{
+    prelude::Uuid, ActiveModelTrait, ActiveValue::Set, DatabaseConnection, DbErr, EntityTrait,
+    ModelTrait,
+};
+use serenity::{
+    http::HttpError,
+    json::Value,
+    model::id::{ChannelId, MessageId},
+};
+use snafu::{OptionExt, ResultExt, Snafu};
+use time::{Duration, OffsetDateTime};
+
+use crate::{
+    discord_api::DiscordApi,
+    discord_ids::{FromDiscordId, ToDiscordId},
+    finish_archive_move, render_request, ArchiveRequestError,
+};
+
+/// Number of failed attempts after which a queued message is given up on
+pub const MAX_ATTEMPTS: i32 = 50;
+/// How long to wait before trying a message again for the first time
+const RETRY_DELAY: Duration = Duration::seconds(30);
+/// The longest that a message that keeps failing waits between attempts
+const MAX_RETRY_DELAY: Duration = Duration::hours(1);
+
+/// Discord's error code for channels that the bot can't see
+const MISSING_ACCESS: isize = 50001;
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum Error {
+    Database {
+        source: DbErr,
+    },
+    #[snafu(display("request {request} not found"))]
+    RequestNotFound {
+        request: Uuid,
+    },
+    #[snafu(display("failed to post the queued message in {channel}"))]
+    SendMessage {
+        source: serenity::Error,
+        channel: ChannelId,
+    },
+    #[snafu(display("failed to move the request to its archive channel"))]
+    MoveRequest {
+        source: ArchiveRequestError,
+    },
+}
+
+/// Whether posting in a channel failed in a way that may fix itself, so that it is worth trying again
+pub fn is_channel_unavailable(err: &serenity::Error) -> bool {
+    match err {
+        serenity::Error::Http(err) => matches!(
+            &**err,
+            HttpError::UnsuccessfulRequest(response)
+                if response.status_code.is_server_error() || response.error.code == MISSING_ACCESS
+        ),
+        _ => false,
+    }
+}
+
+/// How long to wait before the next attempt, after `attempts` have failed
+fn retry_delay(attempts: i32) -> Duration {
+    let factor = 1 << (attempts - 1).clamp(0, 16);
+    (RETRY_DELAY * factor).min(MAX_RETRY_DELAY)
+}
+
+/// Keeps the `payload` of `request`'s archived message, which failed to post in `channel` with `error`
+pub async fn queue(
+    db: &DatabaseConnection,
+    request: Uuid,
+    channel: ChannelId,
+    payload: Value,
+    error: &serenity::Error,
+) -> Result<outbox_message::Model, DbErr> {
+    outbox_message::ActiveModel {
+        request: Set(request),
+        discord_channel_id: Set(channel.db_id()),
+        payload: Set(payload),
+        attempts: Set(1),
+        next_attempt_at: Set(OffsetDateTime::now_utc() + retry_delay(1)),
+        last_error: Set(Some(error.to_string())),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+}
+
+/// Tries to post a queued message again, and finishes moving its request if it goes through
+///
+/// Returns the posted message, or [`None`] if the channel is still unavailable, in which case the
+/// message is kept for another attempt. Messages that fail otherwise, or too often, are dropped,
+/// leaving their request archived in place.
+pub async fn deliver(
+    db: &DatabaseConnection,
+    api: &dyn DiscordApi,
+    message: outbox_message::Model,
+    now: OffsetDateTime,
+) -> Result<Option<MessageId>, Error> {
+    let channel = message.discord_channel_id.discord();
+    let request = request::Entity::find_by_id(message.request)
+        .one(db)
+        .await
+        .context(error::DatabaseSnafu)?
+        .context(error::RequestNotFoundSnafu {
+            request: message.request,
+        })?;
+    let archived_msg = match api.send_message(channel, message.payload.clone()).await {
+        Ok(archived_msg) => archived_msg,
+        Err(source) if is_channel_unavailable(&source) && message.attempts < MAX_ATTEMPTS => {
+            let attempts = message.attempts + 1;
+            outbox_message::ActiveModel {
+                attempts: Set(attempts),
+                next_attempt_at: Set(now + retry_delay(attempts)),
+                last_error: Set(Some(source.to_string())),
+                ..message.into()
+            }
+            .update(db)
+            .await
+            .context(error::DatabaseSnafu)?;
+            return Ok(None);
+        }
+        Err(source) => {
+            message.delete(db).await.context(error::DatabaseSnafu)?;
+            return Err(source).context(error::SendMessageSnafu { channel });
+        }
+    };
+    // Posted now, so it must not be posted again even if the rest of the move fails
+    message.delete(db).await.context(error::DatabaseSnafu)?;
+
+    if let (Some(from_channel), Some(old_message)) =
+        (request.discord_channel_id, request.discord_message_id)
+    {
+        if let Err(err) = api
+            .delete_message(from_channel.discord(), old_message.discord())
+            .await
+        {
+            tracing::warn!(
+                error = &err as &dyn std::error::Error,
+                request.id = %request.id,
+                "failed to delete the request's message from before it was archived, ignoring..."
+            );
+        }
+    }
+    let pages = render_request(db, request.id).await.into_iter().skip(1);
+    finish_archive_move(db, api, &request, channel, archived_msg, pages)
+        .await
+        .context(error::MoveRequestSnafu)?;
+    Ok(Some(archived_msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Duration;
+
+    use super::{retry_delay, MAX_RETRY_DELAY};
+
+    #[test]
+    fn retries_back_off_until_an_hour() {
+        let delays = (1..=4).map(retry_delay).collect::<Vec<_>>();
+        assert_eq!(delays, [30, 60, 120, 240].map(Duration::seconds));
+        assert_eq!(retry_delay(40), MAX_RETRY_DELAY);
+    }
+}
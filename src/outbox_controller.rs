@@ -0,0 +1,70 @@
+//! Posts the messages that were queued in the [`outbox`] once their channel is reachable again
+
+use std::time::Duration;
+
+use entity::{outbox_message, request};
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder};
+use time::OffsetDateTime;
+
+use crate::{
+    backoff::{self, Backoff},
+    discord_api::DiscordApi,
+    expiration_controller::Partition,
+    leader, outbox,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+pub async fn run(db: &DatabaseConnection, discord: &dyn DiscordApi, partition: &Partition) {
+    let mut backoff = Backoff::new(POLL_INTERVAL, backoff::MAX_RETRY_DELAY);
+    loop {
+        let delay = match run_turn(db, discord, partition).await {
+            Ok(()) => {
+                backoff.succeeded();
+                POLL_INTERVAL
+            }
+            Err(err) => {
+                let delay = backoff.failed();
+                tracing::error!(error = &err as &dyn std::error::Error, retry_in = ?delay, "failed to deliver queued messages, retrying...");
+                delay
+            }
+        };
+        tokio::time::sleep(delay).await;
+    }
+}
+
+pub async fn run_turn(
+    db: &DatabaseConnection,
+    discord: &dyn DiscordApi,
+    partition: &Partition,
+) -> Result<(), DbErr> {
+    let Some(leadership) =
+        leader::try_lead(db, leader::Controller::Outbox, partition.application_id.0).await?
+    else {
+        return Ok(());
+    };
+    let now = OffsetDateTime::now_utc();
+    let due = outbox_message::Entity::find()
+        .inner_join(request::Entity)
+        .filter(outbox_message::Column::NextAttemptAt.lte(now))
+        .filter(partition.condition())
+        .order_by_asc(outbox_message::Column::CreatedAt)
+        .all(db)
+        .await?;
+    for message in due {
+        let id = message.id;
+        let request = message.request;
+        match outbox::deliver(db, discord, message, now).await {
+            Ok(Some(_)) => {
+                tracing::info!(outbox_message.id = %id, request.id = %request, "delivered queued message")
+            }
+            Ok(None) => {
+                tracing::debug!(outbox_message.id = %id, request.id = %request, "channel is still unavailable, retrying later...")
+            }
+            Err(err) => {
+                tracing::warn!(error = &err as &dyn std::error::Error, outbox_message.id = %id, request.id = %request, "failed to deliver queued message");
+            }
+        }
+    }
+    leadership.release().await
+}
@@ -0,0 +1,115 @@
+//! The colours of request embeds, which show the kind of a request while it is open and how far
+//! along it is after that
+//!
+//! Each server picks a [`Palette`] with `/server-palette`.
+
+use crate::RequestType;
+
+/// How far along a request is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RequestState {
+    /// No tasks have been completed yet
+    Open,
+    /// Some, but not all, tasks have been completed
+    InProgress,
+    /// Unfinished, and about to expire
+    ExpiringSoon,
+    /// Expired (or was archived) before all tasks were completed
+    Expired,
+    /// All tasks have been completed
+    Complete,
+}
+
+impl RequestState {
+    /// Works out the state from the number of tasks that have been `completed` out of `total`
+    pub fn new(completed: usize, total: usize, expired: bool, expiring_soon: bool) -> Self {
+        if total > 0 && completed == total {
+            RequestState::Complete
+        } else if expired {
+            RequestState::Expired
+        } else if expiring_soon {
+            RequestState::ExpiringSoon
+        } else if completed > 0 {
+            RequestState::InProgress
+        } else {
+            RequestState::Open
+        }
+    }
+}
+
+/// The colours that a server's requests are shown in
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, strum::AsRefStr, strum::EnumIter, strum::EnumString,
+)]
+pub enum Palette {
+    #[default]
+    Default,
+    /// Colours that stay apart for the common kinds of colour blindness (Okabe-Ito)
+    Colourblind,
+    /// Leaves embeds in Discord's default colour
+    Off,
+}
+
+impl Palette {
+    /// The colour of a request's embeds, if any
+    pub fn colour(self, kind: &RequestType, state: RequestState) -> Option<u32> {
+        let colour = match (self, state) {
+            (Palette::Off, _) => return None,
+            (Palette::Default, RequestState::InProgress) => 0xF1C40F,
+            (Palette::Default, RequestState::ExpiringSoon | RequestState::Expired) => 0xE74C3C,
+            (Palette::Default, RequestState::Complete) => 0x2ECC71,
+            (Palette::Default, RequestState::Open) => match kind {
+                RequestType::General => 0x5865F2,
+                RequestType::Truck => 0x3498DB,
+                RequestType::Flatbed => 0x9B59B6,
+                RequestType::Freighter => 0x1ABC9C,
+                RequestType::Train => 0xE67E22,
+            },
+            (Palette::Colourblind, RequestState::InProgress) => 0xF0E442,
+            (Palette::Colourblind, RequestState::ExpiringSoon | RequestState::Expired) => 0xD55E00,
+            (Palette::Colourblind, RequestState::Complete) => 0x009E73,
+            (Palette::Colourblind, RequestState::Open) => match kind {
+                RequestType::General => 0x999999,
+                RequestType::Truck => 0x0072B2,
+                RequestType::Flatbed => 0x56B4E9,
+                RequestType::Freighter => 0xCC79A7,
+                RequestType::Train => 0xE69F00,
+            },
+        };
+        Some(colour)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Palette, RequestState};
+    use crate::RequestType;
+
+    #[test]
+    fn colours_follow_the_state_of_the_request() {
+        let state = |completed, expired| RequestState::new(completed, 3, expired, false);
+        assert_eq!(state(0, false), RequestState::Open);
+        assert_eq!(state(1, false), RequestState::InProgress);
+        assert_eq!(state(1, true), RequestState::Expired);
+        assert_eq!(state(3, true), RequestState::Complete);
+        assert_eq!(
+            RequestState::new(0, 0, false, false),
+            RequestState::Open,
+            "requests without tasks are never complete"
+        );
+
+        let colour = |kind, state| Palette::Default.colour(&kind, state);
+        assert_ne!(
+            colour(RequestType::Truck, RequestState::Open),
+            colour(RequestType::Train, RequestState::Open)
+        );
+        assert_eq!(
+            colour(RequestType::Truck, RequestState::Complete),
+            colour(RequestType::Train, RequestState::Complete)
+        );
+        assert_eq!(
+            Palette::Off.colour(&RequestType::Truck, RequestState::Expired),
+            None
+        );
+    }
+}
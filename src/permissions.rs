@@ -0,0 +1,106 @@
+//! Checks that the bot may do what it is about to do in a channel, so that a missing permission
+//! can be explained to the server's admins instead of failing halfway through with a 403
+
+use serenity::model::{id::ChannelId, Permissions};
+use snafu::{ResultExt, Snafu};
+
+use crate::discord_api::DiscordApi;
+
+/// Needed to post requests (and their follow-up messages) in a channel
+pub const POST: Permissions = Permissions::VIEW_CHANNEL
+    .union(Permissions::SEND_MESSAGES)
+    .union(Permissions::EMBED_LINKS);
+/// Needed to remove a request's messages from a channel when it is moved to an archive channel
+pub const DELETE: Permissions = Permissions::VIEW_CHANNEL.union(Permissions::MANAGE_MESSAGES);
+/// Needed to pin open requests in a channel, and to unpin them once they are archived
+pub const PIN: Permissions = Permissions::VIEW_CHANNEL.union(Permissions::MANAGE_MESSAGES);
+
+/// What each permission lets the bot do, and what Discord calls it in the server settings
+const DESCRIPTIONS: &[(Permissions, &str, &str)] = &[
+    (Permissions::VIEW_CHANNEL, "see messages", "View Channel"),
+    (Permissions::SEND_MESSAGES, "send messages", "Send Messages"),
+    (Permissions::EMBED_LINKS, "embed links", "Embed Links"),
+    (
+        Permissions::MANAGE_MESSAGES,
+        "delete and pin messages",
+        "Manage Messages",
+    ),
+];
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum Error {
+    #[snafu(display("failed to look up my permissions in <#{channel}>"))]
+    Lookup {
+        source: serenity::Error,
+        channel: ChannelId,
+    },
+    #[snafu(display(
+        "I can't {} in <#{channel}>, ask a server admin to grant me {} there",
+        describe(*missing, |(_, action, _)| action, " or "),
+        describe(*missing, |(_, _, name)| name, " and "),
+    ))]
+    Missing {
+        channel: ChannelId,
+        missing: Permissions,
+    },
+}
+
+fn describe(
+    permissions: Permissions,
+    part: impl Fn(&(Permissions, &'static str, &'static str)) -> &'static str,
+    separator: &str,
+) -> String {
+    DESCRIPTIONS
+        .iter()
+        .filter(|(permission, _, _)| permissions.contains(*permission))
+        .map(part)
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Checks that the bot has all of the `required` permissions in `channel`
+///
+/// Channels outside of servers (such as DMs) have no permissions to check, so they always pass.
+pub async fn ensure(
+    api: &dyn DiscordApi,
+    channel: ChannelId,
+    required: Permissions,
+) -> Result<(), Error> {
+    let Some(granted) = api
+        .get_bot_permissions(channel)
+        .await
+        .context(error::LookupSnafu { channel })?
+    else {
+        return Ok(());
+    };
+    let missing = required - granted;
+    snafu::ensure!(missing.is_empty(), error::MissingSnafu { channel, missing });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::model::{id::ChannelId, Permissions};
+
+    use super::{Error, DELETE, POST};
+
+    #[test]
+    fn explains_missing_permissions() {
+        let missing = |missing| {
+            Error::Missing {
+                channel: ChannelId(10),
+                missing,
+            }
+            .to_string()
+        };
+        assert_eq!(
+            missing(DELETE - Permissions::VIEW_CHANNEL),
+            "I can't delete and pin messages in <#10>, ask a server admin to grant me Manage Messages there"
+        );
+        assert_eq!(
+            missing(POST - Permissions::VIEW_CHANNEL),
+            "I can't send messages or embed links in <#10>, ask a server admin to grant me Send Messages and Embed Links there"
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! Pinning open requests in the channels that ask for it, so that they stay easy to find
+//!
+//! Channels are picked with `/request-pins`. A request's first message is pinned once it has been
+//! posted in such a channel, and unpinned again when the request is archived.
+
+use entity::{pin_channel, request};
+use sea_orm::{
+    prelude::Uuid, ActiveModelTrait, ActiveValue::Set, DatabaseConnection, DbErr, EntityTrait,
+};
+use serenity::model::id::MessageId;
+use snafu::{OptionExt, ResultExt, Snafu};
+
+use crate::{discord_api::DiscordApi, discord_ids::FromDiscordId, permissions};
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum Error {
+    Database {
+        source: DbErr,
+    },
+    #[snafu(display("request {request} not found"))]
+    RequestNotFound {
+        request: Uuid,
+    },
+    #[snafu(display("not allowed to pin the request"))]
+    Permissions {
+        source: permissions::Error,
+    },
+    #[snafu(display("failed to pin {message}"))]
+    Pin {
+        source: serenity::Error,
+        message: MessageId,
+    },
+    #[snafu(display("failed to unpin {message}"))]
+    Unpin {
+        source: serenity::Error,
+        message: MessageId,
+    },
+}
+
+/// Pins the request while it is open in a channel that pins requests, and unpins it otherwise
+pub async fn sync(
+    db: &DatabaseConnection,
+    api: &dyn DiscordApi,
+    request_id: Uuid,
+) -> Result<(), Error> {
+    let request = request::Entity::find_by_id(request_id)
+        .one(db)
+        .await
+        .context(error::DatabaseSnafu)?
+        .context(error::RequestNotFoundSnafu {
+            request: request_id,
+        })?;
+    let (Some(channel), Some(message)) = (request.discord_channel_id, request.discord_message_id)
+    else {
+        return Ok(());
+    };
+    // Archived requests are unpinned while they are being archived, see [`unpin`]
+    if request.archived_on.is_some() {
+        return Ok(());
+    }
+    let pins_requests = pin_channel::Entity::find_by_id(channel)
+        .one(db)
+        .await
+        .context(error::DatabaseSnafu)?
+        .is_some();
+    if !pins_requests {
+        return unpin(db, api, &request).await;
+    }
+    if request.pinned {
+        return Ok(());
+    }
+    let (channel, message) = (channel.discord(), message.discord());
+    permissions::ensure(api, channel, permissions::PIN)
+        .await
+        .context(error::PermissionsSnafu)?;
+    api.pin_message(channel, message)
+        .await
+        .context(error::PinSnafu { message })?;
+    set_pinned(db, request.id, true).await
+}
+
+/// Unpins the request if it has been pinned, before it is archived
+pub async fn unpin(
+    db: &DatabaseConnection,
+    api: &dyn DiscordApi,
+    request: &request::Model,
+) -> Result<(), Error> {
+    let (true, Some(channel), Some(message)) = (
+        request.pinned,
+        request.discord_channel_id,
+        request.discord_message_id,
+    ) else {
+        return Ok(());
+    };
+    let message = message.discord();
+    api.unpin_message(channel.discord(), message)
+        .await
+        .context(error::UnpinSnafu { message })?;
+    set_pinned(db, request.id, false).await
+}
+
+async fn set_pinned(db: &DatabaseConnection, request_id: Uuid, pinned: bool) -> Result<(), Error> {
+    request::ActiveModel {
+        id: sea_orm::ActiveValue::Unchanged(request_id),
+        pinned: Set(pinned),
+        ..Default::default()
+    }
+    .update(db)
+    .await
+    .context(error::DatabaseSnafu)?;
+    Ok(())
+}
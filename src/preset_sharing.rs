@@ -0,0 +1,139 @@
+//! Sharing `/request` presets between servers as codes, with `/request-presets share` and `import`
+//!
+//! A code is the preset as JSON, so that it survives being pasted around in chat. Anything that only
+//! means something in the server that shared it is left out of the tasks, both when sharing and when
+//! importing: reservations are dropped, mentions are replaced by what they mention, and custom emoji
+//! by their names.
+
+use entity::preset;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use snafu::{ensure, ResultExt, Snafu};
+
+use crate::task_syntax::{self, TaskSpec};
+
+/// The version of the code's format, bumped whenever it changes incompatibly
+const FORMAT: u32 = 1;
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum Error {
+    #[snafu(display(
+        "that is not a preset code, copy the whole code that /request-presets share gave"
+    ))]
+    Decode { source: serde_json::Error },
+    #[snafu(display(
+        "the preset code has format {format}, but only format {FORMAT} is supported"
+    ))]
+    UnsupportedFormat { format: u32 },
+    #[snafu(display("the preset's tasks are invalid"))]
+    Tasks { source: task_syntax::Error },
+}
+
+/// A preset, as it is shared between servers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SharedPreset {
+    pub format: u32,
+    pub name: String,
+    pub tasks: String,
+}
+
+/// Encodes a preset as a code that other servers can import
+pub fn share(preset: &preset::Model) -> Result<String, Error> {
+    let shared = SharedPreset {
+        format: FORMAT,
+        name: preset.name.clone(),
+        tasks: strip(&preset.tasks)?,
+    };
+    Ok(serde_json::to_string(&shared).expect("presets always serialize"))
+}
+
+/// Decodes a code made by [`share`], which may have been pasted with the code block around it
+pub fn import(code: &str) -> Result<SharedPreset, Error> {
+    let code = code.trim().trim_matches('`');
+    let code = code.strip_prefix("json").unwrap_or(code);
+    let shared = serde_json::from_str::<SharedPreset>(code).context(error::DecodeSnafu)?;
+    ensure!(
+        shared.format == FORMAT,
+        error::UnsupportedFormatSnafu {
+            format: shared.format
+        }
+    );
+    Ok(SharedPreset {
+        name: shared.name.trim().to_string(),
+        tasks: strip(&shared.tasks)?,
+        ..shared
+    })
+}
+
+/// Removes everything that only means something in the server that the tasks were written for
+fn strip(tasks: &str) -> Result<String, Error> {
+    let tasks = task_syntax::parse(tasks)
+        .context(error::TasksSnafu)?
+        .into_iter()
+        .map(|task| TaskSpec {
+            item: strip_mentions(&task.item),
+            reserved_for: None,
+            ..task
+        })
+        .collect::<Vec<_>>();
+    Ok(task_syntax::format(&tasks))
+}
+
+fn strip_mentions(text: &str) -> String {
+    let mention_regex = Regex::new(r"<(?:(@[!&]?)|(#)|a?:(\w+):)\d+>").unwrap();
+    mention_regex
+        .replace_all(text, |captures: &Captures| {
+            match (captures.get(1), captures.get(2), captures.get(3)) {
+                (Some(user), _, _) if user.as_str() == "@&" => "@role".to_string(),
+                (Some(_), _, _) => "@user".to_string(),
+                (_, Some(_), _) => "#channel".to_string(),
+                (_, _, Some(emoji)) => format!(":{}:", emoji.as_str()),
+                _ => unreachable!("mention regex matched nothing"),
+            }
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use entity::{discord_id::DiscordId, preset};
+    use sea_orm::prelude::Uuid;
+
+    use super::{import, share, Error, SharedPreset};
+
+    #[test]
+    fn shared_presets_leave_the_server_behind() {
+        let preset = preset::Model {
+            id: Uuid::nil(),
+            discord_guild_id: Some(DiscordId::new(1)),
+            name: "Frontline".to_string(),
+            tasks: "{2x} <@&5>: 40 shirts ~2; == Ammo ==; 9 7.62mm <:rifle:123> for <#7> {by 2h}"
+                .to_string(),
+        };
+        let code = share(&preset).unwrap();
+        assert!(!code.contains("<@"));
+        assert_eq!(
+            import(&format!("```json\n{code}\n```")).unwrap(),
+            SharedPreset {
+                format: 1,
+                name: "Frontline".to_string(),
+                tasks: "{2x} 40 shirts ~2; == Ammo ==; 9 7.62mm :rifle: for \\#channel {by 2h}"
+                    .to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn imports_reject_what_they_cant_read() {
+        assert!(matches!(import("Frontline"), Err(Error::Decode { .. })));
+        assert!(matches!(
+            import(r#"{"format": 2, "name": "Frontline", "tasks": "shirts"}"#),
+            Err(Error::UnsupportedFormat { format: 2 })
+        ));
+        assert!(matches!(
+            import(r#"{"format": 1, "name": "Frontline", "tasks": "{0x} shirts"}"#),
+            Err(Error::Tasks { .. })
+        ));
+    }
+}
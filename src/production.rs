@@ -0,0 +1,306 @@
+//! Material costs of equipment produced at a Mass Production Factory (MPF), for `/mpf`
+//!
+//! Costs are per crate, as produced at a regular Factory, and need to be updated by hand when the
+//! game rebalances them. The MPF makes every crate after the first in an order 10% cheaper than
+//! the one before, down to half price, and takes at most [`MAX_CRATES_PER_ORDER`] crates per order.
+
+use std::{
+    fmt::{self, Display},
+    ops::{Add, AddAssign},
+};
+
+use snafu::{OptionExt, Snafu};
+
+use crate::{limits, task_syntax::TaskSpec};
+
+/// The largest order that the MPF takes for a single item, larger amounts are split into several orders
+pub const MAX_CRATES_PER_ORDER: u32 = 9;
+/// Discount (in percent) per crate after the first in an order
+const DISCOUNT_STEP: u32 = 10;
+const MAX_DISCOUNT: u32 = 50;
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum Error {
+    #[snafu(display("{name:?} is not an item that can be produced at the MPF"))]
+    UnknownItem { name: String },
+    #[snafu(display("no items given"))]
+    NoItems,
+    #[snafu(display(
+        "at most {} crates of {name} can be ordered at once, split it into several requests",
+        limits::MPF_CRATES
+    ))]
+    TooManyCrates { name: &'static str },
+}
+
+/// An amount of each kind of material
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Cost {
+    pub bmats: u32,
+    pub rmats: u32,
+    pub emats: u32,
+    pub hemats: u32,
+}
+
+impl Cost {
+    const fn new(bmats: u32, rmats: u32, emats: u32, hemats: u32) -> Self {
+        Self {
+            bmats,
+            rmats,
+            emats,
+            hemats,
+        }
+    }
+
+    /// Each material and its amount, leaving out the materials that are not needed
+    pub fn materials(&self) -> impl Iterator<Item = (&'static str, u32)> {
+        [
+            ("bmats", self.bmats),
+            ("rmats", self.rmats),
+            ("emats", self.emats),
+            ("hemats", self.hemats),
+        ]
+        .into_iter()
+        .filter(|(_, amount)| *amount > 0)
+    }
+
+    /// Takes `times` times every material, saturating rather than overflowing
+    fn times(&self, times: u32) -> Self {
+        Self::new(
+            self.bmats.saturating_mul(times),
+            self.rmats.saturating_mul(times),
+            self.emats.saturating_mul(times),
+            self.hemats.saturating_mul(times),
+        )
+    }
+
+    /// Takes `percent`% of every material, rounding up
+    fn percent(&self, percent: u32) -> Self {
+        let scale = |amount: u32| (amount * percent).div_ceil(100);
+        Self::new(
+            scale(self.bmats),
+            scale(self.rmats),
+            scale(self.emats),
+            scale(self.hemats),
+        )
+    }
+}
+
+impl Add for Cost {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(
+            self.bmats.saturating_add(rhs.bmats),
+            self.rmats.saturating_add(rhs.rmats),
+            self.emats.saturating_add(rhs.emats),
+            self.hemats.saturating_add(rhs.hemats),
+        )
+    }
+}
+
+impl AddAssign for Cost {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+/// Writes out the cost as a list, such as `300 bmats, 40 emats`
+impl Display for Cost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (material, amount)) in self.materials().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{amount} {material}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Something that can be produced at the MPF
+#[derive(Debug, PartialEq, Eq)]
+pub struct Item {
+    pub name: &'static str,
+    /// Other names that the item is commonly known by
+    aliases: &'static [&'static str],
+    /// The cost of a single crate
+    pub cost: Cost,
+}
+
+const fn item(
+    name: &'static str,
+    aliases: &'static [&'static str],
+    bmats: u32,
+    rmats: u32,
+    emats: u32,
+    hemats: u32,
+) -> Item {
+    Item {
+        name,
+        aliases,
+        cost: Cost::new(bmats, rmats, emats, hemats),
+    }
+}
+
+#[rustfmt::skip]
+const ITEMS: &[Item] = &[
+    //   name                         aliases                              bmats rmats emats hemats
+    item("7.62mm",                    &["762", "7.62"],                    80,   0,    0,    0),
+    item("9mm",                       &["9"],                              80,   0,    0,    0),
+    item(".44",                       &["44"],                             40,   0,    0,    0),
+    item("12.7mm",                    &["127", "12.7"],                    100,  0,    0,    0),
+    item("Buckshot",                  &["shotgun ammo"],                   80,   0,    0,    0),
+    item("20mm",                      &[],                                 100,  0,    0,    0),
+    item("30mm",                      &[],                                 80,   0,    20,   0),
+    item("40mm",                      &[],                                 160,  0,    120,  0),
+    item("68mm",                      &["at shells"],                      120,  0,    80,   0),
+    item("120mm",                     &[],                                 60,   0,    15,   0),
+    item("150mm",                     &[],                                 120,  0,    0,    10),
+    item("250mm",                     &[],                                 120,  0,    0,    15),
+    item("Mortar Shell",              &["mortar"],                         60,   0,    35,   0),
+    item("Mortar Flare Shell",        &["flare"],                          60,   0,    10,   0),
+    item("RPG",                       &["rpg shell"],                      60,   0,    75,   0),
+    item("Anti-Tank Sticky Bomb",     &["sticky", "sticky bomb"],          50,   0,    50,   0),
+    item("Bomastone Grenade",         &["bomastone"],                      100,  0,    40,   0),
+    item("A3 Harpa Fragmentation Grenade", &["harpa"],                     100,  0,    20,   0),
+    item("Green Ash Grenade",         &["gas", "green ash"],               140,  0,    0,    0),
+    item("Bandages",                  &["bandage"],                        80,   0,    0,    0),
+    item("First Aid Kit",             &["fak"],                            60,   0,    0,    0),
+    item("Trauma Kit",                &["trauma"],                         80,   0,    0,    0),
+    item("Blood Plasma",              &["plasma"],                         80,   0,    0,    0),
+    item("Soldier Supplies",          &["shirts"],                         80,   0,    0,    0),
+    item("Gas Mask",                  &["mask"],                           160,  0,    0,    0),
+    item("Gas Mask Filter",           &["filter", "filters"],              100,  0,    0,    0),
+    item("Binoculars",                &["binos"],                          75,   0,    0,    0),
+    item("Radio Backpack",            &["radio"],                          150,  0,    0,    0),
+    item("Shovel",                    &[],                                 200,  0,    0,    0),
+    item("Sledge Hammer",             &["sledge"],                         200,  0,    0,    0),
+    item("Wrench",                    &[],                                 75,   0,    0,    0),
+    item("Tripod",                    &[],                                 100,  0,    0,    0),
+];
+
+/// Looks up an item by its name or one of its aliases, ignoring case
+pub fn find(name: &str) -> Option<&'static Item> {
+    let name = name.trim();
+    ITEMS.iter().find(|item| {
+        item.name.eq_ignore_ascii_case(name)
+            || item
+                .aliases
+                .iter()
+                .any(|alias| alias.eq_ignore_ascii_case(name))
+    })
+}
+
+/// Every item that can be produced, in the order that they are listed in-game
+pub fn items() -> &'static [Item] {
+    ITEMS
+}
+
+/// The cost of ordering `crates` crates of `item`, split into as few orders as possible
+pub fn mpf_cost(item: &Item, crates: u32) -> Cost {
+    // The first `crates` crates of a single order
+    let order_cost = |crates: u32| {
+        (0..crates).fold(Cost::default(), |cost, i| {
+            let discount = (DISCOUNT_STEP * i).min(MAX_DISCOUNT);
+            cost + item.cost.percent(100 - discount)
+        })
+    };
+    order_cost(MAX_CRATES_PER_ORDER).times(crates / MAX_CRATES_PER_ORDER)
+        + order_cost(crates % MAX_CRATES_PER_ORDER)
+}
+
+/// Items and the number of crates of each
+pub struct Order {
+    pub items: Vec<(&'static Item, u32)>,
+    pub cost: Cost,
+}
+
+/// Works out the total cost of producing `items`
+///
+/// The amount of each task is the number of crates, which defaults to one. Items that are
+/// listed more than once are combined into the same orders, which may add up to at most
+/// [`limits::MPF_CRATES`] crates each.
+pub fn plan(items: &[TaskSpec]) -> Result<Order, Error> {
+    let mut order: Vec<(&'static Item, u32)> = Vec::new();
+    for spec in items {
+        let item = find(&spec.item).context(error::UnknownItemSnafu { name: &spec.item })?;
+        let too_many = error::TooManyCratesSnafu { name: item.name };
+        let crates = u32::try_from(spec.multiplier)
+            .ok()
+            .and_then(|multiplier| spec.amount.unwrap_or(1).checked_mul(multiplier))
+            .context(too_many)?;
+        let index = match order.iter().position(|(ordered, _)| *ordered == item) {
+            Some(index) => index,
+            None => {
+                order.push((item, 0));
+                order.len() - 1
+            }
+        };
+        let ordered_crates = &mut order[index].1;
+        *ordered_crates = ordered_crates
+            .checked_add(crates)
+            .filter(|crates| *crates <= limits::MPF_CRATES)
+            .context(too_many)?;
+    }
+    snafu::ensure!(!order.is_empty(), error::NoItemsSnafu);
+    let cost = order.iter().fold(Cost::default(), |cost, (item, crates)| {
+        cost + mpf_cost(item, *crates)
+    });
+    Ok(Order { items: order, cost })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find, mpf_cost, plan, Cost, Error};
+    use crate::task_syntax;
+
+    #[test]
+    fn finds_items_by_alias() {
+        assert_eq!(find("762").unwrap().name, "7.62mm");
+        assert_eq!(find(" Sticky BOMB ").unwrap().name, "Anti-Tank Sticky Bomb");
+        assert!(find("tank").is_none());
+    }
+
+    #[test]
+    fn discounts_crates_in_the_same_order() {
+        let bandages = find("bandages").unwrap();
+        assert_eq!(mpf_cost(bandages, 1).bmats, 80);
+        // 100% + 90% + 80% + 70% + 60% + 4 * 50%
+        assert_eq!(mpf_cost(bandages, 9).bmats, 80 * 600 / 100);
+        // The tenth crate starts a new order
+        assert_eq!(mpf_cost(bandages, 10).bmats, 80 * 700 / 100);
+        let order = plan(&task_syntax::parse("9 762; {2x} sticky; 1 762").unwrap()).unwrap();
+        assert_eq!(
+            order.cost,
+            Cost {
+                bmats: 80 * 700 / 100 + 50 + 45,
+                rmats: 0,
+                emats: 50 + 45,
+                hemats: 0,
+            }
+        );
+        assert_eq!(order.cost.to_string(), "655 bmats, 95 emats");
+    }
+
+    #[test]
+    fn refuses_huge_orders() {
+        let bandages = find("bandages").unwrap();
+        assert_eq!(mpf_cost(bandages, 900).bmats, 80 * 600 / 100 * 100);
+        assert_eq!(mpf_cost(bandages, u32::MAX).bmats, u32::MAX);
+        assert!(matches!(
+            plan(&task_syntax::parse("4000000000 shirts").unwrap()),
+            Err(Error::TooManyCrates {
+                name: "Soldier Supplies"
+            })
+        ));
+        assert!(matches!(
+            plan(&task_syntax::parse("{100x} 4294967295 shirts").unwrap()),
+            Err(Error::TooManyCrates { .. })
+        ));
+        assert!(matches!(
+            plan(&task_syntax::parse("600 shirts; 600 shirts").unwrap()),
+            Err(Error::TooManyCrates { .. })
+        ));
+    }
+}
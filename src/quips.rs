@@ -0,0 +1,251 @@
+//! The quips in the footers of requests, see `/server-quips`
+//!
+//! Every request is given a quip when it is first rendered, and keeps it from then on. Quips are
+//! picked out of the built-in quips (those without a server) and the server's own, from the packs
+//! that the server picked (or all of them), more often the more weight they have. Seasonal quips are
+//! only picked between their dates, and the quips of the server's last [`REPEAT_WINDOW`] requests
+//! aren't picked again unless there is nothing else left.
+//!
+//! Quips are read from the database whenever one is picked, so they can be changed without
+//! redeploying the bot.
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet},
+    hash::{BuildHasher, BuildHasherDefault},
+};
+
+use entity::{guild_setting, quip, request};
+use sea_orm::{
+    sea_query::Expr, ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect,
+};
+use serenity::model::id::GuildId;
+use time::{Date, OffsetDateTime};
+
+use crate::discord_ids::{FromDiscordId, ToDiscordId};
+
+/// How many of a server's latest requests' quips are avoided when picking a new one
+pub const REPEAT_WINDOW: u64 = 10;
+/// The pack of the quips that the bot comes with
+pub const BUILT_IN_PACK: &str = "default";
+/// The quip of requests that no quip is available for
+const FALLBACK: &str = "Remember: There is no shadow council";
+
+/// Parses a day of the year written as `MM-DD`, into the form stored in the database
+pub fn parse_day(day: &str) -> Option<i16> {
+    let (month, day) = day.trim().split_once('-')?;
+    let (month, day) = (month.parse::<u8>().ok()?, day.parse::<u8>().ok()?);
+    let month = time::Month::try_from(month).ok()?;
+    // Leap days are only available in leap years, but may still start or end a season
+    (1..=time::util::days_in_year_month(2024, month))
+        .contains(&day)
+        .then_some(i16::from(u8::from(month)) * 100 + i16::from(day))
+}
+
+/// Formats a day of the year as stored in the database, as `MM-DD`
+pub fn format_day(day: i16) -> String {
+    format!("{:02}-{:02}", day / 100, day % 100)
+}
+
+/// Whether a quip that is available between `from` and `until` (inclusive) can be picked on `date`
+///
+/// Seasons that end before they start wrap around the new year.
+pub fn in_season(from: Option<i16>, until: Option<i16>, date: Date) -> bool {
+    let today = i16::from(u8::from(date.month())) * 100 + i16::from(date.day());
+    match (from, until) {
+        (Some(from), Some(until)) if from <= until => (from..=until).contains(&today),
+        (Some(from), Some(until)) => today >= from || today <= until,
+        (Some(from), None) => today >= from,
+        (None, Some(until)) => today <= until,
+        (None, None) => true,
+    }
+}
+
+/// The packs that `packs` (as stored in [`guild_setting::Model::quip_packs`]) picks, or [`None`]
+/// for all of them
+pub fn parse_packs(packs: Option<&str>) -> Option<Vec<String>> {
+    let packs = packs?
+        .split(',')
+        .map(str::trim)
+        .filter(|pack| !pack.is_empty())
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    (!packs.is_empty()).then_some(packs)
+}
+
+/// Picks one of `quips` by weight with `roll`, avoiding those that were used `recently` if possible
+pub fn pick<'a>(
+    quips: &'a [quip::Model],
+    recently: &HashSet<&str>,
+    roll: u64,
+) -> Option<&'a quip::Model> {
+    let weighted = quips.iter().filter(|quip| quip.weight > 0);
+    let mut pool = weighted
+        .clone()
+        .filter(|quip| !recently.contains(quip.text.as_str()))
+        .collect::<Vec<_>>();
+    if pool.is_empty() {
+        pool = weighted.collect();
+    }
+    let total = pool.iter().map(|quip| quip.weight as u64).sum::<u64>();
+    if total == 0 {
+        return None;
+    }
+    let mut roll = roll % total;
+    pool.into_iter().find(|quip| {
+        let weight = quip.weight as u64;
+        if roll < weight {
+            true
+        } else {
+            roll -= weight;
+            false
+        }
+    })
+}
+
+/// The quips that `guild` can pick from on `date`, or the built-in ones outside of servers
+pub async fn available(
+    db: &DatabaseConnection,
+    guild: Option<GuildId>,
+    date: Date,
+) -> Result<Vec<quip::Model>, DbErr> {
+    let mut owners = Condition::any().add(quip::Column::DiscordGuildId.is_null());
+    let mut packs = None;
+    if let Some(guild) = guild {
+        owners = owners.add(quip::Column::DiscordGuildId.eq(guild.db_id()));
+        packs = guild_setting::Entity::find_by_id(guild.db_id())
+            .one(db)
+            .await?
+            .and_then(|settings| parse_packs(settings.quip_packs.as_deref()));
+    }
+    let mut quips = quip::Entity::find().filter(owners);
+    if let Some(packs) = packs {
+        quips = quips.filter(quip::Column::Pack.is_in(packs));
+    }
+    Ok(quips
+        .order_by_asc(quip::Column::Id)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|quip| in_season(quip.available_from, quip.available_until, date))
+        .collect())
+}
+
+/// The quip of `request`, which is picked the first time that it is asked for
+pub async fn quip_for(db: &DatabaseConnection, request: &request::Model) -> Result<String, DbErr> {
+    if let Some(quip) = &request.quip {
+        return Ok(quip.clone());
+    }
+    let guild = request.discord_guild_id;
+    let quips = available(
+        db,
+        guild.map(|guild| guild.discord()),
+        OffsetDateTime::now_utc().date(),
+    )
+    .await?;
+    let recent = match guild {
+        Some(guild) => {
+            request::Entity::find()
+                .select_only()
+                .column(request::Column::Quip)
+                .filter(request::Column::DiscordGuildId.eq(guild))
+                .filter(request::Column::Quip.is_not_null())
+                .order_by_desc(request::Column::CreatedAt)
+                .limit(REPEAT_WINDOW)
+                .into_tuple::<String>()
+                .all(db)
+                .await?
+        }
+        None => Vec::new(),
+    };
+    // Seeded by the request, so that renders that race each other mostly pick the same quip
+    let roll = BuildHasherDefault::<DefaultHasher>::default().hash_one(request.id);
+    let quip = pick(&quips, &recent.iter().map(String::as_str).collect(), roll)
+        .map_or(FALLBACK, |quip| quip.text.as_str())
+        .to_string();
+    let picked = request::Entity::update_many()
+        .col_expr(request::Column::Quip, Expr::value(&quip))
+        .filter(request::Column::Id.eq(request.id))
+        .filter(request::Column::Quip.is_null())
+        .exec(db)
+        .await?;
+    if picked.rows_affected > 0 {
+        return Ok(quip);
+    }
+    // Another render picked first, and its quip is the one that is kept
+    Ok(request::Entity::find_by_id(request.id)
+        .one(db)
+        .await?
+        .and_then(|request| request.quip)
+        .unwrap_or(quip))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use entity::quip;
+    use sea_orm::prelude::Uuid;
+    use time::macros::date;
+
+    use super::{format_day, in_season, parse_day, parse_packs, pick};
+
+    fn quip(text: &str, weight: i32) -> quip::Model {
+        quip::Model {
+            id: Uuid::nil(),
+            discord_guild_id: None,
+            pack: "default".to_string(),
+            text: text.to_string(),
+            weight,
+            available_from: None,
+            available_until: None,
+        }
+    }
+
+    #[test]
+    fn quips_are_picked_by_weight() {
+        let quips = [quip("a", 1), quip("b", 3), quip("muted", 0)];
+        let picks = (0..4)
+            .map(|roll| pick(&quips, &HashSet::new(), roll).unwrap().text.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(picks, ["a", "b", "b", "b"]);
+        assert!(pick(&quips[2..], &HashSet::new(), 0).is_none());
+    }
+
+    #[test]
+    fn recent_quips_are_avoided_unless_nothing_else_is_left() {
+        let quips = [quip("a", 1), quip("b", 1)];
+        for roll in 0..4 {
+            assert_eq!(pick(&quips, &HashSet::from(["a"]), roll).unwrap().text, "b");
+        }
+        assert!(pick(&quips, &HashSet::from(["a", "b"]), 0).is_some());
+    }
+
+    #[test]
+    fn seasons_can_wrap_around_the_new_year() {
+        let (from, until) = (parse_day("12-20"), parse_day("01-02"));
+        assert_eq!(from, Some(1220));
+        assert_eq!(format_day(until.unwrap()), "01-02");
+        assert!(in_season(from, until, date!(2026 - 12 - 24)));
+        assert!(in_season(from, until, date!(2027 - 01 - 01)));
+        assert!(!in_season(from, until, date!(2026 - 10 - 17)));
+        let (from, until) = (parse_day("10-01"), parse_day("10-31"));
+        assert!(in_season(from, until, date!(2026 - 10 - 17)));
+        assert!(!in_season(from, until, date!(2026 - 11 - 01)));
+        assert!(in_season(None, None, date!(2026 - 11 - 01)));
+        assert_eq!(parse_day("02-29"), Some(229));
+        assert_eq!(parse_day("13-01"), None);
+        assert_eq!(parse_day("04-31"), None);
+        assert_eq!(parse_day("christmas"), None);
+    }
+
+    #[test]
+    fn packs_are_listed_by_commas() {
+        assert_eq!(parse_packs(None), None);
+        assert_eq!(parse_packs(Some(" , ")), None);
+        assert_eq!(
+            parse_packs(Some("default, ops")),
+            Some(vec!["default".to_string(), "ops".to_string()])
+        );
+    }
+}
@@ -0,0 +1,90 @@
+//! Per-user command rate limiting, to keep a single user from flooding a channel
+
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// A sliding window limit of `limit` uses per `window` for each key
+pub struct RateLimiter<K> {
+    limit: usize,
+    window: Duration,
+    uses: Mutex<HashMap<K, VecDeque<Instant>>>,
+}
+
+impl<K: Eq + Hash> RateLimiter<K> {
+    pub fn new(limit: usize, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            uses: Mutex::default(),
+        }
+    }
+
+    /// Records a use by `key` at `now`, unless it is over the limit
+    ///
+    /// Returns how long `key` must wait before trying again if it is over the limit.
+    /// Rejected uses don't count towards the limit, so retrying doesn't extend the wait.
+    pub fn check(&self, key: K, now: Instant) -> Result<(), Duration> {
+        let mut uses = self.uses.lock().unwrap();
+        // Forget keys that have gone quiet, so that the map doesn't grow forever
+        uses.retain(|_, key_uses| {
+            key_uses
+                .back()
+                .is_some_and(|last| now.duration_since(*last) < self.window)
+        });
+        let key_uses = uses.entry(key).or_default();
+        while key_uses
+            .front()
+            .is_some_and(|first| now.duration_since(*first) >= self.window)
+        {
+            key_uses.pop_front();
+        }
+        if key_uses.len() >= self.limit {
+            let oldest = key_uses.front().copied().unwrap_or(now);
+            return Err(self.window - now.duration_since(oldest));
+        }
+        key_uses.push_back(now);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::RateLimiter;
+
+    #[test]
+    fn limits_each_key_separately() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+        assert_eq!(limiter.check("a", start), Ok(()));
+        assert_eq!(limiter.check("a", start + Duration::from_secs(10)), Ok(()));
+        assert_eq!(
+            limiter.check("a", start + Duration::from_secs(20)),
+            Err(Duration::from_secs(40))
+        );
+        assert_eq!(limiter.check("b", start + Duration::from_secs(20)), Ok(()));
+    }
+
+    #[test]
+    fn window_slides() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(60));
+        let start = Instant::now();
+        assert_eq!(limiter.check("a", start), Ok(()));
+        assert!(limiter.check("a", start + Duration::from_secs(59)).is_err());
+        assert_eq!(limiter.check("a", start + Duration::from_secs(60)), Ok(()));
+    }
+
+    #[test]
+    fn zero_limit_rejects_everything() {
+        let limiter = RateLimiter::new(0, Duration::from_secs(60));
+        assert_eq!(
+            limiter.check("a", Instant::now()),
+            Err(Duration::from_secs(60))
+        );
+    }
+}
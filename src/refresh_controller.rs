@@ -0,0 +1,163 @@
+//! Re-renders open requests whose time-dependent decorations (the expiring soon warning, the
+//! expired colour, and the overdue flag) have changed since they were last rendered
+//!
+//! Discord updates relative timestamps by itself, but nothing else. [`crate::reminder_controller`]
+//! renders these changes as they happen, but it only remembers what it has rendered until the bot
+//! restarts or another replica takes over, and it gives up on renders that fail. This controller
+//! goes by when each request was last rendered instead, so it catches whatever was missed, at a
+//! more leisurely pace.
+
+use std::{collections::HashMap, time::Duration};
+
+use entity::{guild_setting, request};
+use sea_orm::{ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, QueryFilter};
+use time::OffsetDateTime;
+
+use crate::{
+    backoff::{self, Backoff},
+    discord_api::DiscordApi,
+    expiration_controller::Partition,
+    leader, update_request_messages, EXPIRY_WARNING_WINDOW,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+pub async fn run(db: &DatabaseConnection, discord: &dyn DiscordApi, partition: &Partition) {
+    let mut backoff = Backoff::new(POLL_INTERVAL, backoff::MAX_RETRY_DELAY);
+    loop {
+        let delay = match run_turn(db, discord, partition).await {
+            Ok(_) => {
+                backoff.succeeded();
+                POLL_INTERVAL
+            }
+            Err(err) => {
+                let delay = backoff.failed();
+                tracing::error!(error = &err as &dyn std::error::Error, retry_in = ?delay, "failed to find requests that need to be refreshed, retrying...");
+                delay
+            }
+        };
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Refreshes the partition's stale requests, returning how many were refreshed
+pub(crate) async fn run_turn(
+    db: &DatabaseConnection,
+    discord: &dyn DiscordApi,
+    partition: &Partition,
+) -> Result<usize, DbErr> {
+    let Some(leadership) =
+        leader::try_lead(db, leader::Controller::Refresh, partition.application_id.0).await?
+    else {
+        return Ok(0);
+    };
+    let now = OffsetDateTime::now_utc();
+    let targets = guild_setting::Entity::find()
+        .filter(guild_setting::Column::TargetCompletionSecs.is_not_null())
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|settings| {
+            Some((
+                settings.discord_guild_id,
+                time::Duration::seconds(settings.target_completion_secs?),
+            ))
+        })
+        .collect::<HashMap<_, _>>();
+    let candidates = request::Entity::find()
+        .filter(request::Column::ArchivedOn.is_null())
+        .filter(
+            Condition::any()
+                .add(request::Column::ExpiresOn.lte(Some(now + EXPIRY_WARNING_WINDOW)))
+                .add(request::Column::DiscordGuildId.is_in(targets.keys().copied())),
+        )
+        .filter(partition.condition())
+        .all(db)
+        .await?;
+    let mut refreshed = 0;
+    for req in candidates {
+        let target = req
+            .discord_guild_id
+            .and_then(|guild| targets.get(&guild).copied());
+        let Some(changed_at) = last_change(req.created_at, req.expires_on, target, now) else {
+            continue;
+        };
+        // Requests are rendered when they are created, even if that wasn't recorded
+        if req.rendered_at.unwrap_or(req.created_at) >= changed_at {
+            continue;
+        }
+        match update_request_messages(db, discord, req.id, None).await {
+            Ok(()) => refreshed += 1,
+            Err(err) => {
+                tracing::error!(error = &err as &dyn std::error::Error, request.id = %req.id, "failed to refresh request, retrying later...");
+            }
+        }
+    }
+    leadership.release().await?;
+    Ok(refreshed)
+}
+
+/// When the time-dependent decorations of a request last changed, [`None`] if they haven't yet
+///
+/// `target` is the request's server's target time to completion, if it has one.
+fn last_change(
+    created_at: OffsetDateTime,
+    expires_on: Option<OffsetDateTime>,
+    target: Option<time::Duration>,
+    now: OffsetDateTime,
+) -> Option<OffsetDateTime> {
+    [
+        expires_on.map(|expires_on| expires_on - EXPIRY_WARNING_WINDOW),
+        expires_on,
+        target.and_then(|target| created_at.checked_add(target)),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|&change| change <= now)
+    .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Duration, OffsetDateTime};
+
+    use super::last_change;
+
+    fn at(minutes: i64) -> OffsetDateTime {
+        OffsetDateTime::UNIX_EPOCH + Duration::minutes(minutes)
+    }
+
+    #[test]
+    fn nothing_changes_before_the_warning_window() {
+        assert_eq!(
+            last_change(at(0), Some(at(24 * 60)), None, at(23 * 60)),
+            None
+        );
+        assert_eq!(last_change(at(0), None, None, at(100 * 60)), None);
+    }
+
+    #[test]
+    fn the_latest_passed_change_counts() {
+        let expires_on = Some(at(24 * 60));
+        assert_eq!(
+            last_change(at(0), expires_on, None, at(24 * 60 - 15)),
+            Some(at(24 * 60 - 30))
+        );
+        assert_eq!(
+            last_change(at(0), expires_on, None, at(25 * 60)),
+            expires_on
+        );
+        assert_eq!(
+            last_change(at(0), expires_on, Some(Duration::hours(6)), at(8 * 60)),
+            Some(at(6 * 60))
+        );
+    }
+
+    #[test]
+    fn targets_past_the_end_of_time_never_pass() {
+        assert_eq!(
+            last_change(at(0), None, Some(Duration::seconds(i64::MAX)), at(60)),
+            None
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! Re-renders requests as they come within [`EXPIRY_WARNING_WINDOW`] of expiring, or as they go
+//! over their server's target time to completion, so that the warning shows up without waiting
+//! for someone to interact with them
+//!
+//! Their creators (and servers) are notified of both too, if they want to be, see [`notifier`], and
+//! likewise as their open tasks come within the same window of their deadlines.
+
+use std::{collections::HashSet, time::Duration};
+
+use entity::{guild_setting, request, task};
+use sea_orm::{prelude::Uuid, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter};
+use time::OffsetDateTime;
+
+use crate::{
+    backoff::{self, Backoff},
+    discord_api::DiscordApi,
+    expiration_controller::Partition,
+    leader,
+    notifier::{self, Event},
+    request_link, stats, update_request_messages, EXPIRY_WARNING_WINDOW,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+pub async fn run(db: &DatabaseConnection, discord: &dyn DiscordApi, partition: &Partition) {
+    // Keyed by expiry too, so that a request is warned about again after being extended
+    let mut warned = HashSet::new();
+    // Keyed by target too, so that a request is flagged again after the target is changed
+    let mut overdue = HashSet::new();
+    // Keyed by deadline too, so that a task is reminded about again after its deadline is moved
+    let mut due = HashSet::new();
+    let mut backoff = Backoff::new(POLL_INTERVAL, backoff::MAX_RETRY_DELAY);
+    loop {
+        let delay = match run_turn(db, discord, partition, &mut warned, &mut overdue, &mut due)
+            .await
+        {
+            Ok(()) => {
+                backoff.succeeded();
+                POLL_INTERVAL
+            }
+            Err(err) => {
+                let delay = backoff.failed();
+                tracing::error!(error = &err as &dyn std::error::Error, retry_in = ?delay, "failed to find requests that are about to expire, retrying...");
+                delay
+            }
+        };
+        tokio::time::sleep(delay).await;
+    }
+}
+
+async fn run_turn(
+    db: &DatabaseConnection,
+    discord: &dyn DiscordApi,
+    partition: &Partition,
+    warned: &mut HashSet<(Uuid, OffsetDateTime)>,
+    overdue: &mut HashSet<(Uuid, i64)>,
+    due: &mut HashSet<(Uuid, OffsetDateTime)>,
+) -> Result<(), DbErr> {
+    let Some(leadership) =
+        leader::try_lead(db, leader::Controller::Reminder, partition.application_id.0).await?
+    else {
+        return Ok(());
+    };
+    let now = OffsetDateTime::now_utc();
+    // Expired requests are the expiration controller's business
+    warned.retain(|(_, expires_on)| *expires_on > now);
+    let expiring_requests = request::Entity::find()
+        .filter(request::Column::ArchivedOn.is_null())
+        .filter(request::Column::ExpiresOn.gt(Some(now)))
+        .filter(request::Column::ExpiresOn.lte(Some(now + EXPIRY_WARNING_WINDOW)))
+        .filter(partition.condition())
+        .all(db)
+        .await?;
+    for req in expiring_requests {
+        let Some(expires_on) = req.expires_on else {
+            continue;
+        };
+        if warned.contains(&(req.id, expires_on)) {
+            continue;
+        }
+        match update_request_messages(db, discord, req.id, None).await {
+            Ok(()) => {
+                warned.insert((req.id, expires_on));
+                let content = format!(
+                    "⏰ {} expires <t:{}:R>",
+                    request_link(&req).unwrap_or_else(|| req.title.clone()),
+                    expires_on.unix_timestamp()
+                );
+                notifier::notify_request(db, &req, Event::Reminded, content).await?;
+            }
+            Err(err) => {
+                tracing::error!(error = &err as &dyn std::error::Error, request.id = %req.id, "failed to warn about request expiring, retrying later...");
+            }
+        }
+    }
+    let targets = guild_setting::Entity::find()
+        .filter(guild_setting::Column::TargetCompletionSecs.is_not_null())
+        .all(db)
+        .await?;
+    let mut still_overdue = HashSet::new();
+    for settings in targets {
+        let Some(target_secs) = settings.target_completion_secs else {
+            continue;
+        };
+        // Targets saved before they were capped may reach past the start of time, then no
+        // request can have missed them
+        let Some(overdue_before) = now.checked_sub(time::Duration::seconds(target_secs)) else {
+            continue;
+        };
+        let overdue_requests = request::Entity::find()
+            .filter(request::Column::ArchivedOn.is_null())
+            .filter(request::Column::DiscordGuildId.eq(settings.discord_guild_id))
+            .filter(request::Column::CreatedAt.lt(overdue_before))
+            .filter(partition.condition())
+            .all(db)
+            .await?;
+        for req in overdue_requests {
+            let key = (req.id, target_secs);
+            still_overdue.insert(key);
+            if overdue.contains(&key) {
+                continue;
+            }
+            match update_request_messages(db, discord, req.id, None).await {
+                Ok(()) => {
+                    overdue.insert(key);
+                    let content = format!(
+                        "⌛ {} is taking longer than the server's target of {}",
+                        request_link(&req).unwrap_or_else(|| req.title.clone()),
+                        stats::format_time_to_completion(time::Duration::seconds(target_secs))
+                    );
+                    notifier::notify_request(db, &req, Event::Overdue, content).await?;
+                }
+                Err(err) => {
+                    tracing::error!(error = &err as &dyn std::error::Error, request.id = %req.id, "failed to flag overdue request, retrying later...");
+                }
+            }
+        }
+    }
+    // Archived requests and changed targets don't need to be remembered anymore
+    overdue.retain(|key| still_overdue.contains(key));
+
+    due.retain(|(_, deadline)| *deadline > now);
+    let due_tasks = task::Entity::find()
+        .find_also_related(request::Entity)
+        .filter(task::Column::Deadline.gt(Some(now)))
+        .filter(task::Column::Deadline.lte(Some(now + EXPIRY_WARNING_WINDOW)))
+        .filter(task::Column::CompletedAt.is_null())
+        .filter(task::Column::RemovedAt.is_null())
+        .filter(task::Column::MovedTo.is_null())
+        .filter(request::Column::ArchivedOn.is_null())
+        .filter(partition.condition())
+        .all(db)
+        .await?;
+    let mut rerendered = HashSet::new();
+    for (task, req) in due_tasks {
+        let (Some(deadline), Some(req)) = (task.deadline, req) else {
+            continue;
+        };
+        if due.contains(&(task.id, deadline)) {
+            continue;
+        }
+        // Several tasks of a request are often due at once, but it only needs to be re-rendered once
+        if !rerendered.contains(&req.id) {
+            if let Err(err) = update_request_messages(db, discord, req.id, None).await {
+                tracing::error!(error = &err as &dyn std::error::Error, request.id = %req.id, "failed to warn about task deadline, retrying later...");
+                continue;
+            }
+            rerendered.insert(req.id);
+        }
+        due.insert((task.id, deadline));
+        let content = format!(
+            "⏰ **{}** of {} is due <t:{}:R>",
+            task.task,
+            request_link(&req).unwrap_or_else(|| req.title.clone()),
+            deadline.unix_timestamp()
+        );
+        notifier::notify_request(db, &req, Event::Reminded, content).await?;
+    }
+    leadership.release().await
+}
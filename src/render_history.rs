@@ -0,0 +1,198 @@
+//! Earlier renders of requests' messages, shown as what changed in their timelines
+//!
+//! Whenever a request is rendered, each of its pages is flattened into the text that it shows and
+//! recorded if that differs from the page's last render, so that the previous version survives the
+//! message being edited. Only the latest [`RENDERS_PER_PAGE`] renders of each page are kept.
+//! Buttons and menus are left out, since they follow from the tasks that are shown anyway.
+
+use entity::request_render_history;
+use sea_orm::{
+    prelude::Uuid, ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection, DbErr,
+    EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+};
+use serde_json::Value;
+
+use crate::limits;
+
+/// Number of renders that are kept of each page of a request
+pub const RENDERS_PER_PAGE: u64 = 20;
+/// Number of changed lines that are shown for each render in the timeline
+const CHANGED_LINES: usize = 3;
+/// Maximum length of a changed line shown in the timeline
+const CHANGED_LINE: usize = 100;
+
+/// Flattens a rendered message, as sent to Discord, into the lines of text that it shows
+pub fn snapshot(message: &Value) -> String {
+    fn text(value: Option<&Value>) -> &str {
+        value.and_then(Value::as_str).unwrap_or_default()
+    }
+    fn list(value: Option<&Value>) -> &[Value] {
+        value.and_then(Value::as_array).map_or(&[], Vec::as_slice)
+    }
+    let mut lines = text(message.get("content"))
+        .lines()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    for embed in list(message.get("embeds")) {
+        let title = text(embed.get("title"));
+        if !title.is_empty() {
+            lines.push(format!("# {title}"));
+        }
+        lines.extend(text(embed.get("description")).lines().map(str::to_string));
+        for field in list(embed.get("fields")) {
+            lines.push(format!("## {}", text(field.get("name"))));
+            lines.extend(text(field.get("value")).lines().map(str::to_string));
+        }
+        let footer = text(embed.get("footer").and_then(|footer| footer.get("text")));
+        if !footer.is_empty() {
+            lines.push(format!("-# {footer}"));
+        }
+    }
+    lines.join("\n")
+}
+
+/// A line that differs between two renders
+#[derive(Debug, PartialEq, Eq)]
+pub enum Change<'a> {
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// The lines that were removed from `before` and added to get `after`, in the order they appear
+pub fn diff<'a>(before: &'a str, after: &'a str) -> Vec<Change<'a>> {
+    let before = before.lines().collect::<Vec<_>>();
+    let after = after.lines().collect::<Vec<_>>();
+    // common[i][j] is the length of the longest common subsequence of before[i..] and after[j..]
+    let mut common = vec![vec![0_usize; after.len() + 1]; before.len() + 1];
+    for i in (0..before.len()).rev() {
+        for j in (0..after.len()).rev() {
+            common[i][j] = if before[i] == after[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut changes = Vec::new();
+    while i < before.len() || j < after.len() {
+        if i < before.len() && j < after.len() && before[i] == after[j] {
+            i += 1;
+            j += 1;
+        } else if i < before.len() && (j == after.len() || common[i + 1][j] >= common[i][j + 1]) {
+            changes.push(Change::Removed(before[i]));
+            i += 1;
+        } else {
+            changes.push(Change::Added(after[j]));
+            j += 1;
+        }
+    }
+    changes
+}
+
+/// Describes what changed between two renders of `page`, or [`None`] if nothing did
+pub fn describe(page: i32, before: &str, after: &str) -> Option<String> {
+    let changes = diff(before, after);
+    if changes.is_empty() {
+        return None;
+    }
+    let mut description = match page {
+        0 => "Message re-rendered".to_string(),
+        _ => format!("Page {} re-rendered", page + 1),
+    };
+    for change in changes.iter().take(CHANGED_LINES) {
+        let (sign, line) = match change {
+            Change::Removed(line) => ('-', line),
+            Change::Added(line) => ('+', line),
+        };
+        description += &format!("\n> `{sign}` {}", limits::truncate(line, CHANGED_LINE));
+    }
+    if changes.len() > CHANGED_LINES {
+        description += &format!("\n> …and {} more", changes.len() - CHANGED_LINES);
+    }
+    Some(description)
+}
+
+/// Records `snapshot` as the latest render of `request`'s `page`, unless it is unchanged
+pub async fn record(
+    db: &DatabaseConnection,
+    request: Uuid,
+    page: i32,
+    snapshot: String,
+) -> Result<(), DbErr> {
+    let of_page = || {
+        request_render_history::Entity::find()
+            .filter(request_render_history::Column::Request.eq(request))
+            .filter(request_render_history::Column::Page.eq(page))
+            .order_by_desc(request_render_history::Column::RenderedAt)
+    };
+    let latest = of_page().one(db).await?;
+    if latest.is_some_and(|latest| latest.snapshot == snapshot) {
+        return Ok(());
+    }
+    request_render_history::ActiveModel {
+        request: Set(request),
+        page: Set(page),
+        snapshot: Set(snapshot),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    let expired = of_page()
+        .select_only()
+        .column(request_render_history::Column::Id)
+        .offset(RENDERS_PER_PAGE)
+        .into_tuple::<Uuid>()
+        .all(db)
+        .await?;
+    if !expired.is_empty() {
+        request_render_history::Entity::delete_many()
+            .filter(request_render_history::Column::Id.is_in(expired))
+            .exec(db)
+            .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{describe, diff, snapshot, Change};
+
+    #[test]
+    fn messages_are_flattened_into_what_they_show() {
+        let message = json!({
+            "content": "**Logi run**",
+            "embeds": [{
+                "title": "Tasks",
+                "description": "1. bmats\n2. rmats",
+                "fields": [{"name": "Notes", "value": "bring a truck"}],
+                "footer": {"text": "F"},
+                "color": 1,
+            }],
+            "components": [],
+        });
+        assert_eq!(
+            snapshot(&message),
+            "**Logi run**\n# Tasks\n1. bmats\n2. rmats\n## Notes\nbring a truck\n-# F"
+        );
+    }
+
+    #[test]
+    fn only_changed_lines_are_described() {
+        assert_eq!(
+            diff("a\nb\nc", "a\nB\nc\nd"),
+            [Change::Removed("b"), Change::Added("B"), Change::Added("d")]
+        );
+        assert_eq!(describe(0, "a\nb", "a\nb"), None);
+        assert_eq!(
+            describe(1, "a\nb", "a\nc"),
+            Some("Page 2 re-rendered\n> `-` b\n> `+` c".to_string())
+        );
+        assert_eq!(
+            describe(0, "a\nb\nc", "d\ne"),
+            Some("Message re-rendered\n> `-` a\n> `-` b\n> `-` c\n> …and 2 more".to_string())
+        );
+    }
+}
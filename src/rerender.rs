@@ -0,0 +1,147 @@
+//! Rebuilding a request's messages from the database, with `/admin-rerender`
+//!
+//! Requests whose message is still there are simply edited again, see
+//! [`crate::update_request_messages`], which brings back stripped thumbnails and lost components.
+//! Requests whose message was deleted are posted anew instead, in the channel that they are
+//! currently in, and their stored message IDs are swapped for the new ones.
+
+use entity::{request, request_message};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    ModelTrait, QueryFilter, TransactionTrait,
+};
+use serenity::model::id::{ChannelId, MessageId};
+use snafu::{OptionExt, ResultExt, Snafu};
+
+use crate::{
+    discord_api::{self, DiscordApi},
+    discord_ids::{FromDiscordId, ToDiscordId},
+    permissions, render_request, sync_feed, sync_mirrors, sync_pin,
+};
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum Error {
+    Database {
+        source: DbErr,
+    },
+    #[snafu(display("the request was never posted anywhere"))]
+    NeverPosted,
+    #[snafu(display("not allowed to post the request again"))]
+    Permissions {
+        source: permissions::Error,
+    },
+    #[snafu(display("failed to post the request again in {channel}"))]
+    SendMessage {
+        source: serenity::Error,
+        channel: ChannelId,
+    },
+}
+
+/// Posts the request again in the channel that it is in, replacing whatever is left of its old messages
+///
+/// Returns the channel and the request's new message.
+pub async fn repost(
+    db: &DatabaseConnection,
+    api: &dyn DiscordApi,
+    request: &request::Model,
+) -> Result<(ChannelId, MessageId), Error> {
+    let channel = request
+        .discord_archive_channel_id
+        .or(request.discord_channel_id)
+        .context(error::NeverPostedSnafu)?
+        .discord();
+    permissions::ensure(api, channel, permissions::POST)
+        .await
+        .context(error::PermissionsSnafu)?;
+
+    let mut messages = Vec::new();
+    for rendered in render_request(db, request.id).await {
+        match api
+            .send_message(
+                channel,
+                discord_api::create_message(|msg| rendered.create_message(msg)),
+            )
+            .await
+        {
+            Ok(message) => messages.push(message),
+            Err(source) => {
+                delete_messages(api, messages.into_iter().map(|message| (channel, message))).await;
+                return Err(Error::SendMessage { source, channel });
+            }
+        }
+    }
+    let (first, followups) = messages
+        .split_first()
+        .expect("request rendered no messages");
+
+    let txn = db.begin().await.context(error::DatabaseSnafu)?;
+    request::ActiveModel {
+        id: sea_orm::ActiveValue::Unchanged(request.id),
+        discord_message_id: Set(Some(first.db_id())),
+        // Pins don't follow the message, the new one is pinned again if the channel wants it
+        pinned: Set(false),
+        ..Default::default()
+    }
+    .update(&txn)
+    .await
+    .context(error::DatabaseSnafu)?;
+    let old_followups = request
+        .find_related(request_message::Entity)
+        .all(&txn)
+        .await
+        .context(error::DatabaseSnafu)?;
+    request_message::Entity::delete_many()
+        .filter(request_message::Column::Request.eq(request.id))
+        .exec(&txn)
+        .await
+        .context(error::DatabaseSnafu)?;
+    for (i, message) in followups.iter().enumerate() {
+        request_message::ActiveModel {
+            request: Set(request.id),
+            page: Set(i as i32 + 1),
+            discord_channel_id: Set(channel.db_id()),
+            discord_message_id: Set(message.db_id()),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await
+        .context(error::DatabaseSnafu)?;
+    }
+    txn.commit().await.context(error::DatabaseSnafu)?;
+
+    // Whatever is left of the old messages, which may well be nothing
+    let old_messages = old_followups
+        .iter()
+        .map(|followup| {
+            (
+                followup.discord_channel_id.discord(),
+                followup.discord_message_id.discord(),
+            )
+        })
+        .chain(
+            request
+                .discord_message_id
+                .map(|message| (channel, message.discord())),
+        );
+    delete_messages(api, old_messages).await;
+    sync_mirrors(db, api, request.id).await;
+    sync_feed(db, api, request.id).await;
+    sync_pin(db, api, request.id).await;
+    Ok((channel, *first))
+}
+
+async fn delete_messages(
+    api: &dyn DiscordApi,
+    messages: impl IntoIterator<Item = (ChannelId, MessageId)>,
+) {
+    for (channel, message) in messages {
+        if let Err(err) = api.delete_message(channel, message).await {
+            tracing::debug!(
+                error = &err as &dyn std::error::Error,
+                %message,
+                "failed to delete request message, ignoring..."
+            );
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! Starts new seasons in servers that have them, and announces the winners of the season that ended
+//!
+//! Servers pick how long their seasons last with `/server-seasons`, see [`SeasonLength`]. The first
+//! season starts as soon as they do. Seasons per war are told apart by the War API's war number, so
+//! a season that was started before the server switched to them takes on the number of the war that
+//! is being fought, and ends with it.
+
+use std::{str::FromStr, time::Duration};
+
+use entity::{guild_setting, season};
+use sea_orm::{
+    sea_query::{Expr, OnConflict},
+    ActiveValue::Set,
+    ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+};
+use serenity::model::id::GuildId;
+use time::OffsetDateTime;
+
+use crate::{
+    backoff::{self, Backoff},
+    discord_api::{self, DiscordApi},
+    discord_ids::{FromDiscordId, ToDiscordId},
+    expiration_controller::Partition,
+    leader,
+    leaderboard::{self, SeasonLength, Standing},
+    war_map::WarApi,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// How many of a season's top contributors are announced as its winners
+const WINNERS: usize = 3;
+
+pub async fn run(
+    db: &DatabaseConnection,
+    discord: &dyn DiscordApi,
+    war_api: &WarApi,
+    partition: &Partition,
+) {
+    let mut backoff = Backoff::new(POLL_INTERVAL, backoff::MAX_RETRY_DELAY);
+    loop {
+        let delay = match run_turn(db, discord, war_api, partition).await {
+            Ok(()) => {
+                backoff.succeeded();
+                POLL_INTERVAL
+            }
+            Err(err) => {
+                let delay = backoff.failed();
+                tracing::error!(error = &err as &dyn std::error::Error, retry_in = ?delay, "failed to roll over seasons, retrying...");
+                delay
+            }
+        };
+        tokio::time::sleep(delay).await;
+    }
+}
+
+async fn run_turn(
+    db: &DatabaseConnection,
+    discord: &dyn DiscordApi,
+    war_api: &WarApi,
+    partition: &Partition,
+) -> Result<(), DbErr> {
+    let Some(leadership) =
+        leader::try_lead(db, leader::Controller::Season, partition.application_id.0).await?
+    else {
+        return Ok(());
+    };
+    let settings = guild_setting::Entity::find()
+        .filter(guild_setting::Column::SeasonLength.is_not_null())
+        .all(db)
+        .await?;
+    // Only asked for once per turn, and only if a server needs it
+    let mut war = None;
+    for settings in settings {
+        let Some(length) = settings
+            .season_length
+            .as_deref()
+            .and_then(|length| SeasonLength::from_str(length).ok())
+        else {
+            continue;
+        };
+        let guild = settings.discord_guild_id.discord();
+        let war_number = match length {
+            SeasonLength::Weekly => None,
+            SeasonLength::War => {
+                if war.is_none() {
+                    war = Some(war_api.war_number().await.map_err(|err| {
+                        tracing::warn!(
+                            error = &err as &dyn std::error::Error,
+                            "failed to find out which war is being fought, retrying later..."
+                        );
+                    }));
+                }
+                war.and_then(Result::ok)
+            }
+        };
+        let now = OffsetDateTime::now_utc();
+        let Some(current) = leaderboard::current_season(db, guild).await? else {
+            start_season(db, guild, 1, now, war_number).await?;
+            continue;
+        };
+        if current.war_number.is_none() && length == SeasonLength::War {
+            if let Some(war_number) = war_number {
+                season::Entity::update_many()
+                    .col_expr(season::Column::WarNumber, Expr::value(war_number))
+                    .filter(season::Column::DiscordGuildId.eq(current.discord_guild_id))
+                    .filter(season::Column::Number.eq(current.number))
+                    .exec(db)
+                    .await?;
+            }
+            continue;
+        }
+        if !leaderboard::is_over(&current, length, now, war_number) {
+            continue;
+        }
+        // Every bot in the server takes its turn, so only the one that ends the season announces it
+        let ended = season::Entity::update_many()
+            .col_expr(season::Column::EndedAt, Expr::value(now))
+            .filter(season::Column::DiscordGuildId.eq(current.discord_guild_id))
+            .filter(season::Column::Number.eq(current.number))
+            .filter(season::Column::EndedAt.is_null())
+            .exec(db)
+            .await?;
+        if ended.rows_affected == 0 {
+            continue;
+        }
+        start_season(db, guild, current.number + 1, now, war_number).await?;
+        tracing::info!(guild.id = %guild, season = current.number, "season ended");
+        let Some(channel) = settings.feed_channel.or(settings.report_channel) else {
+            continue;
+        };
+        let ended = season::Model {
+            ended_at: Some(now),
+            ..current
+        };
+        let standings = leaderboard::standings(db, guild, Some(&ended)).await?;
+        let channel = channel.discord();
+        if let Err(err) = discord
+            .send_message(
+                channel,
+                discord_api::create_message(|msg| {
+                    msg.content(announcement(ended.number, &standings))
+                        .allowed_mentions(|mentions| mentions.empty_parse())
+                }),
+            )
+            .await
+        {
+            // The season has already moved on, so the announcement isn't retried
+            tracing::error!(error = &err as &dyn std::error::Error, guild.id = %guild, %channel, "failed to announce the winners of the season, ignoring...");
+        }
+    }
+    leadership.release().await
+}
+
+async fn start_season(
+    db: &DatabaseConnection,
+    guild: GuildId,
+    number: i32,
+    now: OffsetDateTime,
+    war_number: Option<i32>,
+) -> Result<(), DbErr> {
+    season::Entity::insert(season::ActiveModel {
+        discord_guild_id: Set(guild.db_id()),
+        number: Set(number),
+        started_at: Set(now),
+        ended_at: Set(None),
+        war_number: Set(war_number),
+    })
+    .on_conflict(
+        OnConflict::columns([season::Column::DiscordGuildId, season::Column::Number])
+            .do_nothing()
+            .to_owned(),
+    )
+    .exec_without_returning(db)
+    .await?;
+    Ok(())
+}
+
+fn announcement(number: i32, standings: &[Standing]) -> String {
+    if standings.is_empty() {
+        return format!(
+            "🏁 Season {number} is over, without any completed tasks. Season {} starts now!",
+            number + 1
+        );
+    }
+    format!(
+        "🏆 Season {number} is over! Thanks to everyone who hauled, and congratulations to the top contributors:\n{}Season {} starts now, see `/leaderboard`",
+        leaderboard::format(standings, WINNERS),
+        number + 1
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use entity::discord_id::DiscordId;
+
+    use super::announcement;
+    use crate::leaderboard::Standing;
+
+    #[test]
+    fn announcements_name_the_winners() {
+        let standings = (1..=4)
+            .map(|i| Standing {
+                user: DiscordId::new(i),
+                completed: 5 - i as usize,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            announcement(3, &standings),
+            "🏆 Season 3 is over! Thanks to everyone who hauled, and congratulations to the top contributors:\n\
+             🥇 <@1>: 4 task(s)\n🥈 <@2>: 3 task(s)\n🥉 <@3>: 2 task(s)\n\
+             Season 4 starts now, see `/leaderboard`"
+        );
+        assert_eq!(
+            announcement(3, &[]),
+            "🏁 Season 3 is over, without any completed tasks. Season 4 starts now!"
+        );
+    }
+}
@@ -0,0 +1,453 @@
+//! Aggregated statistics about the requests made in a guild, see [`crate::chart`] for drawing them
+//!
+//! Requests are bucketed by the (guild-local) day that they were made on, so a request made on
+//! Monday and completed on Wednesday counts towards Monday's completion rate.
+//!
+//! Servers can set a target for how long requests should take to complete with
+//! `/server-target-completion`, in which case the share of requests that met it is reported too.
+//! Tasks with their own deadlines are held to those instead, once they are done or overdue.
+
+use std::collections::HashMap;
+
+use entity::{guild_setting, request, task};
+use sea_orm::{
+    prelude::Uuid, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+};
+use serenity::model::id::GuildId;
+use time::{Date, Duration, OffsetDateTime};
+use time_tz::{OffsetDateTimeExt, Tz};
+
+use crate::discord_ids::ToDiscordId;
+
+/// Requests made on a single day
+#[derive(Clone, Debug, PartialEq)]
+pub struct DailyStats {
+    pub date: Date,
+    /// Number of requests made
+    pub created: usize,
+    /// Number of the requests made that have been completed since
+    pub completed: usize,
+    /// Average time from making a completed request until its last task was completed
+    pub average_time_to_completion: Option<Duration>,
+    /// Number of the completed requests that were completed within the server's target, if any
+    pub completed_within_target: Option<usize>,
+}
+
+impl DailyStats {
+    /// Share of requests that have been completed, between 0 and 1
+    pub fn completion_rate(&self) -> Option<f64> {
+        completion_rate(self.completed, self.created)
+    }
+
+    /// Share of completed requests that were completed within the server's target, between 0 and 1
+    pub fn target_attainment(&self) -> Option<f64> {
+        completion_rate(self.completed_within_target?, self.completed)
+    }
+}
+
+/// Requests made over a number of days, see [`guild_stats`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GuildStats {
+    /// One entry per day, oldest first, including days without any requests
+    pub days: Vec<DailyStats>,
+    pub created: usize,
+    pub completed: usize,
+    pub average_time_to_completion: Option<Duration>,
+    /// Average time from making a request until any of its tasks was first claimed (or completed)
+    pub average_time_to_first_claim: Option<Duration>,
+    /// The server's target time to completion, if it has one
+    pub target_completion: Option<Duration>,
+    pub completed_within_target: Option<usize>,
+    /// Number of tasks with deadlines that have been completed or have run past them
+    pub tasks_due: usize,
+    /// Number of the due tasks that were completed by their deadline
+    pub tasks_done_in_time: usize,
+}
+
+impl GuildStats {
+    /// Share of requests that have been completed, between 0 and 1
+    pub fn completion_rate(&self) -> Option<f64> {
+        completion_rate(self.completed, self.created)
+    }
+
+    /// Share of completed requests that were completed within the server's target, between 0 and 1
+    pub fn target_attainment(&self) -> Option<f64> {
+        completion_rate(self.completed_within_target?, self.completed)
+    }
+
+    /// Share of due tasks that were completed by their deadline, between 0 and 1
+    pub fn deadline_attainment(&self) -> Option<f64> {
+        completion_rate(self.tasks_done_in_time, self.tasks_due)
+    }
+}
+
+fn completion_rate(completed: usize, created: usize) -> Option<f64> {
+    (created > 0).then(|| completed as f64 / created as f64)
+}
+
+fn average(durations: &[Duration]) -> Option<Duration> {
+    (!durations.is_empty())
+        .then(|| durations.iter().copied().sum::<Duration>() / durations.len() as u32)
+}
+
+/// Formats an average time to completion, to the minute
+pub fn format_time_to_completion(time: Duration) -> String {
+    humantime::format_duration(std::time::Duration::from_secs(
+        time.whole_minutes().max(1) as u64 * 60,
+    ))
+    .to_string()
+}
+
+/// How long requests in the server should take at most to be completed, if it has set a target
+pub async fn target_completion(
+    db: &DatabaseConnection,
+    guild: GuildId,
+) -> Result<Option<Duration>, DbErr> {
+    Ok(guild_setting::Entity::find_by_id(guild.db_id())
+        .one(db)
+        .await?
+        .and_then(|settings| settings.target_completion_secs)
+        .map(Duration::seconds))
+}
+
+/// Gathers statistics for the requests made in `guild` over the last `days` days (including today)
+pub async fn guild_stats(
+    db: &DatabaseConnection,
+    guild: GuildId,
+    days: u32,
+    tz: &Tz,
+    now: OffsetDateTime,
+) -> Result<GuildStats, DbErr> {
+    let today = now.to_timezone(tz).date();
+    let first_day = today - Duration::days(i64::from(days.max(1)) - 1);
+    // Fetch a day extra on either side, since days are guild-local and the timestamps are not
+    let requests = request::Entity::find()
+        .filter(request::Column::DiscordGuildId.eq(guild.db_id()))
+        .filter(
+            request::Column::CreatedAt
+                .gte(first_day.previous_day().unwrap().midnight().assume_utc()),
+        )
+        .order_by_asc(request::Column::CreatedAt)
+        .find_with_related(task::Entity)
+        .all(db)
+        .await?;
+    let target = target_completion(db, guild).await?;
+    Ok(aggregate(&requests, first_day, today, tz, target, now))
+}
+
+/// Works out when a request was completed, which is when the last of its remaining tasks was
+///
+/// Tasks that were moved to other requests or removed don't count, and neither do requests that
+/// were merged away as duplicates.
+pub fn completed_at(request: &request::Model, tasks: &[task::Model]) -> Option<OffsetDateTime> {
+    if request.merged_into.is_some() {
+        return None;
+    }
+    let mut tasks = tasks
+        .iter()
+        .filter(|task| task.moved_to.is_none() && task.removed_at.is_none())
+        .peekable();
+    tasks.peek()?;
+    tasks
+        .map(|task| task.completed_at)
+        .try_fold(None, |last, completed_at| {
+            Some(last.max(Some(completed_at?)))
+        })
+        .flatten()
+}
+
+/// Buckets `requests` into the days from `first_day` to `last_day` (inclusive) in `tz`
+///
+/// Requests made outside of that range are ignored. Requests are only checked against the `target`
+/// time to completion if there is one, and tasks are only checked against their deadlines once
+/// they are completed or `now` has passed them.
+pub fn aggregate(
+    requests: &[(request::Model, Vec<task::Model>)],
+    first_day: Date,
+    last_day: Date,
+    tz: &Tz,
+    target: Option<Duration>,
+    now: OffsetDateTime,
+) -> GuildStats {
+    let mut days = Vec::new();
+    let mut tasks_due = 0;
+    let mut tasks_done_in_time = 0;
+    let mut times_to_completion = Vec::new();
+    let mut times_to_first_claim = Vec::new();
+    let mut date = first_day;
+    while date <= last_day {
+        let mut day_times_to_completion = Vec::new();
+        let mut created = 0;
+        for (request, tasks) in requests {
+            if request.merged_into.is_some() || request.created_at.to_timezone(tz).date() != date {
+                continue;
+            }
+            created += 1;
+            if let Some(first_claimed_at) = request.first_claimed_at {
+                times_to_first_claim.push(first_claimed_at - request.created_at);
+            }
+            if let Some(completed_at) = completed_at(request, tasks) {
+                day_times_to_completion.push(completed_at - request.created_at);
+            }
+            let deadlines = tasks
+                .iter()
+                .filter(|task| task.moved_to.is_none() && task.removed_at.is_none())
+                .filter_map(|task| Some((task.deadline?, task.completed_at)));
+            for (deadline, completed_at) in deadlines {
+                match completed_at {
+                    Some(completed_at) => {
+                        tasks_due += 1;
+                        if completed_at <= deadline {
+                            tasks_done_in_time += 1;
+                        }
+                    }
+                    None if deadline < now => tasks_due += 1,
+                    None => {}
+                }
+            }
+        }
+        days.push(DailyStats {
+            date,
+            created,
+            completed: day_times_to_completion.len(),
+            average_time_to_completion: average(&day_times_to_completion),
+            completed_within_target: within_target(&day_times_to_completion, target),
+        });
+        times_to_completion.extend(day_times_to_completion);
+        date = date.next_day().unwrap();
+    }
+    GuildStats {
+        created: days.iter().map(|day| day.created).sum(),
+        completed: times_to_completion.len(),
+        average_time_to_completion: average(&times_to_completion),
+        average_time_to_first_claim: average(&times_to_first_claim),
+        target_completion: target,
+        completed_within_target: within_target(&times_to_completion, target),
+        tasks_due,
+        tasks_done_in_time,
+        days,
+    }
+}
+
+fn within_target(times_to_completion: &[Duration], target: Option<Duration>) -> Option<usize> {
+    let target = target?;
+    Some(
+        times_to_completion
+            .iter()
+            .filter(|time| **time <= target)
+            .count(),
+    )
+}
+
+/// What went into a single request, shown once it has been archived
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArchiveSummary {
+    /// Time from making the request until its last task was completed, if it ever was
+    pub time_to_completion: Option<Duration>,
+    /// Number of completed tasks that each user is credited with, most first
+    pub tasks_per_user: Vec<(Uuid, usize)>,
+}
+
+impl ArchiveSummary {
+    /// Number of users that are credited with any of the request's tasks
+    pub fn contributors(&self) -> usize {
+        self.tasks_per_user.len()
+    }
+}
+
+/// Summarizes a request from its tasks' timestamps
+///
+/// Users are credited for the tasks that they completed, as well as for the tasks that they had
+/// claimed before somebody else completed them.
+pub fn archive_summary(request: &request::Model, tasks: &[task::Model]) -> ArchiveSummary {
+    let mut tasks_per_user = HashMap::<Uuid, usize>::new();
+    let completed_tasks = tasks.iter().filter(|task| {
+        task.moved_to.is_none() && task.removed_at.is_none() && task.completed_at.is_some()
+    });
+    for task in completed_tasks {
+        let claimer = task
+            .assigned_to
+            .filter(|claimer| task.started_at.is_some() && Some(*claimer) != task.completed_by);
+        for user in task.completed_by.into_iter().chain(claimer) {
+            *tasks_per_user.entry(user).or_default() += 1;
+        }
+    }
+    let mut tasks_per_user = tasks_per_user.into_iter().collect::<Vec<_>>();
+    tasks_per_user.sort_by(|(a_user, a_tasks), (b_user, b_tasks)| {
+        b_tasks.cmp(a_tasks).then(a_user.cmp(b_user))
+    });
+    ArchiveSummary {
+        time_to_completion: completed_at(request, tasks)
+            .map(|completed_at| completed_at - request.created_at),
+        tasks_per_user,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use entity::{request, task};
+    use sea_orm::prelude::Uuid;
+    use time::{Duration, OffsetDateTime};
+
+    use super::{aggregate, archive_summary};
+    use crate::{testing, time_zone};
+
+    fn request(id: u128, created_at: OffsetDateTime) -> request::Model {
+        request::Model {
+            created_at,
+            updated_at: created_at,
+            ..testing::request(id)
+        }
+    }
+
+    fn task(request: &request::Model, completed_at: Option<OffsetDateTime>) -> task::Model {
+        task::Model {
+            completed_at,
+            ..testing::task(request, 0)
+        }
+    }
+
+    #[test]
+    fn aggregate_by_creation_day() {
+        // 2024-07-15, 12:00 UTC
+        let monday = OffsetDateTime::from_unix_timestamp(1721044800).unwrap();
+        let tuesday = monday + Duration::days(1);
+        let mut completed = request(1, monday);
+        completed.first_claimed_at = Some(monday + Duration::minutes(10));
+        let mut partial = request(2, monday);
+        partial.first_claimed_at = Some(monday + Duration::minutes(30));
+        let moved = request(3, tuesday);
+        let mut moved_task = task(&moved, None);
+        moved_task.moved_to = Some(Uuid::from_u128(4));
+        let requests = [
+            (
+                completed.clone(),
+                vec![
+                    task(&completed, Some(monday + Duration::hours(1))),
+                    task(&completed, Some(monday + Duration::hours(3))),
+                ],
+            ),
+            (
+                partial.clone(),
+                vec![
+                    task(&partial, Some(monday + Duration::hours(1))),
+                    task(&partial, None),
+                ],
+            ),
+            (
+                moved.clone(),
+                vec![moved_task, task(&moved, Some(tuesday + Duration::hours(1)))],
+            ),
+        ];
+        let stats = aggregate(
+            &requests,
+            monday.date().previous_day().unwrap(),
+            tuesday.date(),
+            time_zone::default(),
+            Some(Duration::hours(2)),
+            tuesday + Duration::hours(12),
+        );
+
+        let days = stats
+            .days
+            .iter()
+            .map(|day| (day.created, day.completed, day.average_time_to_completion))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            days,
+            [
+                (0, 0, None),
+                (2, 1, Some(Duration::hours(3))),
+                (1, 1, Some(Duration::hours(1))),
+            ]
+        );
+        assert_eq!(stats.days[0].completion_rate(), None);
+        assert_eq!(stats.created, 3);
+        assert_eq!(stats.completed, 2);
+        assert_eq!(stats.average_time_to_completion, Some(Duration::hours(2)));
+        // Requests that nobody has taken on yet don't count
+        assert_eq!(
+            stats.average_time_to_first_claim,
+            Some(Duration::minutes(20))
+        );
+        assert_eq!(stats.completion_rate(), Some(2.0 / 3.0));
+        let attainment = stats
+            .days
+            .iter()
+            .map(|day| day.target_attainment())
+            .collect::<Vec<_>>();
+        assert_eq!(attainment, [None, Some(0.0), Some(1.0)]);
+        assert_eq!(stats.completed_within_target, Some(1));
+        assert_eq!(stats.target_attainment(), Some(0.5));
+    }
+
+    #[test]
+    fn aggregate_deadlines_once_they_are_due() {
+        let created_at = OffsetDateTime::from_unix_timestamp(1721044800).unwrap();
+        let request = request(1, created_at);
+        let deadline = created_at + Duration::hours(2);
+        let with_deadline = |completed_at| task::Model {
+            deadline: Some(deadline),
+            ..task(&request, completed_at)
+        };
+        let mut moved = with_deadline(None);
+        moved.moved_to = Some(Uuid::from_u128(2));
+        let requests = [(
+            request.clone(),
+            vec![
+                with_deadline(Some(created_at + Duration::hours(1))),
+                with_deadline(Some(created_at + Duration::hours(3))),
+                with_deadline(None),
+                moved,
+                task(&request, None),
+            ],
+        )];
+        let stats_at = |now| {
+            aggregate(
+                &requests,
+                created_at.date(),
+                created_at.date(),
+                time_zone::default(),
+                None,
+                now,
+            )
+        };
+
+        let stats = stats_at(created_at + Duration::hours(1));
+        assert_eq!((stats.tasks_due, stats.tasks_done_in_time), (2, 1));
+        // Open tasks only count against the server once they are overdue
+        let stats = stats_at(created_at + Duration::hours(4));
+        assert_eq!((stats.tasks_due, stats.tasks_done_in_time), (3, 1));
+        assert_eq!(stats.deadline_attainment(), Some(1.0 / 3.0));
+    }
+
+    #[test]
+    fn archive_summary_credits_finishers_and_claimers() {
+        let created_at = OffsetDateTime::from_unix_timestamp(1721044800).unwrap();
+        let request = request(1, created_at);
+        let (alice, bob) = (Uuid::from_u128(10), Uuid::from_u128(11));
+        let mut solo = task(&request, Some(created_at + Duration::hours(1)));
+        solo.completed_by = Some(alice);
+        solo.assigned_to = Some(alice);
+        solo.started_at = Some(created_at);
+        let mut handed_over = task(&request, Some(created_at + Duration::hours(5)));
+        handed_over.completed_by = Some(alice);
+        handed_over.assigned_to = Some(bob);
+        handed_over.started_at = Some(created_at);
+        let mut moved = task(&request, None);
+        moved.moved_to = Some(Uuid::from_u128(2));
+
+        let summary = archive_summary(&request, &[solo, handed_over, moved]);
+        assert_eq!(summary.time_to_completion, Some(Duration::hours(5)));
+        assert_eq!(summary.tasks_per_user, [(alice, 2), (bob, 1)]);
+        assert_eq!(summary.contributors(), 2);
+    }
+
+    #[test]
+    fn archive_summary_of_expired_request() {
+        let request = request(1, OffsetDateTime::UNIX_EPOCH);
+        let summary = archive_summary(&request, &[task(&request, None)]);
+        assert_eq!(summary.time_to_completion, None);
+        assert_eq!(summary.contributors(), 0);
+    }
+}
@@ -0,0 +1,239 @@
+//! Counts the crates of catalog items that completed tasks deliver into the server's target
+//! stockpile, see `/server-stockpile`
+//!
+//! The catalog is the list of items that `/mpf` knows, see [`production`]. Tasks only count if
+//! they are nothing but an amount of one of them, such as `40 shirts`. The counts drift from the
+//! game as soon as anything is taken out of the stockpile, so moderators can correct them by hand.
+
+use std::collections::{hash_map::Entry, HashMap};
+
+use entity::{
+    discord_id::{kind, DiscordId},
+    guild_setting, request, stockpile_item, task,
+};
+use sea_orm::{
+    sea_query::{Alias, Expr, Func, OnConflict, SimpleExpr},
+    ActiveModelTrait,
+    ActiveValue::{Set, Unchanged},
+    ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+};
+use serenity::model::id::GuildId;
+
+use crate::{
+    discord_ids::ToDiscordId,
+    production::{self, Item},
+    task_syntax,
+};
+
+/// The catalog item that a task delivers, and how many crates of it
+///
+/// Tasks of more crates than a stockpile can count aren't counted at all.
+pub fn catalog_item(task: &str) -> Option<(&'static Item, i32)> {
+    let specs = task_syntax::parse(task).ok()?;
+    let [spec] = specs.as_slice() else {
+        return None;
+    };
+    let item = production::find(&spec.item)?;
+    let amount = i32::try_from(spec.amount.unwrap_or(1)).ok()?;
+    let crates = amount.checked_mul(i32::try_from(spec.multiplier).ok()?)?;
+    Some((item, crates))
+}
+
+/// Adds `crates` (which may be negative) to the count of a stockpile item, saturating rather than
+/// overflowing the column
+fn add_to_count(count: SimpleExpr, crates: i32) -> SimpleExpr {
+    let sum = count.cast_as(Alias::new("bigint")).add(i64::from(crates));
+    let clamped = Func::cust(Alias::new("GREATEST"))
+        .arg(
+            Func::cust(Alias::new("LEAST"))
+                .arg(sum)
+                .arg(i64::from(i32::MAX)),
+        )
+        .arg(i64::from(i32::MIN));
+    SimpleExpr::from(clamped).cast_as(Alias::new("integer"))
+}
+
+/// Adds the crates of the completed `tasks` to the target stockpiles of their servers, returning
+/// the tasks as they are now
+///
+/// Tasks that have already been counted are left alone, so that completing them again doesn't
+/// count them twice.
+pub async fn deliver(
+    db: &DatabaseConnection,
+    tasks: Vec<task::Model>,
+) -> Result<Vec<task::Model>, DbErr> {
+    let mut targets = HashMap::new();
+    let mut delivered = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        let crates = catalog_item(&task.task)
+            .filter(|_| task.completed_at.is_some() && task.stocked_in.is_none());
+        let Some((item, crates)) = crates else {
+            delivered.push(task);
+            continue;
+        };
+        let target = match targets.entry(task.request) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(target_of_request(db, task.request).await?),
+        };
+        let Some((guild, stockpile)) = target.clone() else {
+            delivered.push(task);
+            continue;
+        };
+        stockpile_item::Entity::insert(stockpile_item::ActiveModel {
+            discord_guild_id: Set(guild),
+            stockpile: Set(stockpile.clone()),
+            item: Set(item.name.to_string()),
+            count: Set(crates),
+        })
+        .on_conflict(
+            OnConflict::columns([
+                stockpile_item::Column::DiscordGuildId,
+                stockpile_item::Column::Stockpile,
+                stockpile_item::Column::Item,
+            ])
+            .value(
+                stockpile_item::Column::Count,
+                add_to_count(
+                    Expr::col((stockpile_item::Entity, stockpile_item::Column::Count)).into(),
+                    crates,
+                ),
+            )
+            .to_owned(),
+        )
+        .exec(db)
+        .await?;
+        let task = task::ActiveModel {
+            id: Unchanged(task.id),
+            stocked_in: Set(Some(stockpile)),
+            stocked_crates: Set(Some(crates)),
+            ..Default::default()
+        }
+        .update(db)
+        .await?;
+        delivered.push(task);
+    }
+    Ok(delivered)
+}
+
+/// Takes the crates that `tasks` added back out of their stockpiles, now that they are no longer
+/// completed, returning the tasks as they are now
+pub async fn undeliver(
+    db: &DatabaseConnection,
+    tasks: Vec<task::Model>,
+) -> Result<Vec<task::Model>, DbErr> {
+    let mut undelivered = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        let (Some(stockpile), Some(crates)) = (&task.stocked_in, task.stocked_crates) else {
+            undelivered.push(task);
+            continue;
+        };
+        let item = catalog_item(&task.task).map(|(item, _)| item.name);
+        let guild = request::Entity::find_by_id(task.request)
+            .one(db)
+            .await?
+            .and_then(|request| request.discord_guild_id);
+        if let (Some(guild), Some(item)) = (guild, item) {
+            stockpile_item::Entity::update_many()
+                .col_expr(
+                    stockpile_item::Column::Count,
+                    add_to_count(
+                        Expr::col(stockpile_item::Column::Count).into(),
+                        crates.saturating_neg(),
+                    ),
+                )
+                .filter(stockpile_item::Column::DiscordGuildId.eq(guild))
+                .filter(stockpile_item::Column::Stockpile.eq(stockpile))
+                .filter(stockpile_item::Column::Item.eq(item))
+                .exec(db)
+                .await?;
+        }
+        let task = task::ActiveModel {
+            id: Unchanged(task.id),
+            stocked_in: Set(None),
+            stocked_crates: Set(None),
+            ..Default::default()
+        }
+        .update(db)
+        .await?;
+        undelivered.push(task);
+    }
+    Ok(undelivered)
+}
+
+/// The server and target stockpile that the tasks of `request` are counted into, if any
+async fn target_of_request(
+    db: &DatabaseConnection,
+    request: sea_orm::prelude::Uuid,
+) -> Result<Option<(DiscordId<kind::Guild>, String)>, DbErr> {
+    let Some(guild) = request::Entity::find_by_id(request)
+        .one(db)
+        .await?
+        .and_then(|request| request.discord_guild_id)
+    else {
+        return Ok(None);
+    };
+    Ok(guild_setting::Entity::find_by_id(guild)
+        .one(db)
+        .await?
+        .and_then(|settings| settings.target_stockpile)
+        .map(|stockpile| (guild, stockpile)))
+}
+
+/// The count of every item in `stockpile`, by item name
+pub async fn counts(
+    db: &DatabaseConnection,
+    guild: GuildId,
+    stockpile: &str,
+) -> Result<Vec<stockpile_item::Model>, DbErr> {
+    stockpile_item::Entity::find()
+        .filter(stockpile_item::Column::DiscordGuildId.eq(guild.db_id()))
+        .filter(stockpile_item::Column::Stockpile.eq(stockpile))
+        .order_by_asc(stockpile_item::Column::Item)
+        .all(db)
+        .await
+}
+
+/// Sets the count of `item` in `stockpile` by hand, such as after taking crates out of it
+pub async fn correct(
+    db: &DatabaseConnection,
+    guild: GuildId,
+    stockpile: &str,
+    item: &Item,
+    count: i32,
+) -> Result<(), DbErr> {
+    stockpile_item::Entity::insert(stockpile_item::ActiveModel {
+        discord_guild_id: Set(guild.db_id()),
+        stockpile: Set(stockpile.to_string()),
+        item: Set(item.name.to_string()),
+        count: Set(count),
+    })
+    .on_conflict(
+        OnConflict::columns([
+            stockpile_item::Column::DiscordGuildId,
+            stockpile_item::Column::Stockpile,
+            stockpile_item::Column::Item,
+        ])
+        .update_column(stockpile_item::Column::Count)
+        .to_owned(),
+    )
+    .exec(db)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::catalog_item;
+
+    #[test]
+    fn tasks_of_catalog_items_count_their_crates() {
+        let (item, crates) = catalog_item("40 shirts").unwrap();
+        assert_eq!((item.name, crates), ("Soldier Supplies", 40));
+        assert_eq!(catalog_item("{3x} 2 762").unwrap().1, 6);
+        assert_eq!(catalog_item("bandages").unwrap().1, 1);
+        assert!(catalog_item("300 bmats").is_none());
+        assert!(catalog_item("40 shirts; 5 bandages").is_none());
+        assert!(catalog_item("4000000000 shirts").is_none());
+        assert!(catalog_item("{100x} 30000000 shirts").is_none());
+    }
+}
@@ -0,0 +1,187 @@
+//! The spreadsheets accepted by `/request-import`
+//!
+//! Each row is a task, with the columns `task`, `amount`, and `notes` (in that order). The amount
+//! and notes may be left empty or out entirely. Rows are separated into columns by tabs (as when
+//! pasting from a spreadsheet) or commas (CSV), whichever the first row uses. Fields may be
+//! wrapped in double quotes, with `""` standing for a literal quote. A header row is skipped if
+//! its first column is `task`.
+//!
+//! ```text
+//! task,amount,notes
+//! bmats,300,for the trucks
+//! "shirts, medium",40,
+//! ```
+
+use snafu::{ensure, Snafu};
+
+use crate::task_syntax::TaskSpec;
+
+#[derive(Debug, Snafu, PartialEq, Eq)]
+#[snafu(module)]
+pub enum Error {
+    #[snafu(display("row {row} has an unterminated quote"))]
+    UnterminatedQuote { row: usize },
+    #[snafu(display(
+        "row {row} has {columns} columns, but may have at most 3 (task, amount, notes)"
+    ))]
+    TooManyColumns { row: usize, columns: usize },
+    #[snafu(display("row {row} has no task"))]
+    MissingTask { row: usize },
+    #[snafu(display("row {row} has an invalid amount {amount:?}, expected a whole number"))]
+    InvalidAmount { row: usize, amount: String },
+    #[snafu(display("the file has no tasks"))]
+    NoTasks,
+}
+
+/// Parses a spreadsheet, see the [module documentation](self) for the format
+///
+/// Every invalid row is reported, rather than just the first one.
+pub fn parse(input: &str) -> Result<Vec<TaskSpec>, Vec<Error>> {
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim_end_matches('\r')))
+        .filter(|(_, line)| !line.trim().is_empty())
+        .peekable();
+    let delimiter = match lines.peek() {
+        Some((_, line)) if line.contains('\t') => '\t',
+        _ => ',',
+    };
+    let mut tasks = Vec::new();
+    let mut errors = Vec::new();
+    for (i, (row, line)) in lines.enumerate() {
+        let fields = match split_row(row, line, delimiter) {
+            Ok(fields) => fields,
+            Err(err) => {
+                errors.push(err);
+                continue;
+            }
+        };
+        if i == 0 && fields[0].eq_ignore_ascii_case("task") {
+            continue;
+        }
+        match parse_row(row, &fields) {
+            Ok(task) => tasks.push(task),
+            Err(err) => errors.push(err),
+        }
+    }
+    if errors.is_empty() && tasks.is_empty() {
+        errors.push(Error::NoTasks);
+    }
+    if errors.is_empty() {
+        Ok(tasks)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Splits a row into its (trimmed) fields, resolving quotes
+fn split_row(row: usize, line: &str, delimiter: char) -> Result<Vec<String>, Error> {
+    let mut fields = vec![String::new()];
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().unwrap();
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => fields.push(String::new()),
+            c => field.push(c),
+        }
+    }
+    ensure!(!in_quotes, error::UnterminatedQuoteSnafu { row });
+    Ok(fields
+        .into_iter()
+        .map(|field| field.trim().to_string())
+        .collect())
+}
+
+fn parse_row(row: usize, fields: &[String]) -> Result<TaskSpec, Error> {
+    // Spreadsheets tend to pad rows out with empty trailing columns
+    let columns = fields
+        .iter()
+        .rposition(|field| !field.is_empty())
+        .map_or(0, |last| last + 1);
+    ensure!(columns <= 3, error::TooManyColumnsSnafu { row, columns });
+    let field = |i: usize| fields.get(i).map_or("", String::as_str);
+    let (task, amount, notes) = (field(0), field(1), field(2));
+    ensure!(!task.is_empty(), error::MissingTaskSnafu { row });
+    let amount = match amount {
+        "" => None,
+        amount => Some(amount.parse::<u32>().map_err(|_| Error::InvalidAmount {
+            row,
+            amount: amount.to_string(),
+        })?),
+    };
+    Ok(TaskSpec {
+        multiplier: 1,
+        amount,
+        item: match notes {
+            "" => task.to_string(),
+            notes => format!("{task} ({notes})"),
+        },
+        effort: None,
+        section: None,
+        reserved_for: None,
+        deadline: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Error};
+    use crate::task_syntax::TaskSpec;
+
+    fn task(amount: Option<u32>, item: &str) -> TaskSpec {
+        TaskSpec {
+            multiplier: 1,
+            amount,
+            item: item.to_string(),
+            effort: None,
+            section: None,
+            reserved_for: None,
+            deadline: None,
+        }
+    }
+
+    #[test]
+    fn parses_csv_and_tsv() {
+        let expected = [
+            task(Some(300), "bmats (for the trucks)"),
+            task(Some(40), "shirts, \"medium\""),
+            task(None, "flatbed"),
+        ];
+        assert_eq!(
+            parse(
+                "Task,Amount,Notes\r\nbmats,300,for the trucks\r\n\r\n\"shirts, \"\"medium\"\"\", 40 ,\r\nflatbed"
+            )
+            .unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse("bmats\t300\tfor the trucks\t\t\n\"shirts, \"\"medium\"\"\"\t40\nflatbed\t\t")
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn reports_every_invalid_row() {
+        assert_eq!(
+            parse("task,amount\n,3\nbmats,lots\n\"shirts,4\nbmats,1,a,b").unwrap_err(),
+            [
+                Error::MissingTask { row: 2 },
+                Error::InvalidAmount {
+                    row: 3,
+                    amount: "lots".to_string()
+                },
+                Error::UnterminatedQuote { row: 4 },
+                Error::TooManyColumns { row: 5, columns: 4 },
+            ]
+        );
+        assert_eq!(parse("task,amount,notes\n").unwrap_err(), [Error::NoTasks]);
+    }
+}
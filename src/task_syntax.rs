@@ -0,0 +1,970 @@
+//! The syntax used for the `tasks` option of `/request`
+//!
+//! Tasks are separated by `;`. Each task may be prefixed by a multiplier such as `{3x}`, which
+//! creates that many copies of the task, and may start with an amount (`300 bmats`).
+//! A task may end with an effort estimate such as `~3`, in whatever unit the group finds useful
+//! (crates, trips, minutes...).
+//! A task may end with a deadline, either a time of day such as `{by 18:00}` (the next time that it
+//! comes around in the requester's time zone) or a duration such as `{by 2h}` (counting from when
+//! the task is added).
+//! A task may be reserved for a user or a role by starting it with a mention and a colon
+//! (`@Hauler: flatbed`), which Discord sends as `<@123>: flatbed` (or `<@&123>: flatbed` for roles).
+//! Anything after a `#` is a comment and is ignored.
+//! A section header such as `== Shirts ==` in place of a task groups the tasks after it, up to
+//! the next header (`====` ends the section without starting a new one).
+//!
+//! `;`, `#`, `{`, `<`, `~` and `|` can be used literally by wrapping (part of) the task in double quotes,
+//! or by escaping them with a backslash (`\;`). Inside quotes, `\"` and `\\` are also supported.
+//!
+//! ```text
+//! {2x} 300 bmats ~2; "fuel; diesel" # for the trucks; flatbed ~1 {by 18:00}
+//! ```
+//!
+//! `/request-multi` takes several lists of tasks at once, separated by `|`, see [`split_groups`].
+//!
+//! Commands that act on existing tasks refer to them by number instead, see [`TaskSelection`].
+
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use snafu::{ensure, Snafu};
+use time::{OffsetDateTime, Time};
+use time_tz::{OffsetDateTimeExt, Tz};
+
+use crate::limits;
+
+/// The largest multiplier that a single task may use
+pub const MAX_MULTIPLIER: usize = 100;
+
+#[derive(Debug, Snafu, PartialEq, Eq)]
+#[snafu(module)]
+pub enum Error {
+    #[snafu(display("task {task} has an unterminated quote"))]
+    UnterminatedQuote { task: usize },
+    #[snafu(display("task {task} ends with a dangling backslash"))]
+    DanglingEscape { task: usize },
+    #[snafu(display(
+        "task {task} has an invalid multiplier {multiplier:?}, expected something like {{3x}}"
+    ))]
+    InvalidMultiplier { task: usize, multiplier: String },
+    #[snafu(display("task {task} has a multiplier of {multiplier}, which must be between 1 and {MAX_MULTIPLIER}"))]
+    MultiplierOutOfRange { task: usize, multiplier: usize },
+    #[snafu(display(
+        "task {task} has an invalid deadline {deadline:?}, expected something like {{by 18:00}} or {{by 2h}}"
+    ))]
+    InvalidDeadline { task: usize, deadline: String },
+    #[snafu(display("no tasks given"))]
+    NoTasks,
+}
+
+/// A single task, as written by the requester
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskSpec {
+    /// How many copies of the task should be created
+    pub multiplier: usize,
+    /// How much of [`Self::item`] is requested, if the task starts with a number
+    pub amount: Option<u32>,
+    /// The rest of the task
+    pub item: String,
+    /// The estimated effort of the task, if the task ends with `~N`
+    pub effort: Option<u32>,
+    /// The section that the task was listed under, if any
+    pub section: Option<String>,
+    /// Who may claim and complete the task, if it starts with a mention
+    pub reserved_for: Option<Reservation>,
+    /// When the task should be done by, if it ends with `{by ...}`
+    pub deadline: Option<Deadline>,
+}
+
+/// When a task should be done by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deadline {
+    /// The next time that the clock shows this time of day
+    At(Time),
+    /// This long after the task is added
+    In(std::time::Duration),
+}
+
+impl Deadline {
+    /// The moment that the deadline falls on, for a task that is added at `now` by someone in `tz`
+    pub fn resolve(self, now: OffsetDateTime, tz: &Tz) -> OffsetDateTime {
+        match self {
+            Deadline::At(time) => {
+                let local = now.to_timezone(tz);
+                let mut due = local.replace_time(time);
+                if due <= local {
+                    due += time::Duration::days(1);
+                }
+                due
+            }
+            Deadline::In(duration) => now + duration,
+        }
+    }
+}
+
+impl FromStr for Deadline {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some((hour, minute)) = s.split_once(':') {
+            let time = hour
+                .parse()
+                .ok()
+                .zip(minute.parse().ok().filter(|_| minute.len() == 2))
+                .and_then(|(hour, minute)| Time::from_hms(hour, minute, 0).ok());
+            return time.map(Deadline::At).ok_or(());
+        }
+        humantime::parse_duration(s)
+            .ok()
+            .filter(|duration| !duration.is_zero())
+            .map(Deadline::In)
+            .ok_or(())
+    }
+}
+
+/// Formats the deadline as it is written after `{by`
+impl Display for Deadline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Deadline::At(time) => write!(f, "{:02}:{:02}", time.hour(), time.minute()),
+            Deadline::In(duration) => write!(f, "{}", humantime::format_duration(*duration)),
+        }
+    }
+}
+
+/// Who a task is reserved for, by Discord ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reservation {
+    User(u64),
+    Role(u64),
+}
+
+impl Reservation {
+    /// Whether the user with `roles` may take a task that is reserved like this
+    pub fn allows(self, user: u64, roles: &[u64]) -> bool {
+        match self {
+            Reservation::User(reserved) => reserved == user,
+            Reservation::Role(reserved) => roles.contains(&reserved),
+        }
+    }
+}
+
+/// Formats the reservation as a mention
+impl Display for Reservation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reservation::User(user) => write!(f, "<@{user}>"),
+            Reservation::Role(role) => write!(f, "<@&{role}>"),
+        }
+    }
+}
+
+impl TaskSpec {
+    /// The task as it should be stored and displayed, without the multiplier
+    pub fn text(&self) -> String {
+        match self.amount {
+            Some(amount) => format!("{amount} {}", self.item),
+            None => self.item.clone(),
+        }
+    }
+}
+
+/// Formats the task back into the task syntax, such that [`parse`] returns the same task
+///
+/// The section is left out, since it is written as a header before the task.
+impl Display for TaskSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.multiplier != 1 {
+            write!(f, "{{{}x}} ", self.multiplier)?;
+        }
+        if let Some(reserved_for) = self.reserved_for {
+            write!(f, "{reserved_for}: ")?;
+        }
+        if let Some(amount) = self.amount {
+            write!(f, "{amount} ")?;
+        }
+        for (i, c) in self.item.chars().enumerate() {
+            let ambiguous_amount = i == 0 && self.amount.is_none() && c.is_ascii_digit();
+            let ambiguous_section = i == 0 && self.item.starts_with("==");
+            // A `{` could also start a trailing deadline
+            if matches!(c, ';' | '#' | '"' | '\\' | '~' | '|' | '{')
+                || (i == 0 && c == '<')
+                || ambiguous_amount
+                || ambiguous_section
+            {
+                write!(f, "\\")?;
+            }
+            write!(f, "{c}")?;
+        }
+        if let Some(effort) = self.effort {
+            write!(f, " ~{effort}")?;
+        }
+        if let Some(deadline) = self.deadline {
+            write!(f, " {{by {deadline}}}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A character of a task, remembering whether it was quoted or escaped
+#[derive(Clone, Copy)]
+struct Char {
+    c: char,
+    literal: bool,
+}
+
+/// Parses a list of tasks, see the [module documentation](self) for the syntax
+pub fn parse(input: &str) -> Result<Vec<TaskSpec>, Error> {
+    let mut tasks = Vec::new();
+    let mut section = None;
+    for (i, segment) in split(input)?.into_iter().enumerate() {
+        if let Some(header) = parse_section_header(&segment) {
+            section = Some(header).filter(|header| !header.is_empty());
+        } else if let Some(mut task) = parse_task(i + 1, &segment)? {
+            task.section = section.clone();
+            tasks.push(task);
+        }
+    }
+    ensure!(!tasks.is_empty(), error::NoTasksSnafu);
+    Ok(tasks)
+}
+
+/// Splits the input into separate lists of tasks at each `|`, each of which can be [`parse`]d
+///
+/// Quotes and escapes are left for [`parse`] to resolve, but a quoted or escaped `|` doesn't split.
+pub fn split_groups(input: &str) -> Vec<&str> {
+    let mut groups = Vec::new();
+    let mut start = 0;
+    let mut in_quotes = false;
+    let mut in_comment = false;
+    let mut chars = input.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '"' if !in_comment => in_quotes = !in_quotes,
+            ';' if !in_quotes => in_comment = false,
+            '#' if !in_quotes => in_comment = true,
+            '|' if !in_quotes => {
+                in_comment = false;
+                groups.push(&input[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    groups.push(&input[start..]);
+    groups
+}
+
+/// Expands the multipliers of `tasks`, returning each individual task
+pub fn expand(tasks: &[TaskSpec]) -> impl Iterator<Item = &TaskSpec> {
+    tasks
+        .iter()
+        .flat_map(|task| std::iter::repeat_n(task, task.multiplier))
+}
+
+/// Writes `tasks` back in the syntax that [`parse`] reads, with a header wherever the section changes
+pub fn format(tasks: &[TaskSpec]) -> String {
+    let mut parts = Vec::new();
+    let mut section = None;
+    for task in tasks {
+        if task.section != section {
+            parts.push(match &task.section {
+                Some(name) => {
+                    let name = name
+                        .chars()
+                        .map(|c| match c {
+                            ';' | '#' | '"' | '\\' | '|' => format!("\\{c}"),
+                            _ => c.to_string(),
+                        })
+                        .collect::<String>();
+                    format!("== {name} ==")
+                }
+                None => "====".to_string(),
+            });
+            section = task.section.clone();
+        }
+        parts.push(task.to_string());
+    }
+    parts.join("; ")
+}
+
+/// A set of task numbers, such as `1, 3-5`
+///
+/// Numbers and ranges may be separated by commas, semicolons, or whitespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskSelection(pub BTreeSet<i32>);
+
+#[derive(Debug, Snafu, PartialEq, Eq)]
+#[snafu(module)]
+pub enum SelectionError {
+    #[snafu(display("{part:?} is not a task number or range (such as 3 or 1-4)"))]
+    InvalidPart { part: String },
+    #[snafu(display("the range {start}-{end} is backwards"))]
+    BackwardsRange { start: i32, end: i32 },
+    #[snafu(display(
+        "there is no task {task}, requests have at most {} tasks",
+        limits::REQUEST_TASKS
+    ))]
+    TooLarge { task: u64 },
+    #[snafu(display("no tasks selected"))]
+    NoneSelected,
+}
+
+impl TaskSelection {
+    pub fn contains(&self, task: i32) -> bool {
+        self.0.contains(&task)
+    }
+
+    /// Moves the selected tasks out of `order` (task numbers, top first) and back in together,
+    /// keeping their order, so that the first of them ends up at `position` (counting from 1)
+    pub fn move_to(&self, order: &[i32], position: usize) -> Vec<i32> {
+        let (mut moved, mut kept) = order
+            .iter()
+            .partition::<Vec<_>, _>(|task| self.contains(**task));
+        let at = position.saturating_sub(1).min(kept.len());
+        kept.splice(at..at, moved.drain(..));
+        kept
+    }
+}
+
+/// Writes the selection back out compactly, with runs of tasks as ranges (such as `1 3-5`)
+impl Display for TaskSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tasks = self.0.iter().copied().peekable();
+        let mut first = true;
+        while let Some(start) = tasks.next() {
+            let mut end = start;
+            while tasks.next_if_eq(&(end + 1)).is_some() {
+                end += 1;
+            }
+            if !first {
+                f.write_str(" ")?;
+            }
+            first = false;
+            if start == end {
+                write!(f, "{start}")?;
+            } else {
+                write!(f, "{start}-{end}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for TaskSelection {
+    type Err = SelectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tasks = BTreeSet::new();
+        for part in s
+            .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+        {
+            let parse = |n: &str| {
+                let task = n.parse::<u64>().ok().filter(|n| *n > 0).ok_or_else(|| {
+                    SelectionError::InvalidPart {
+                        part: part.to_string(),
+                    }
+                })?;
+                // Ranges are expanded, so they mustn't be able to reach far beyond any request
+                ensure!(
+                    task <= limits::REQUEST_TASKS as u64,
+                    selection_error::TooLargeSnafu { task }
+                );
+                Ok(task as i32)
+            };
+            let (start, end) = match part.split_once('-') {
+                Some((start, end)) => (parse(start)?, parse(end)?),
+                None => (parse(part)?, parse(part)?),
+            };
+            ensure!(
+                start <= end,
+                selection_error::BackwardsRangeSnafu { start, end }
+            );
+            tasks.extend(start..=end);
+        }
+        ensure!(!tasks.is_empty(), selection_error::NoneSelectedSnafu);
+        Ok(Self(tasks))
+    }
+}
+
+/// Splits the input into tasks, resolving quotes and escapes and dropping comments
+fn split(input: &str) -> Result<Vec<Vec<Char>>, Error> {
+    let mut segments = vec![Vec::new()];
+    let mut in_quotes = false;
+    let mut in_comment = false;
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        let task = segments.len();
+        let segment = segments.last_mut().unwrap();
+        match c {
+            '\\' => {
+                let escaped = chars.next().ok_or(Error::DanglingEscape { task })?;
+                if !in_comment {
+                    segment.push(Char {
+                        c: escaped,
+                        literal: true,
+                    });
+                }
+            }
+            '"' if !in_comment => in_quotes = !in_quotes,
+            ';' if !in_quotes => {
+                in_comment = false;
+                segments.push(Vec::new());
+            }
+            '#' if !in_quotes => in_comment = true,
+            _ if in_comment => {}
+            c => segment.push(Char {
+                c,
+                literal: in_quotes,
+            }),
+        }
+    }
+    ensure!(
+        !in_quotes,
+        error::UnterminatedQuoteSnafu {
+            task: segments.len()
+        }
+    );
+    Ok(segments)
+}
+
+/// Parses a section header such as `== Shirts ==`, returning the name of the section
+fn parse_section_header(chars: &[Char]) -> Option<String> {
+    let chars = trim(chars);
+    let is_marker = |marker: &[Char]| marker.iter().all(|c| c.c == '=' && !c.literal);
+    if chars.len() < 4 || !is_marker(&chars[..2]) || !is_marker(&chars[chars.len() - 2..]) {
+        return None;
+    }
+    Some(
+        trim(&chars[2..chars.len() - 2])
+            .iter()
+            .map(|c| c.c)
+            .collect(),
+    )
+}
+
+fn parse_task(task: usize, chars: &[Char]) -> Result<Option<TaskSpec>, Error> {
+    let mut chars = trim(chars);
+    if chars.is_empty() {
+        return Ok(None);
+    }
+
+    let mut multiplier = 1;
+    if let Some(Char {
+        c: '{',
+        literal: false,
+    }) = chars.first()
+    {
+        let end = chars
+            .iter()
+            .position(|c| c.c == '}' && !c.literal)
+            .unwrap_or(chars.len());
+        let raw = chars[1..end].iter().map(|c| c.c).collect::<String>();
+        multiplier = raw
+            .strip_suffix('x')
+            .and_then(|n| n.parse::<usize>().ok())
+            .filter(|_| end < chars.len())
+            .ok_or_else(|| Error::InvalidMultiplier {
+                task,
+                multiplier: raw.clone(),
+            })?;
+        ensure!(
+            (1..=MAX_MULTIPLIER).contains(&multiplier),
+            error::MultiplierOutOfRangeSnafu { task, multiplier }
+        );
+        chars = trim(&chars[end + 1..]);
+    }
+
+    let mut reserved_for = None;
+    if let Some((reservation, len)) = parse_reservation(chars) {
+        reserved_for = Some(reservation);
+        chars = trim(&chars[len..]);
+    }
+
+    let digits = chars
+        .iter()
+        .take_while(|c| c.c.is_ascii_digit() && !c.literal)
+        .count();
+    let amount = chars
+        .get(digits)
+        .filter(|c| digits > 0 && c.c.is_whitespace())
+        .and_then(|_| {
+            chars[..digits]
+                .iter()
+                .map(|c| c.c)
+                .collect::<String>()
+                .parse::<u32>()
+                .ok()
+        });
+    if amount.is_some() {
+        chars = trim(&chars[digits..]);
+    }
+
+    let mut deadline = None;
+    if let Some(Char {
+        c: '}',
+        literal: false,
+    }) = chars.last()
+    {
+        let start = chars.iter().rposition(|c| c.c == '{' && !c.literal);
+        let raw = start.map(|start| {
+            chars[start + 1..chars.len() - 1]
+                .iter()
+                .map(|c| c.c)
+                .collect::<String>()
+        });
+        if let Some((start, by)) = start.zip(raw.as_deref().and_then(|raw| raw.strip_prefix("by ")))
+        {
+            deadline = Some(by.parse().map_err(|()| Error::InvalidDeadline {
+                task,
+                deadline: by.trim().to_string(),
+            })?);
+            chars = trim(&chars[..start]);
+        }
+    }
+
+    let mut effort = None;
+    if let Some(tilde) = chars.iter().rposition(|c| c.c == '~' && !c.literal) {
+        let digits = &chars[tilde + 1..];
+        let preceded_by_space = tilde > 0 && chars[tilde - 1].c.is_whitespace();
+        if preceded_by_space
+            && !digits.is_empty()
+            && digits.iter().all(|c| c.c.is_ascii_digit() && !c.literal)
+        {
+            effort = digits.iter().map(|c| c.c).collect::<String>().parse().ok();
+        }
+        if effort.is_some() {
+            chars = trim(&chars[..tilde]);
+        }
+    }
+
+    let item = chars.iter().map(|c| c.c).collect::<String>();
+    if item.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(TaskSpec {
+        multiplier,
+        amount,
+        item,
+        effort,
+        section: None,
+        reserved_for,
+        deadline,
+    }))
+}
+
+/// Parses a mention followed by a colon at the start of a task, such as `<@123>:`, returning who
+/// it reserves the task for and how many characters it takes up
+fn parse_reservation(chars: &[Char]) -> Option<(Reservation, usize)> {
+    let unquoted = |i: usize, c: char| chars.get(i).is_some_and(|ch| ch.c == c && !ch.literal);
+    if !unquoted(0, '<') || !unquoted(1, '@') {
+        return None;
+    }
+    let role = unquoted(2, '&');
+    // Older clients mention users by nickname as `<@!123>`
+    let start = if role || unquoted(2, '!') { 3 } else { 2 };
+    let end = start
+        + chars[start.min(chars.len())..]
+            .iter()
+            .take_while(|c| c.c.is_ascii_digit() && !c.literal)
+            .count();
+    if end == start || !unquoted(end, '>') {
+        return None;
+    }
+    let colon = end
+        + 1
+        + chars[end + 1..]
+            .iter()
+            .take_while(|c| c.c.is_whitespace() && !c.literal)
+            .count();
+    if !unquoted(colon, ':') {
+        return None;
+    }
+    let id = chars[start..end]
+        .iter()
+        .map(|c| c.c)
+        .collect::<String>()
+        .parse()
+        .ok()?;
+    let reservation = if role {
+        Reservation::Role(id)
+    } else {
+        Reservation::User(id)
+    };
+    Some((reservation, colon + 1))
+}
+
+/// Trims unquoted whitespace
+fn trim(chars: &[Char]) -> &[Char] {
+    let is_space = |c: &Char| c.c.is_whitespace() && !c.literal;
+    let start = chars
+        .iter()
+        .position(|c| !is_space(c))
+        .unwrap_or(chars.len());
+    let end = chars
+        .iter()
+        .rposition(|c| !is_space(c))
+        .map_or(start, |i| i + 1);
+    &chars[start..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use proptest::prelude::*;
+    use time::{
+        macros::{datetime, time},
+        Time,
+    };
+    use time_tz::timezones;
+
+    use super::{
+        expand, parse, split_groups, Deadline, Error, Reservation, SelectionError, TaskSelection,
+        TaskSpec, MAX_MULTIPLIER,
+    };
+    use crate::limits;
+
+    fn task(multiplier: usize, amount: Option<u32>, item: &str) -> TaskSpec {
+        TaskSpec {
+            multiplier,
+            amount,
+            item: item.to_string(),
+            effort: None,
+            section: None,
+            reserved_for: None,
+            deadline: None,
+        }
+    }
+
+    #[test]
+    fn parses_plain_tasks() {
+        assert_eq!(
+            parse("shirts; bmats ;;flatbed").unwrap(),
+            [
+                task(1, None, "shirts"),
+                task(1, None, "bmats"),
+                task(1, None, "flatbed")
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_multipliers_and_amounts() {
+        assert_eq!(
+            parse("{3x}shirts; {2x} 300 bmats; 40mm").unwrap(),
+            [
+                task(3, None, "shirts"),
+                task(2, Some(300), "bmats"),
+                task(1, None, "40mm")
+            ]
+        );
+        assert_eq!(
+            expand(&parse("{2x} 300 bmats; flatbed").unwrap())
+                .map(TaskSpec::text)
+                .collect::<Vec<_>>(),
+            ["300 bmats", "300 bmats", "flatbed"]
+        );
+    }
+
+    #[test]
+    fn parses_quotes_escapes_and_comments() {
+        assert_eq!(
+            parse(r##""fuel; diesel" # to the depot; \{2x} gun\#1; "#5 wrench""##).unwrap(),
+            [
+                task(1, None, "fuel; diesel"),
+                task(1, None, "{2x} gun#1"),
+                task(1, None, "#5 wrench")
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_groups_outside_of_quotes_and_escapes() {
+        assert_eq!(
+            split_groups(r#"shirts; bmats | "gun|1" ; flatbed|\|"#),
+            ["shirts; bmats ", r#" "gun|1" ; flatbed"#, r"\|"]
+        );
+        assert_eq!(
+            parse(split_groups(r#"shirts|"gun|1"; \|"#)[1]).unwrap(),
+            [task(1, None, "gun|1"), task(1, None, "|")]
+        );
+    }
+
+    #[test]
+    fn rejects_broken_syntax() {
+        assert_eq!(parse("\"shirts"), Err(Error::UnterminatedQuote { task: 1 }));
+        assert_eq!(
+            parse("shirts; bmats\\"),
+            Err(Error::DanglingEscape { task: 2 })
+        );
+        assert_eq!(
+            parse("{lots}shirts"),
+            Err(Error::InvalidMultiplier {
+                task: 1,
+                multiplier: "lots".to_string()
+            })
+        );
+        assert_eq!(
+            parse("{0x}shirts"),
+            Err(Error::MultiplierOutOfRange {
+                task: 1,
+                multiplier: 0
+            })
+        );
+        assert_eq!(parse(" ; # nothing here"), Err(Error::NoTasks));
+    }
+
+    #[test]
+    fn parses_effort() {
+        assert_eq!(
+            parse("300 bmats ~4; flatbed~2; tea \\~3; ~5").unwrap(),
+            [
+                TaskSpec {
+                    effort: Some(4),
+                    ..task(1, Some(300), "bmats")
+                },
+                task(1, None, "flatbed~2"),
+                task(1, None, "tea ~3"),
+                task(1, None, "~5"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_deadlines() {
+        assert_eq!(
+            parse("shirts {by 18:00}; 300 bmats ~2 {by 1h 30m}; \\{by 9:00}; tea {at 5}").unwrap(),
+            [
+                TaskSpec {
+                    deadline: Some(Deadline::At(time!(18:00))),
+                    ..task(1, None, "shirts")
+                },
+                TaskSpec {
+                    effort: Some(2),
+                    deadline: Some(Deadline::In(Duration::from_secs(90 * 60))),
+                    ..task(1, Some(300), "bmats")
+                },
+                task(1, None, "{by 9:00}"),
+                task(1, None, "tea {at 5}"),
+            ]
+        );
+        assert_eq!(
+            parse("shirts {by 25:00}"),
+            Err(Error::InvalidDeadline {
+                task: 1,
+                deadline: "25:00".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn deadlines_fall_on_the_next_time_that_comes_around() {
+        let now = datetime!(2026-10-17 17:00 UTC);
+        let stockholm = timezones::db::europe::STOCKHOLM;
+        assert_eq!(
+            Deadline::At(time!(18:00)).resolve(now, stockholm),
+            datetime!(2026-10-18 16:00 UTC)
+        );
+        assert_eq!(
+            Deadline::At(time!(20:00)).resolve(now, stockholm),
+            datetime!(2026-10-17 18:00 UTC)
+        );
+        assert_eq!(
+            Deadline::In(Duration::from_secs(2 * 60 * 60)).resolve(now, stockholm),
+            datetime!(2026-10-17 19:00 UTC)
+        );
+    }
+
+    #[test]
+    fn parses_sections() {
+        let sections = parse("flatbed; == Shirts ==; {2x} 40 shirts; ==  Ammo==; 9 7.62mm; ====; fuel; \\== not a section ==")
+            .unwrap()
+            .into_iter()
+            .map(|task| (task.item, task.section))
+            .collect::<Vec<_>>();
+        let section = |name: &str| Some(name.to_string());
+        assert_eq!(
+            sections,
+            [
+                ("flatbed".to_string(), None),
+                ("shirts".to_string(), section("Shirts")),
+                ("7.62mm".to_string(), section("Ammo")),
+                ("fuel".to_string(), None),
+                ("== not a section ==".to_string(), None),
+            ]
+        );
+        assert_eq!(parse("== Shirts =="), Err(Error::NoTasks));
+    }
+
+    #[test]
+    fn parses_reservations() {
+        assert_eq!(
+            parse("<@123>: flatbed; {2x} <@&456> : 40 shirts; <@!789>:bmats; <@123> fuel; \\<@1>: tea")
+                .unwrap(),
+            [
+                TaskSpec {
+                    reserved_for: Some(Reservation::User(123)),
+                    ..task(1, None, "flatbed")
+                },
+                TaskSpec {
+                    reserved_for: Some(Reservation::Role(456)),
+                    ..task(2, Some(40), "shirts")
+                },
+                TaskSpec {
+                    reserved_for: Some(Reservation::User(789)),
+                    ..task(1, None, "bmats")
+                },
+                task(1, None, "<@123> fuel"),
+                task(1, None, "<@1>: tea"),
+            ]
+        );
+        assert!(Reservation::User(1).allows(1, &[]));
+        assert!(!Reservation::User(1).allows(2, &[1]));
+        assert!(Reservation::Role(5).allows(2, &[4, 5]));
+        assert!(!Reservation::Role(5).allows(5, &[4]));
+    }
+
+    #[test]
+    fn moves_selected_tasks() {
+        let selection = "2 4".parse::<TaskSelection>().unwrap();
+        assert_eq!(selection.move_to(&[1, 2, 3, 4, 5], 1), [2, 4, 1, 3, 5]);
+        assert_eq!(selection.move_to(&[1, 2, 3, 4, 5], 3), [1, 3, 2, 4, 5]);
+        assert_eq!(selection.move_to(&[1, 2, 3, 4, 5], 99), [1, 3, 5, 2, 4]);
+        assert_eq!(selection.move_to(&[1, 3, 4], 0), [4, 1, 3]);
+    }
+
+    #[test]
+    fn parses_selections() {
+        assert_eq!(
+            "1, 3-5;8 8".parse::<TaskSelection>().unwrap().0,
+            [1, 3, 4, 5, 8].into()
+        );
+        assert_eq!(
+            "5-3".parse::<TaskSelection>(),
+            Err(SelectionError::BackwardsRange { start: 5, end: 3 })
+        );
+        assert_eq!(
+            "1,two".parse::<TaskSelection>(),
+            Err(SelectionError::InvalidPart {
+                part: "two".to_string()
+            })
+        );
+        assert_eq!(
+            " , ".parse::<TaskSelection>(),
+            Err(SelectionError::NoneSelected)
+        );
+    }
+
+    #[test]
+    fn refuses_selections_beyond_any_request() {
+        assert_eq!(
+            "1-4294967295".parse::<TaskSelection>(),
+            Err(SelectionError::TooLarge { task: 4294967295 })
+        );
+        assert_eq!(
+            "1-99999999999999999999".parse::<TaskSelection>(),
+            Err(SelectionError::InvalidPart {
+                part: "1-99999999999999999999".to_string()
+            })
+        );
+        assert_eq!(
+            format!("1-{}", limits::REQUEST_TASKS)
+                .parse::<TaskSelection>()
+                .unwrap()
+                .0
+                .len(),
+            limits::REQUEST_TASKS
+        );
+    }
+
+    #[test]
+    fn writes_selections_as_ranges() {
+        let selection = "8, 1 3-5;4 6".parse::<TaskSelection>().unwrap();
+        assert_eq!(selection.to_string(), "1 3-6 8");
+        assert_eq!(
+            selection.to_string().parse::<TaskSelection>(),
+            Ok(selection)
+        );
+    }
+
+    fn task_spec() -> impl Strategy<Value = TaskSpec> {
+        (
+            1..=MAX_MULTIPLIER,
+            proptest::option::of(any::<u32>()),
+            "[^\\s]([^\n]*[^\\s])?",
+            proptest::option::of(any::<u32>()),
+            proptest::option::of(prop_oneof![
+                any::<u64>().prop_map(Reservation::User),
+                any::<u64>().prop_map(Reservation::Role),
+            ]),
+            proptest::option::of(prop_oneof![
+                (0..24_u8, 0..60_u8).prop_map(|(hour, minute)| Deadline::At(
+                    Time::from_hms(hour, minute, 0).unwrap()
+                )),
+                (1..100_000_u64)
+                    .prop_map(|minutes| Deadline::In(Duration::from_secs(minutes * 60))),
+            ]),
+        )
+            .prop_map(
+                |(multiplier, amount, item, effort, reserved_for, deadline)| TaskSpec {
+                    multiplier,
+                    amount,
+                    item,
+                    effort,
+                    section: None,
+                    reserved_for,
+                    deadline,
+                },
+            )
+    }
+
+    proptest! {
+        #[test]
+        fn parse_never_panics(input in any::<String>()) {
+            let _ = parse(&input);
+        }
+
+        #[test]
+        fn display_roundtrips(tasks in proptest::collection::vec(task_spec(), 1..10)) {
+            let input = tasks.iter().map(ToString::to_string).collect::<Vec<_>>().join(";");
+            prop_assert_eq!(parse(&input).unwrap(), tasks);
+        }
+
+        #[test]
+        fn display_keeps_groups_together(tasks in proptest::collection::vec(task_spec(), 1..10)) {
+            let input = tasks.iter().map(ToString::to_string).collect::<Vec<_>>().join(";");
+            prop_assert_eq!(split_groups(&input), [input.as_str()]);
+        }
+
+        #[test]
+        fn format_roundtrips(
+            tasks in proptest::collection::vec(
+                (task_spec(), proptest::option::of("[^\\s]([^\n]*[^\\s])?")),
+                1..10,
+            )
+        ) {
+            let tasks = tasks
+                .into_iter()
+                .map(|(task, section)| TaskSpec { section, ..task })
+                .collect::<Vec<_>>();
+            prop_assert_eq!(parse(&format(&tasks)).unwrap(), tasks);
+        }
+
+        #[test]
+        fn expand_respects_multipliers(tasks in proptest::collection::vec(task_spec(), 1..10)) {
+            prop_assert_eq!(
+                expand(&tasks).count(),
+                tasks.iter().map(|t| t.multiplier).sum::<usize>()
+            );
+        }
+    }
+}
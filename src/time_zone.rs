@@ -0,0 +1,147 @@
+//! Time zone preferences, for the places where times are written out as plain text rather than
+//! as Discord timestamps (which are already rendered in the reader's own time zone)
+//!
+//! A user's own preference wins over their guild's, and everything else falls back to UTC.
+
+use entity::{guild_setting, user};
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait};
+use serenity::model::id::GuildId;
+use snafu::{OptionExt, Snafu};
+use time::OffsetDateTime;
+use time_tz::{timezones, Offset, OffsetDateTimeExt, TimeZone, Tz};
+
+use crate::discord_ids::ToDiscordId;
+
+/// Common abbreviations that are not time zones in their own right in the tz database
+///
+/// Abbreviations that are (such as CET and EST) are resolved by the tz database instead.
+const ABBREVIATIONS: &[(&str, &str)] = &[
+    ("BST", "Europe/London"),
+    ("IST", "Europe/Dublin"),
+    ("WEST", "WET"),
+    ("CEST", "CET"),
+    ("EEST", "EET"),
+    ("MSK", "Europe/Moscow"),
+    ("EDT", "America/New_York"),
+    ("CST", "America/Chicago"),
+    ("CDT", "America/Chicago"),
+    ("MDT", "America/Denver"),
+    ("PST", "America/Los_Angeles"),
+    ("PDT", "America/Los_Angeles"),
+    ("AEST", "Australia/Sydney"),
+    ("AEDT", "Australia/Sydney"),
+];
+
+#[derive(Debug, Snafu)]
+#[snafu(display(
+    "{name:?} is not a known time zone, use a name like Europe/Stockholm or an abbreviation like CET"
+))]
+pub struct UnknownTimeZone {
+    name: String,
+}
+
+/// The time zone used when neither the user nor the guild has chosen one
+pub fn default() -> &'static Tz {
+    timezones::db::UTC
+}
+
+/// Looks up a time zone by its tz database name (such as `Europe/Stockholm`) or a common abbreviation
+///
+/// Matching is case-insensitive.
+pub fn parse(name: &str) -> Result<&'static Tz, UnknownTimeZone> {
+    let name = name.trim();
+    let name = ABBREVIATIONS
+        .iter()
+        .find(|(abbreviation, _)| abbreviation.eq_ignore_ascii_case(name))
+        .map_or(name, |(_, zone)| zone);
+    timezones::get_by_name(name)
+        .or_else(|| timezones::iter().find(|tz| tz.name().eq_ignore_ascii_case(name)))
+        .context(UnknownTimeZoneSnafu { name })
+}
+
+/// Parses a time zone preference, where `default` clears it in favour of the next fallback
+pub fn parse_preference(value: &str) -> Result<Option<&'static Tz>, UnknownTimeZone> {
+    if value.trim().eq_ignore_ascii_case("default") {
+        Ok(None)
+    } else {
+        parse(value).map(Some)
+    }
+}
+
+/// Finds the time zone that should be used when writing out times for `user`
+///
+/// Stored zones that are no longer known (for example, after a tz database update) are skipped.
+pub async fn resolve(
+    db: &DatabaseConnection,
+    user: &user::Model,
+    guild: Option<GuildId>,
+) -> Result<&'static Tz, DbErr> {
+    if let Some(tz) = user.time_zone.as_deref().and_then(|tz| parse(tz).ok()) {
+        return Ok(tz);
+    }
+    Ok(guild_time_zone(db, guild).await?.unwrap_or_else(default))
+}
+
+/// Finds the time zone that the guild has chosen, if any
+pub async fn guild_time_zone(
+    db: &DatabaseConnection,
+    guild: Option<GuildId>,
+) -> Result<Option<&'static Tz>, DbErr> {
+    let Some(guild) = guild else {
+        return Ok(None);
+    };
+    Ok(guild_setting::Entity::find_by_id(guild.db_id())
+        .one(db)
+        .await?
+        .and_then(|settings| settings.time_zone)
+        .and_then(|tz| parse(&tz).ok()))
+}
+
+/// Writes out `time` as local time in `tz`, such as `2024-07-15 19:00 CEST`
+pub fn format(time: OffsetDateTime, tz: &Tz) -> String {
+    let local = time.to_timezone(tz);
+    format!(
+        "{} {:02}:{:02} {}",
+        local.date(),
+        local.hour(),
+        local.minute(),
+        tz.get_offset_utc(&time).name()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use time::OffsetDateTime;
+    use time_tz::TimeZone;
+
+    use super::{format, parse};
+
+    #[test]
+    fn parse_names_and_abbreviations() {
+        assert_eq!(
+            parse("Europe/Stockholm").unwrap().name(),
+            "Europe/Stockholm"
+        );
+        assert_eq!(
+            parse(" europe/stockholm ").unwrap().name(),
+            "Europe/Stockholm"
+        );
+        assert_eq!(parse("CET").unwrap().name(), "CET");
+        assert_eq!(parse("pdt").unwrap().name(), "America/Los_Angeles");
+        assert!(parse("Mars/Olympus_Mons").is_err());
+    }
+
+    #[test]
+    fn format_uses_local_offset() {
+        // 2024-07-15 and 2024-01-15, 17:00 UTC
+        let tz = parse("Europe/Stockholm").unwrap();
+        assert_eq!(
+            format(OffsetDateTime::from_unix_timestamp(1721062800).unwrap(), tz),
+            "2024-07-15 19:00 CEST"
+        );
+        assert_eq!(
+            format(OffsetDateTime::from_unix_timestamp(1705338000).unwrap(), tz),
+            "2024-01-15 18:00 CET"
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! The history of a request, as shown by the *Show timeline* message command
+//!
+//! The timeline is pieced together from what the bot records anyway: the request's own timestamps,
+//! its tasks' claims, completions, and removals, extensions, and moderators' task overrides. Only
+//! the latest claim of each task is recorded, so claims that were given up don't show up.
+//!
+//! What changed in the request's messages is shown for each of their recorded renders, see
+//! [`crate::render_history`].
+
+use std::collections::HashMap;
+
+use entity::{request, request_extension, request_render_history, task, task_override, user};
+use sea_orm::{
+    prelude::Uuid, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, ModelTrait, QueryFilter,
+    QueryOrder,
+};
+use time::{Duration, OffsetDateTime};
+
+use crate::{limits, render_history, stats::format_time_to_completion};
+
+/// Something that happened to a request
+#[derive(Debug, PartialEq, Eq)]
+pub struct Event {
+    pub at: OffsetDateTime,
+    pub description: String,
+}
+
+/// Gathers everything that has happened to `request`, in no particular order
+pub async fn events(
+    db: &DatabaseConnection,
+    request: &request::Model,
+) -> Result<Vec<Event>, DbErr> {
+    let tasks = request.find_related(task::Entity).all(db).await?;
+    let overrides = task_override::Entity::find()
+        .filter(task_override::Column::Task.is_in(tasks.iter().map(|task| task.id)))
+        .all(db)
+        .await?;
+    let extensions = request
+        .find_related(request_extension::Entity)
+        .all(db)
+        .await?;
+    let user_ids = std::iter::once(request.created_by)
+        .chain(tasks.iter().flat_map(|task| {
+            [task.assigned_to, task.completed_by, task.removed_by]
+                .into_iter()
+                .flatten()
+        }))
+        .chain(
+            overrides
+                .iter()
+                .flat_map(|o| [Some(o.overridden_by), o.assignee])
+                .flatten(),
+        )
+        .chain(extensions.iter().map(|extension| extension.extended_by));
+    let users = user::Entity::find()
+        .filter(user::Column::Id.is_in(user_ids))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|user| (user.id, user.discord_user_id))
+        .collect::<HashMap<_, _>>();
+    let mention = |user: Uuid| match users.get(&user) {
+        Some(discord_user) => format!("<@{discord_user}>"),
+        None => "an unknown user".to_string(),
+    };
+
+    let mut events = vec![Event {
+        at: request.created_at,
+        description: format!("Made by {}", mention(request.created_by)),
+    }];
+    for task in &tasks {
+        let name = limits::truncate(&task.task, 100);
+        // Tasks that were completed without being claimed first have no claim
+        if let (Some(started_at), Some(assignee)) = (task.started_at, task.assigned_to) {
+            events.push(Event {
+                at: started_at,
+                description: format!("**{name}** claimed by {}", mention(assignee)),
+            });
+        }
+        if let Some(completed_at) = task.completed_at {
+            let by = task.completed_by.or(task.assigned_to);
+            let mut description = format!(
+                "**{name}** completed by {}",
+                by.map_or("someone".to_string(), mention)
+            );
+            if let Some(started_at) = task.started_at.filter(|&started| started < completed_at) {
+                description += &format!(
+                    ", {} after being claimed",
+                    format_time_to_completion(completed_at - started_at)
+                );
+            }
+            events.push(Event {
+                at: completed_at,
+                description,
+            });
+        }
+        if let Some(removed_at) = task.removed_at {
+            events.push(Event {
+                at: removed_at,
+                description: format!(
+                    "**{name}** removed by {}",
+                    task.removed_by.map_or("someone".to_string(), mention)
+                ),
+            });
+        }
+    }
+    for task_override in &overrides {
+        let Some(task) = tasks.iter().find(|task| task.id == task_override.task) else {
+            continue;
+        };
+        let mut description = format!(
+            "**{}** set to {} by {}",
+            limits::truncate(&task.task, 100),
+            task_override.state.to_lowercase(),
+            mention(task_override.overridden_by)
+        );
+        if let Some(reason) = &task_override.reason {
+            description += &format!(": {reason}");
+        }
+        events.push(Event {
+            at: task_override.created_at,
+            description,
+        });
+    }
+    for extension in &extensions {
+        events.push(Event {
+            at: extension.extended_at,
+            description: format!(
+                "Extended by {} until <t:{}:f>",
+                mention(extension.extended_by),
+                extension.expires_on.unix_timestamp()
+            ),
+        });
+    }
+    let renders = request
+        .find_related(request_render_history::Entity)
+        .order_by_asc(request_render_history::Column::Page)
+        .order_by_asc(request_render_history::Column::RenderedAt)
+        .all(db)
+        .await?;
+    for (before, after) in renders.iter().zip(renders.iter().skip(1)) {
+        if before.page != after.page {
+            continue;
+        }
+        if let Some(description) =
+            render_history::describe(after.page, &before.snapshot, &after.snapshot)
+        {
+            events.push(Event {
+                at: after.rendered_at,
+                description,
+            });
+        }
+    }
+    if let Some(archived_on) = request.archived_on {
+        events.push(Event {
+            at: archived_on,
+            description: "Archived".to_string(),
+        });
+    }
+    Ok(events)
+}
+
+/// Lists `events` in the order that they happened, with how long after `created_at` each one was
+pub fn render(created_at: OffsetDateTime, mut events: Vec<Event>) -> String {
+    events.sort_by_key(|event| event.at);
+    let timeline = events
+        .iter()
+        .map(|event| {
+            let since = event.at - created_at;
+            let since = if since < Duration::minutes(1) {
+                String::new()
+            } else {
+                format!(" (+{})", format_time_to_completion(since))
+            };
+            format!(
+                "<t:{}:f>{since}: {}",
+                event.at.unix_timestamp(),
+                event.description
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    limits::truncate(&timeline, limits::EMBED_DESCRIPTION)
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Duration, OffsetDateTime};
+
+    use super::{render, Event};
+
+    fn event(minutes: i64, description: &str) -> Event {
+        Event {
+            at: OffsetDateTime::UNIX_EPOCH + Duration::minutes(minutes),
+            description: description.to_string(),
+        }
+    }
+
+    #[test]
+    fn events_are_listed_in_order_with_time_since_creation() {
+        let timeline = render(
+            OffsetDateTime::UNIX_EPOCH,
+            vec![
+                event(90, "**bmats** completed by <@2>, 1h after being claimed"),
+                event(0, "Made by <@1>"),
+                event(30, "**bmats** claimed by <@2>"),
+            ],
+        );
+        assert_eq!(
+            timeline,
+            "<t:0:f>: Made by <@1>\n\
+            <t:1800:f> (+30m): **bmats** claimed by <@2>\n\
+            <t:5400:f> (+1h 30m): **bmats** completed by <@2>, 1h after being claimed"
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! One-line summaries of what a request asks for, such as `300 bmats, 50 shirts, 2 flatbeds`
+//!
+//! Tasks that start with an amount (`300 bmats`) count for that much of the item, and every other
+//! task counts for one of whatever it says. Tasks of the same item are added up, going by the names
+//! of the catalog items and materials where they are known (see [`item_emojis::find`]), so that
+//! `shirts` and `Soldier Supplies` are counted together. The items are listed in the order that
+//! they first appear in, as they were first written.
+
+use crate::item_emojis;
+
+/// The number of items that are listed before the rest are only counted
+const MAX_ITEMS: usize = 4;
+
+/// An item of the summary
+struct Entry {
+    /// How the item is grouped, such as its catalog name
+    key: String,
+    /// How the item was first written
+    name: String,
+    amount: u64,
+    /// Whether any of its tasks gave an amount, otherwise the amount is a number of tasks
+    counted: bool,
+}
+
+/// Splits a task into its amount (if it starts with one) and the rest
+fn amount_and_item(task: &str) -> (Option<u32>, &str) {
+    let task = task.trim();
+    match task.split_once(char::is_whitespace) {
+        Some((amount, item)) => match amount.parse() {
+            Ok(amount) => (Some(amount), item.trim()),
+            Err(_) => (None, task),
+        },
+        None => (None, task),
+    }
+}
+
+/// Summarizes `tasks`, or returns `None` if there are none
+pub fn summarize<'a>(tasks: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    let mut entries = Vec::<Entry>::new();
+    for task in tasks {
+        let (amount, item) = amount_and_item(task);
+        if item.is_empty() {
+            continue;
+        }
+        let key = item_emojis::find(item).map_or_else(|| item.to_lowercase(), str::to_string);
+        let entry = match entries.iter_mut().position(|entry| entry.key == key) {
+            Some(i) => &mut entries[i],
+            None => {
+                entries.push(Entry {
+                    key,
+                    name: item.to_string(),
+                    amount: 0,
+                    counted: false,
+                });
+                entries.last_mut().unwrap()
+            }
+        };
+        entry.amount += u64::from(amount.unwrap_or(1));
+        entry.counted |= amount.is_some();
+    }
+    if entries.is_empty() {
+        return None;
+    }
+    let mut summary = entries
+        .iter()
+        .take(MAX_ITEMS)
+        .map(|entry| {
+            // "2 flatbeds" reads better than "2 flatbed", but "300 bmats" was written by the requester
+            let plural = !entry.counted && entry.amount > 1 && !entry.name.ends_with('s');
+            format!(
+                "{} {}{}",
+                entry.amount,
+                entry.name,
+                if plural { "s" } else { "" }
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    if entries.len() > MAX_ITEMS {
+        summary += &format!(" and {} more", entries.len() - MAX_ITEMS);
+    }
+    Some(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::summarize;
+
+    #[test]
+    fn tasks_of_the_same_item_are_added_up() {
+        assert_eq!(
+            summarize([
+                "300 bmats",
+                "40 shirts",
+                "flatbed",
+                "10 Soldier Supplies",
+                "Flatbed"
+            ])
+            .as_deref(),
+            Some("300 bmats, 50 shirts, 2 flatbeds")
+        );
+    }
+
+    #[test]
+    fn long_summaries_only_count_the_rest() {
+        assert_eq!(
+            summarize(["bmats", "rmats", "emats", "hemats", "shirts", "flatbed"]).as_deref(),
+            Some("1 bmats, 1 rmats, 1 emats, 1 hemats and 2 more")
+        );
+    }
+
+    #[test]
+    fn requests_without_tasks_have_no_summary() {
+        assert_eq!(summarize([]), None);
+        assert_eq!(summarize([" "]), None);
+    }
+}
@@ -0,0 +1,145 @@
+//! Snapshots of users' Discord names and avatars, so that they can be shown without asking Discord
+//! for every user
+//!
+//! A user's snapshot is refreshed whenever they interact with the bot, and [`run`] slowly refreshes
+//! the snapshots of users who haven't been seen in a while.
+
+use std::time::Duration;
+
+use entity::user;
+use sea_orm::{
+    sea_query::OnConflict, ActiveModelTrait, ActiveValue::Set, ColumnTrait, Condition,
+    DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+};
+use serenity::model::{id::UserId, user::User};
+use time::OffsetDateTime;
+
+use crate::{
+    backoff::{self, Backoff},
+    discord_api::DiscordApi,
+    discord_ids::{FromDiscordId, ToDiscordId},
+    forget, leader,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// How old a snapshot can get before it is refreshed in the background
+const MAX_AGE: time::Duration = time::Duration::days(7);
+/// Number of users refreshed per turn, to stay well clear of Discord's rate limits
+const BATCH_SIZE: u64 = 50;
+
+/// What a user looks like on Discord
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    pub display_name: String,
+    /// [`None`] if the user uses Discord's default avatar
+    pub avatar_url: Option<String>,
+}
+
+impl From<&User> for Profile {
+    fn from(user: &User) -> Self {
+        Self {
+            display_name: user
+                .global_name
+                .clone()
+                .unwrap_or_else(|| user.name.clone()),
+            avatar_url: user.avatar_url(),
+        }
+    }
+}
+
+/// What to call `user` where Discord can't render a mention for us, such as on the web or in exports
+///
+/// Falls back to their Discord ID if their profile was never snapshotted.
+pub fn display_name(user: &user::Model) -> String {
+    if user.discord_user_id == forget::TOMBSTONE.db_id() {
+        return "deleted user".to_string();
+    }
+    user.display_name
+        .clone()
+        .unwrap_or_else(|| user.discord_user_id.to_string())
+}
+
+/// Records `profile` as the current look of `discord_user`, creating the user if they are new
+pub async fn refresh(
+    db: &DatabaseConnection,
+    discord_user: UserId,
+    profile: &Profile,
+) -> Result<(), DbErr> {
+    user::Entity::insert(user::ActiveModel {
+        discord_user_id: Set(discord_user.db_id()),
+        display_name: Set(Some(profile.display_name.clone())),
+        avatar_url: Set(profile.avatar_url.clone()),
+        profile_synced_at: Set(Some(OffsetDateTime::now_utc())),
+        ..Default::default()
+    })
+    .on_conflict(
+        OnConflict::column(user::Column::DiscordUserId)
+            .update_columns([
+                user::Column::DisplayName,
+                user::Column::AvatarUrl,
+                user::Column::ProfileSyncedAt,
+            ])
+            .to_owned(),
+    )
+    .exec_without_returning(db)
+    .await?;
+    Ok(())
+}
+
+pub async fn run(db: &DatabaseConnection, discord: &dyn DiscordApi) {
+    let mut backoff = Backoff::new(POLL_INTERVAL, backoff::MAX_RETRY_DELAY);
+    loop {
+        let delay = match run_turn(db, discord).await {
+            Ok(()) => {
+                backoff.succeeded();
+                POLL_INTERVAL
+            }
+            Err(err) => {
+                let delay = backoff.failed();
+                tracing::error!(error = &err as &dyn std::error::Error, retry_in = ?delay, "failed to find stale user profiles, retrying...");
+                delay
+            }
+        };
+        tokio::time::sleep(delay).await;
+    }
+}
+
+pub(crate) async fn run_turn(
+    db: &DatabaseConnection,
+    discord: &dyn DiscordApi,
+) -> Result<(), DbErr> {
+    let Some(leadership) = leader::try_lead(db, leader::Controller::UserProfile, 0).await? else {
+        return Ok(());
+    };
+    let stale_users = user::Entity::find()
+        .filter(user::Column::DiscordUserId.ne(forget::TOMBSTONE.db_id()))
+        .filter(
+            Condition::any()
+                .add(user::Column::ProfileSyncedAt.is_null())
+                .add(user::Column::ProfileSyncedAt.lt(OffsetDateTime::now_utc() - MAX_AGE)),
+        )
+        // Postgres sorts nulls last, but users that were never synced are the stalest of all
+        .order_by_desc(user::Column::ProfileSyncedAt.is_null())
+        .order_by_asc(user::Column::ProfileSyncedAt)
+        .limit(BATCH_SIZE)
+        .all(db)
+        .await?;
+    for stale in stale_users {
+        let discord_user = stale.discord_user_id.discord();
+        match discord.get_user_profile(discord_user).await {
+            Ok(profile) => refresh(db, discord_user, &profile).await?,
+            Err(err) => {
+                tracing::warn!(error = &err as &dyn std::error::Error, user.id = %stale.id, "failed to fetch user profile, keeping the old one");
+                // Don't retry deleted users on every turn
+                user::ActiveModel {
+                    id: Set(stale.id),
+                    profile_synced_at: Set(Some(OffsetDateTime::now_utc())),
+                    ..Default::default()
+                }
+                .update(db)
+                .await?;
+            }
+        }
+    }
+    leadership.release().await
+}
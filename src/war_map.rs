@@ -0,0 +1,517 @@
+//! Delivery points on the Foxhole map, and the map snippets that show them
+//!
+//! A location is a hex plus a grid reference within it, such as `G7k3`. The hexes of the current
+//! war and the names of their towns come from the War API. The War API has no images, so the map
+//! of each hex is shipped alongside the bot as `{dir}/{hex}.png`. Snippets are cut out of it
+//! around the grid reference and marked when they are requested.
+//!
+//! The War API also tells which faction holds each town hall, which [`crate::front_controller`]
+//! uses to notice hexes falling to the enemy, and which war is being fought, which
+//! [`crate::season_controller`] uses to start a new season with each war.
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use plotters::{backend::BitMapBackendError, prelude::*};
+use serde::Deserialize;
+use snafu::{OptionExt, ResultExt, Snafu};
+
+/// Where the War API of the live shard is
+pub const LIVE_URL: &str = "https://war-service-live.foxholeservices.com/api";
+
+/// The hexes and towns only change between wars, so they are kept for a while
+const CACHE_LIFETIME: Duration = Duration::from_secs(60 * 60);
+
+/// The columns of a hex's grid, from west to east
+const COLUMNS: &[u8] = b"ABCDEFGHIJKLMNOPQ";
+/// The rows of a hex's grid, from north to south
+const ROWS: u8 = 15;
+/// How many grid cells are shown on each side of the marked one
+const SNIPPET_RADIUS: u32 = 2;
+/// The War API's icons of the tiers of town halls
+const TOWN_HALL_ICONS: &[u32] = &[56, 57, 58];
+const FONT: &str = "sans-serif";
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum Error {
+    #[snafu(display("failed to fetch {what} from the War API"))]
+    Fetch {
+        source: reqwest::Error,
+        what: String,
+    },
+    #[snafu(display("failed to read the map of {hex} from {}", path.display()))]
+    ReadMap {
+        source: std::io::Error,
+        hex: String,
+        path: PathBuf,
+    },
+    #[snafu(display("failed to decode the map of {hex}"))]
+    DecodeMap {
+        source: png::DecodingError,
+        hex: String,
+    },
+    #[snafu(display("the map of {hex} is neither RGB nor RGBA"))]
+    UnsupportedMap { hex: String },
+    #[snafu(display("failed to draw map snippet"))]
+    Draw {
+        source: DrawingAreaErrorKind<BitMapBackendError>,
+    },
+    #[snafu(display("failed to encode map snippet as PNG"))]
+    Encode { source: png::EncodingError },
+}
+
+/// A place within a hex, as players call it out: a grid cell, optionally narrowed down to one of
+/// its keypad subcells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridRef {
+    /// From 0 (`A`) to 16 (`Q`)
+    column: u8,
+    /// From 0 (`1`) to 14 (`15`)
+    row: u8,
+    /// From 1 to 9, laid out like a numpad (`7` is the top left)
+    keypad: Option<u8>,
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(display("{grid:?} is not a grid reference, such as G7 or G7k3"))]
+pub struct ParseGridRefError {
+    grid: String,
+}
+
+impl FromStr for GridRef {
+    type Err = ParseGridRefError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseGridRefSnafu { grid: s };
+        let grid = s.trim().to_ascii_uppercase().replace(' ', "");
+        let (cell, keypad) = match grid.split_once('K') {
+            Some((cell, keypad)) => (cell, Some(keypad)),
+            None => (grid.as_str(), None),
+        };
+        let mut chars = cell.chars();
+        let column = chars
+            .next()
+            .and_then(|column| COLUMNS.iter().position(|&c| char::from(c) == column))
+            .with_context(error)?;
+        let row = chars
+            .as_str()
+            .parse::<u8>()
+            .ok()
+            .filter(|row| (1..=ROWS).contains(row))
+            .with_context(error)?;
+        let keypad = match keypad {
+            Some(keypad) => Some(
+                keypad
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|keypad| (1..=9).contains(keypad))
+                    .with_context(error)?,
+            ),
+            None => None,
+        };
+        Ok(Self {
+            column: column as u8,
+            row: row - 1,
+            keypad,
+        })
+    }
+}
+
+impl Display for GridRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            char::from(COLUMNS[usize::from(self.column)]),
+            self.row + 1
+        )?;
+        if let Some(keypad) = self.keypad {
+            write!(f, "k{keypad}")?;
+        }
+        Ok(())
+    }
+}
+
+impl GridRef {
+    /// The middle of the place, as a fraction of the hex map's width and height
+    pub fn position(&self) -> (f64, f64) {
+        let (column, row) = match self.keypad {
+            Some(keypad) => {
+                let keypad = f64::from(keypad - 1);
+                // The bottom row of the keypad is 1-3
+                (
+                    (keypad % 3.0 + 0.5) / 3.0,
+                    (2.0 - (keypad / 3.0).floor() + 0.5) / 3.0,
+                )
+            }
+            None => (0.5, 0.5),
+        };
+        (
+            (f64::from(self.column) + column) / COLUMNS.len() as f64,
+            (f64::from(self.row) + row) / f64::from(ROWS),
+        )
+    }
+}
+
+/// How a hex is written for people, such as `Dead Lands` for `DeadLandsHex`
+pub fn hex_name(hex: &str) -> String {
+    let hex = hex.strip_suffix("Hex").unwrap_or(hex);
+    let mut name = String::new();
+    for (i, c) in hex.char_indices() {
+        if i > 0 && c.is_ascii_uppercase() {
+            name.push(' ');
+        }
+        name.push(c);
+    }
+    name
+}
+
+/// One of the two sides of the war
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, strum::AsRefStr, strum::EnumIter, strum::EnumString,
+)]
+pub enum Team {
+    Wardens,
+    Colonials,
+}
+
+impl Team {
+    /// The team as the War API calls it, [`None`] for nobody (`NONE`)
+    fn from_api(team: &str) -> Option<Self> {
+        match team {
+            "WARDENS" => Some(Self::Wardens),
+            "COLONIALS" => Some(Self::Colonials),
+            _ => None,
+        }
+    }
+}
+
+/// Something on the map of a hex that belongs to a team, such as a town hall
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct MapItem {
+    #[serde(rename = "teamId")]
+    pub team: String,
+    #[serde(rename = "iconType")]
+    pub icon: u32,
+}
+
+#[derive(Deserialize)]
+struct DynamicMap {
+    #[serde(rename = "mapItems")]
+    items: Vec<MapItem>,
+}
+
+/// The team that holds the hex that `items` are on, which is whoever holds the most town halls
+///
+/// Nobody holds a hex where the teams hold as many town halls as each other.
+pub fn holder(items: &[MapItem]) -> Option<Team> {
+    let held = |team: Team| {
+        items
+            .iter()
+            .filter(|item| TOWN_HALL_ICONS.contains(&item.icon))
+            .filter(|item| Team::from_api(&item.team) == Some(team))
+            .count()
+    };
+    let (wardens, colonials) = (held(Team::Wardens), held(Team::Colonials));
+    match wardens.cmp(&colonials) {
+        std::cmp::Ordering::Greater => Some(Team::Wardens),
+        std::cmp::Ordering::Less => Some(Team::Colonials),
+        std::cmp::Ordering::Equal => None,
+    }
+}
+
+/// A town (or other named place) on the map of a hex
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Label {
+    pub text: String,
+    /// As a fraction of the hex map's width
+    pub x: f64,
+    /// As a fraction of the hex map's height
+    pub y: f64,
+    #[serde(rename = "mapMarkerType")]
+    pub marker_type: String,
+}
+
+#[derive(Deserialize)]
+struct StaticMap {
+    #[serde(rename = "mapTextItems")]
+    text_items: Vec<Label>,
+}
+
+#[derive(Deserialize)]
+struct War {
+    #[serde(rename = "warNumber")]
+    number: i32,
+}
+
+/// A client of the War API, which remembers what it was told for [`CACHE_LIFETIME`]
+pub struct WarApi {
+    client: reqwest::Client,
+    url: String,
+    hexes: Mutex<Option<(Instant, Vec<String>)>>,
+    labels: Mutex<HashMap<String, (Instant, Vec<Label>)>>,
+}
+
+impl WarApi {
+    pub fn new(url: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.trim_end_matches('/').to_string(),
+            hexes: Mutex::default(),
+            labels: Mutex::default(),
+        }
+    }
+
+    /// The hexes that make up the map of the current war, such as `DeadLandsHex`
+    pub async fn hexes(&self) -> Result<Vec<String>, Error> {
+        if let Some((fetched_at, hexes)) = &*self.hexes.lock().unwrap() {
+            if fetched_at.elapsed() < CACHE_LIFETIME {
+                return Ok(hexes.clone());
+            }
+        }
+        let hexes = self
+            .get::<Vec<String>>("/worldconquest/maps", "the hexes of the war")
+            .await?;
+        *self.hexes.lock().unwrap() = Some((Instant::now(), hexes.clone()));
+        Ok(hexes)
+    }
+
+    /// The major places of `hex`, which are worth naming on a map snippet
+    pub async fn labels(&self, hex: &str) -> Result<Vec<Label>, Error> {
+        if let Some((fetched_at, labels)) = self.labels.lock().unwrap().get(hex) {
+            if fetched_at.elapsed() < CACHE_LIFETIME {
+                return Ok(labels.clone());
+            }
+        }
+        let labels = self
+            .get::<StaticMap>(
+                &format!("/worldconquest/maps/{hex}/static"),
+                &format!("the towns of {hex}"),
+            )
+            .await?
+            .text_items
+            .into_iter()
+            .filter(|label| label.marker_type == "Major")
+            .collect::<Vec<_>>();
+        self.labels
+            .lock()
+            .unwrap()
+            .insert(hex.to_string(), (Instant::now(), labels.clone()));
+        Ok(labels)
+    }
+
+    /// The team that holds `hex` right now, see [`holder`]
+    ///
+    /// This changes as the war goes on, so it isn't cached.
+    pub async fn holder(&self, hex: &str) -> Result<Option<Team>, Error> {
+        let map = self
+            .get::<DynamicMap>(
+                &format!("/worldconquest/maps/{hex}/dynamic/public"),
+                &format!("who holds {hex}"),
+            )
+            .await?;
+        Ok(holder(&map.items))
+    }
+
+    /// The number of the current war, which goes up when a new war starts
+    ///
+    /// This is how a new war is noticed, so it isn't cached.
+    pub async fn war_number(&self) -> Result<i32, Error> {
+        Ok(self
+            .get::<War>("/worldconquest/war", "the current war")
+            .await?
+            .number)
+    }
+
+    async fn get<T: for<'de> Deserialize<'de>>(&self, path: &str, what: &str) -> Result<T, Error> {
+        self.client
+            .get(format!("{}{path}", self.url))
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .context(error::FetchSnafu { what })?
+            .json()
+            .await
+            .context(error::FetchSnafu { what })
+    }
+}
+
+/// Reads the map of `hex` from `{dir}/{hex}.png`
+pub fn read_map(dir: &Path, hex: &str) -> Result<Vec<u8>, Error> {
+    let path = dir.join(format!("{hex}.png"));
+    std::fs::read(&path).context(error::ReadMapSnafu { hex, path })
+}
+
+/// Cuts the area around `grid` out of the `map` of `hex` as a PNG image, with the grid drawn over
+/// it, the place marked, and the `labels` that fall inside of it named
+pub fn render_snippet(
+    hex: &str,
+    map: &[u8],
+    grid: GridRef,
+    labels: &[Label],
+) -> Result<Vec<u8>, Error> {
+    let mut decoder = png::Decoder::new(map);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().context(error::DecodeMapSnafu { hex })?;
+    let mut map = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut map)
+        .context(error::DecodeMapSnafu { hex })?;
+    let channels = match info.color_type {
+        png::ColorType::Rgb => 3,
+        png::ColorType::Rgba => 4,
+        _ => return error::UnsupportedMapSnafu { hex }.fail(),
+    };
+
+    let cell_width = f64::from(info.width) / COLUMNS.len() as f64;
+    let cell_height = f64::from(info.height) / f64::from(ROWS);
+    let first_column = u32::from(grid.column).saturating_sub(SNIPPET_RADIUS);
+    let last_column = (u32::from(grid.column) + SNIPPET_RADIUS).min(COLUMNS.len() as u32 - 1);
+    let first_row = u32::from(grid.row).saturating_sub(SNIPPET_RADIUS);
+    let last_row = (u32::from(grid.row) + SNIPPET_RADIUS).min(u32::from(ROWS) - 1);
+    let left = (f64::from(first_column) * cell_width) as u32;
+    let right = ((f64::from(last_column + 1) * cell_width) as u32).min(info.width);
+    let top = (f64::from(first_row) * cell_height) as u32;
+    let bottom = ((f64::from(last_row + 1) * cell_height) as u32).min(info.height);
+    let (width, height) = (right - left, bottom - top);
+
+    let mut pixels = Vec::with_capacity(width as usize * height as usize * 3);
+    for y in top..bottom {
+        let line = &map[y as usize * info.line_size..][..info.line_size];
+        for x in left..right {
+            pixels.extend_from_slice(&line[x as usize * channels..][..3]);
+        }
+    }
+    {
+        let root = BitMapBackend::with_buffer(&mut pixels, (width, height)).into_drawing_area();
+        // Relative to the snippet rather than the whole map
+        let to_pixel = |(x, y): (f64, f64)| {
+            (
+                (x * f64::from(info.width)) as i32 - left as i32,
+                (y * f64::from(info.height)) as i32 - top as i32,
+            )
+        };
+        let line_style = BLACK.mix(0.4);
+        for column in first_column..=last_column {
+            let x = (f64::from(column) * cell_width) as i32 - left as i32;
+            root.draw(&PathElement::new(
+                vec![(x, 0), (x, height as i32)],
+                line_style,
+            ))
+            .context(error::DrawSnafu)?;
+            for row in first_row..=last_row {
+                let y = (f64::from(row) * cell_height) as i32 - top as i32;
+                let name = GridRef {
+                    column: column as u8,
+                    row: row as u8,
+                    keypad: None,
+                };
+                root.draw(&Text::new(
+                    name.to_string(),
+                    (x + 3, y + 3),
+                    (FONT, 12).into_font().color(&WHITE),
+                ))
+                .context(error::DrawSnafu)?;
+            }
+        }
+        for row in first_row..=last_row {
+            let y = (f64::from(row) * cell_height) as i32 - top as i32;
+            root.draw(&PathElement::new(
+                vec![(0, y), (width as i32, y)],
+                line_style,
+            ))
+            .context(error::DrawSnafu)?;
+        }
+        for label in labels {
+            let (x, y) = to_pixel((label.x, label.y));
+            if (0..width as i32).contains(&x) && (0..height as i32).contains(&y) {
+                root.draw(&Text::new(
+                    label.text.clone(),
+                    (x, y),
+                    (FONT, 16).into_font().color(&WHITE),
+                ))
+                .context(error::DrawSnafu)?;
+            }
+        }
+        let marker = to_pixel(grid.position());
+        root.draw(&Circle::new(marker, 9, WHITE.filled()))
+            .context(error::DrawSnafu)?;
+        root.draw(&Circle::new(marker, 6, RED.filled()))
+            .context(error::DrawSnafu)?;
+        root.present().context(error::DrawSnafu)?;
+    }
+
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().context(error::EncodeSnafu)?;
+    writer
+        .write_image_data(&pixels)
+        .context(error::EncodeSnafu)?;
+    writer.finish().context(error::EncodeSnafu)?;
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hex_name, holder, GridRef, MapItem, Team};
+
+    #[test]
+    fn grid_refs_are_parsed_like_players_write_them() {
+        for grid in ["G7", "g7", " G 7 "] {
+            assert_eq!(grid.parse::<GridRef>().unwrap().to_string(), "G7");
+        }
+        assert_eq!("q15K3".parse::<GridRef>().unwrap().to_string(), "Q15k3");
+        for grid in ["", "R1", "A0", "A16", "G7k0", "G7k10", "7G", "G7k"] {
+            assert!(grid.parse::<GridRef>().is_err(), "{grid:?} was accepted");
+        }
+    }
+
+    #[test]
+    fn keypads_are_laid_out_like_a_numpad() {
+        let cell = "A1".parse::<GridRef>().unwrap().position();
+        let top_left = "A1k7".parse::<GridRef>().unwrap().position();
+        let bottom_right = "A1k3".parse::<GridRef>().unwrap().position();
+        assert_eq!("A1k5".parse::<GridRef>().unwrap().position(), cell);
+        assert!(top_left.0 < cell.0 && top_left.1 < cell.1);
+        assert!(bottom_right.0 > cell.0 && bottom_right.1 > cell.1);
+    }
+
+    #[test]
+    fn hexes_are_held_by_whoever_holds_most_town_halls() {
+        let item = |team: &str, icon| MapItem {
+            team: team.to_string(),
+            icon,
+        };
+        let items = [
+            item("WARDENS", 56),
+            item("WARDENS", 58),
+            item("COLONIALS", 57),
+            // Not town halls
+            item("COLONIALS", 33),
+            item("COLONIALS", 33),
+        ];
+        assert_eq!(holder(&items), Some(Team::Wardens));
+        assert_eq!(holder(&items[1..]), None);
+        assert_eq!(
+            holder(&[item("NONE", 56), item("COLONIALS", 56)]),
+            Some(Team::Colonials)
+        );
+        assert_eq!(holder(&[]), None);
+    }
+
+    #[test]
+    fn hexes_are_named_for_people() {
+        assert_eq!(hex_name("DeadLandsHex"), "Dead Lands");
+        assert_eq!(hex_name("TheFingersHex"), "The Fingers");
+        assert_eq!(hex_name("Oarbreaker"), "Oarbreaker");
+    }
+}
@@ -0,0 +1,1175 @@
+//! Web pages for people who can't (or would rather not) use the bot from Discord: claim links and
+//! the officers' dashboard
+//!
+//! `/claim-link` hands out a link that lasts for [`CLAIM_LINK_LIFETIME`], which lets coalition
+//! members claim the tasks of a request without being able to see its channel. The dashboard at
+//! `/dashboard` is for officers, see [`crate::dashboard`]. Visitors log in with their Discord account
+//! (OAuth2) so that their changes are attributed to them just like changes made in Discord, and the
+//! requests' messages are updated to match. Claim links only ask for the `identify` scope, while the
+//! dashboard also asks for `guilds` to find out which servers the visitor is an officer of.
+//!
+//! The map snippets of requests' delivery points are served from here too, since Discord can only
+//! show images that it can fetch, see [`crate::war_map`].
+//!
+//! Pages are rendered from the askama templates in `templates/`.
+
+use std::{collections::HashMap, net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
+
+use askama::Template;
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{AppendHeaders, Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+    Form, Router,
+};
+use entity::{claim_link, request, task, user, web_session, web_session_guild};
+use sea_orm::{
+    prelude::Uuid, ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection, DbErr,
+    EntityTrait, ModelTrait, QueryFilter, QueryOrder, TransactionTrait,
+};
+use serde::Deserialize;
+use serenity::{
+    http::Http,
+    model::{
+        id::{ApplicationId, GuildId, UserId},
+        permissions::Permissions,
+    },
+};
+use snafu::{ensure, OptionExt, Report, ResultExt, Snafu};
+use time::{OffsetDateTime, UtcOffset};
+
+use crate::{
+    chart,
+    dashboard::{self, BulkAction},
+    discord_api::DiscordApi,
+    discord_ids::{FromDiscordId, ToDiscordId},
+    exceeded_claim_capacity, find_guild_ban, get_user_by_discord, may_take_task, set_task_state,
+    stats, time_zone, update_request_messages, user_profile,
+    war_map::{self, GridRef, WarApi},
+    TaskState, DEFAULT_STATS_DAYS, MAX_STATS_DAYS,
+};
+
+/// How long a claim link works for, after which a new one has to be made
+pub const CLAIM_LINK_LIFETIME: Duration = Duration::from_secs(24 * 60 * 60);
+/// How long visitors stay logged in for
+const SESSION_LIFETIME: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+const SESSION_COOKIE: &str = "session";
+/// How long the servers that a visitor is an officer of are trusted for, before they are fetched
+/// from Discord again (such as after they were demoted)
+const GUILDS_LIFETIME: Duration = Duration::from_secs(5 * 60);
+/// How long visitors have to log in with Discord before they have to start over
+const LOGIN_LIFETIME: Duration = Duration::from_secs(10 * 60);
+/// Ties a login to the browser that started it, so that nobody else's login can be finished in it
+const LOGIN_STATE_COOKIE: &str = "login_state";
+
+/// The Discord application that visitors log in with
+pub struct Config {
+    /// Where the pages are reachable from, such as `https://requests.example.com`
+    pub base_url: String,
+    pub client_id: ApplicationId,
+    pub client_secret: String,
+    /// Whether claiming and editing requests is refused, see `--read-only`
+    pub read_only: bool,
+    /// Where the maps of the hexes are, see `--map-dir`
+    pub map_dir: PathBuf,
+}
+
+/// Links to the page of a claim link
+pub fn claim_link_url(base_url: &str, link: Uuid) -> String {
+    format!("{}/claim/{link}", base_url.trim_end_matches('/'))
+}
+
+/// Links to the map snippet of `grid` in `hex`
+pub fn map_url(base_url: &str, hex: &str, grid: &str) -> String {
+    format!("{}/maps/{hex}/{grid}", base_url.trim_end_matches('/'))
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(module)]
+pub enum Error {
+    #[snafu(display("this claim link does not exist"))]
+    LinkNotFound,
+    #[snafu(display("this claim link has expired, ask for a new one"))]
+    LinkExpired,
+    #[snafu(display("the request has been closed"))]
+    RequestClosed,
+    #[snafu(display("the task is not part of the request"))]
+    TaskNotFound,
+    #[snafu(display("someone else has already claimed the task"))]
+    TaskTaken,
+    #[snafu(display("you are banned from using the bot in the request's server"))]
+    Banned,
+    #[snafu(display(
+        "the task is reserved for someone else, or for a role that only Discord can check"
+    ))]
+    Reserved,
+    #[snafu(display("the request is restricted to a role that only Discord can check"))]
+    Restricted,
+    #[snafu(display(
+        "you can hold at most {capacity} claimed task(s) at once in this server, and already hold {held}, complete or unclaim some first"
+    ))]
+    OverCapacity {
+        capacity: i32,
+        held: u64,
+    },
+    #[snafu(display("you are not an officer of this server"))]
+    NotAnOfficer,
+    #[snafu(display("there is no map of this place"))]
+    MapNotFound,
+    #[snafu(display("the bot is in maintenance mode, so nothing can be changed right now"))]
+    ReadOnly,
+    #[snafu(display("this login wasn't started in this browser, or took too long, try again"))]
+    LoginState,
+    #[snafu(display("failed to log in with Discord"))]
+    Login {
+        source: reqwest::Error,
+    },
+    #[snafu(display("failed to draw chart"))]
+    Chart {
+        source: chart::Error,
+    },
+    #[snafu(display("failed to draw map snippet"))]
+    Map {
+        source: war_map::Error,
+    },
+    #[snafu(display("failed to render page"))]
+    Render {
+        source: askama::Error,
+    },
+    Database {
+        source: DbErr,
+    },
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            Error::LinkNotFound | Error::TaskNotFound | Error::MapNotFound => StatusCode::NOT_FOUND,
+            Error::LinkExpired | Error::RequestClosed => StatusCode::GONE,
+            Error::TaskTaken | Error::OverCapacity { .. } => StatusCode::CONFLICT,
+            Error::Banned | Error::NotAnOfficer | Error::Reserved | Error::Restricted => {
+                StatusCode::FORBIDDEN
+            }
+            Error::ReadOnly => StatusCode::SERVICE_UNAVAILABLE,
+            Error::LoginState => StatusCode::BAD_REQUEST,
+            Error::Login { .. } => StatusCode::BAD_GATEWAY,
+            Error::Chart { .. }
+            | Error::Map { .. }
+            | Error::Render { .. }
+            | Error::Database { .. } => {
+                tracing::error!(
+                    error = &self as &dyn std::error::Error,
+                    "failed to serve web page"
+                );
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+        let page = ErrorPage {
+            title: "Something went wrong",
+            message: Report::from_error(self).to_string(),
+        };
+        match page.render() {
+            Ok(html) => (status, Html(html)).into_response(),
+            Err(_) => status.into_response(),
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "error.html")]
+struct ErrorPage {
+    title: &'static str,
+    message: String,
+}
+
+/// How far a task of a claim link's request has come, from the point of view of the visitor
+enum TaskStatus {
+    Available,
+    Claimed,
+    ClaimedByViewer,
+    Done,
+}
+
+struct ClaimTask {
+    id: Uuid,
+    weight: i32,
+    text: String,
+    status: TaskStatus,
+}
+
+#[derive(Template)]
+#[template(path = "claim.html")]
+struct ClaimPage {
+    title: String,
+    link: Uuid,
+    open: bool,
+    tasks: Vec<ClaimTask>,
+}
+
+struct DashboardRequest {
+    id: Uuid,
+    title: String,
+    kind: String,
+    link: Option<String>,
+    contents: String,
+    creator: String,
+    /// [`None`] if the creator uses Discord's default avatar, or their profile is unknown
+    creator_avatar: Option<String>,
+    tasks: usize,
+    completed: usize,
+    created_at: String,
+    updated_at: String,
+    expires_on: String,
+    sticky: bool,
+}
+
+struct DashboardGuild {
+    id: GuildId,
+    name: String,
+    requests: Vec<DashboardRequest>,
+}
+
+#[derive(Template)]
+#[template(path = "dashboard.html")]
+struct DashboardPage {
+    title: &'static str,
+    guilds: Vec<DashboardGuild>,
+    recently_active_first: bool,
+    /// The bulk actions, as (name, label)
+    actions: Vec<(String, String)>,
+}
+
+#[derive(Template)]
+#[template(path = "bulk.html")]
+struct BulkResultPage {
+    title: String,
+    /// What happened to each request, as (title, outcome)
+    results: Vec<(String, String)>,
+}
+
+struct DayRow {
+    date: String,
+    created: usize,
+    completed: usize,
+    average_time_to_completion: String,
+    target_attainment: String,
+}
+
+#[derive(Template)]
+#[template(path = "stats.html")]
+struct StatsPage {
+    title: String,
+    guild: GuildId,
+    days: u32,
+    max_days: u32,
+    created: usize,
+    completed: usize,
+    completion_rate: Option<String>,
+    average_time_to_completion: Option<String>,
+    /// The server's target time to completion and the share of requests that met it, if it has one
+    target_attainment: Option<(String, String)>,
+    /// The share of tasks with deadlines that were done by them, once any are due
+    deadline_attainment: Option<String>,
+    days_stats: Vec<DayRow>,
+}
+
+fn render(page: impl Template) -> Result<Response, Error> {
+    Ok(Html(page.render().context(error::RenderSnafu)?).into_response())
+}
+
+/// Formats a timestamp for the dashboard, to the minute
+fn format_time(time: OffsetDateTime) -> String {
+    let time = time.to_offset(UtcOffset::UTC);
+    format!(
+        "{} {:02}:{:02} UTC",
+        time.date(),
+        time.hour(),
+        time.minute()
+    )
+}
+
+#[derive(Clone)]
+struct AppState {
+    db: DatabaseConnection,
+    /// The bot that each request was made by, whose messages are updated when the request changes
+    apis: Arc<HashMap<ApplicationId, Arc<Http>>>,
+    config: Arc<Config>,
+    client: reqwest::Client,
+    war_api: Arc<WarApi>,
+}
+
+impl AppState {
+    /// The bot that made `request`
+    fn api(&self, request: &request::Model) -> &Http {
+        request
+            .discord_application_id
+            .and_then(|application| self.apis.get(&application.discord()))
+            // Requests made before multi-bot support belong to the first bot
+            .or_else(|| self.apis.get(&self.config.client_id))
+            .expect("no bot for the request's application")
+    }
+}
+
+/// Serves the pages of claim links and the dashboard on `listen`
+pub async fn run(
+    listen: SocketAddr,
+    db: DatabaseConnection,
+    apis: HashMap<ApplicationId, Arc<Http>>,
+    config: Config,
+    war_api: Arc<WarApi>,
+) -> Result<(), axum::Error> {
+    let app = Router::new()
+        .route("/claim/:link", get(show_claim_link))
+        .route("/claim/:link/tasks/:task", post(claim_task))
+        .route("/dashboard", get(show_dashboard))
+        .route("/dashboard/login", get(dashboard_login))
+        .route("/dashboard/requests", post(edit_requests))
+        .route("/dashboard/guilds/:guild/stats", get(show_stats))
+        .route("/dashboard/guilds/:guild/stats.png", get(show_stats_chart))
+        .route("/maps/:hex/:grid", get(show_map_snippet))
+        .route("/oauth/callback", get(finish_login))
+        .with_state(AppState {
+            db,
+            apis: Arc::new(apis),
+            config: Arc::new(config),
+            client: reqwest::Client::new(),
+            war_api,
+        });
+    axum::Server::bind(&listen)
+        .serve(app.into_make_service())
+        .await
+        .map_err(axum::Error::new)
+}
+
+/// Claims `task` for `user` through the claim link `link`
+pub async fn claim(
+    db: &DatabaseConnection,
+    api: &dyn DiscordApi,
+    link: Uuid,
+    task: Uuid,
+    user: &user::Model,
+) -> Result<(), Error> {
+    let request = find_link_request(db, link).await?;
+    ensure!(request.archived_on.is_none(), error::RequestClosedSnafu);
+    ensure!(request.restricted_to_role.is_none(), error::RestrictedSnafu);
+    if let Some(guild) = request.discord_guild_id {
+        let ban = find_guild_ban(db, guild.discord(), user.discord_user_id.discord())
+            .await
+            .context(error::DatabaseSnafu)?;
+        ensure!(ban.is_none(), error::BannedSnafu);
+    }
+    let task = task::Entity::find_by_id(task)
+        .filter(task::Column::Request.eq(request.id))
+        .filter(task::Column::MovedTo.is_null())
+        .filter(task::Column::RemovedAt.is_null())
+        .one(db)
+        .await
+        .context(error::DatabaseSnafu)?
+        .context(error::TaskNotFoundSnafu)?;
+    ensure!(
+        task.started_at.is_none() && task.completed_at.is_none(),
+        error::TaskTakenSnafu
+    );
+    // Claim links don't know the user's roles, so role reservations can only be claimed in Discord
+    ensure!(
+        may_take_task(&task, user.discord_user_id.discord(), &[]),
+        error::ReservedSnafu
+    );
+    if let Some(guild) = request.discord_guild_id {
+        let exceeded =
+            exceeded_claim_capacity(db, guild.discord(), user, std::slice::from_ref(&task))
+                .await
+                .context(error::DatabaseSnafu)?;
+        if let Some((capacity, held)) = exceeded {
+            return error::OverCapacitySnafu { capacity, held }.fail();
+        }
+    }
+    set_task_state(db, [task.id], user, &TaskState::Claimed)
+        .await
+        .context(error::DatabaseSnafu)?;
+    if let Err(err) = update_request_messages(db, api, request.id, None).await {
+        // The claim is saved either way, the messages catch up on the next change
+        tracing::error!(
+            error = &err as &dyn std::error::Error,
+            request.id = %request.id,
+            "failed to update request messages after a claim through a claim link"
+        );
+    }
+    Ok(())
+}
+
+/// Finds the request that a claim link is for, as long as the link still works
+async fn find_link_request(db: &DatabaseConnection, link: Uuid) -> Result<request::Model, Error> {
+    let (link, request) = claim_link::Entity::find_by_id(link)
+        .find_also_related(request::Entity)
+        .one(db)
+        .await
+        .context(error::DatabaseSnafu)?
+        .context(error::LinkNotFoundSnafu)?;
+    ensure!(
+        link.expires_at > OffsetDateTime::now_utc(),
+        error::LinkExpiredSnafu
+    );
+    request.context(error::LinkNotFoundSnafu)
+}
+
+/// The value of the visitor's cookie called `name`, if they have one
+fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|cookies| cookies.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .find_map(|cookie| cookie.trim().strip_prefix(name)?.strip_prefix('='))
+}
+
+/// Sets the cookie `name` for `max_age`, which is only sent over HTTPS if the pages are served over it
+fn set_cookie(config: &Config, name: &str, value: &str, max_age: Duration) -> String {
+    let secure = match config.base_url.starts_with("https://") {
+        true => "; Secure",
+        false => "",
+    };
+    format!(
+        "{name}={value}; Path=/; Max-Age={}; HttpOnly; SameSite=Lax{secure}",
+        max_age.as_secs()
+    )
+}
+
+/// The visitor's session and who they are logged in as, if they are logged in
+async fn find_session(
+    db: &DatabaseConnection,
+    headers: &HeaderMap,
+) -> Result<Option<(web_session::Model, user::Model)>, DbErr> {
+    let session = cookie(headers, SESSION_COOKIE).and_then(|session| Uuid::parse_str(session).ok());
+    let Some(session) = session else {
+        return Ok(None);
+    };
+    Ok(web_session::Entity::find_by_id(session)
+        .filter(web_session::Column::ExpiresAt.gt(OffsetDateTime::now_utc()))
+        .find_also_related(user::Entity)
+        .one(db)
+        .await?
+        .and_then(|(session, user)| Some((session, user?))))
+}
+
+/// The logged in user, if any
+async fn session_user(
+    db: &DatabaseConnection,
+    headers: &HeaderMap,
+) -> Result<Option<user::Model>, DbErr> {
+    Ok(find_session(db, headers).await?.map(|(_, user)| user))
+}
+
+/// The logged in officer and the servers that they are an officer of, if they have logged in for
+/// the dashboard
+///
+/// The servers are fetched from Discord again once they are older than [`GUILDS_LIFETIME`], the
+/// visitor has to log in again if that fails.
+async fn session_officer(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<Option<(user::Model, Vec<web_session_guild::Model>)>, Error> {
+    let Some((session, user)) = find_session(&state.db, headers)
+        .await
+        .context(error::DatabaseSnafu)?
+    else {
+        return Ok(None);
+    };
+    // Sessions from claim links don't know about the visitor's servers
+    let Some(guilds_fetched_at) = session.guilds_fetched_at else {
+        return Ok(None);
+    };
+    if guilds_fetched_at + GUILDS_LIFETIME < OffsetDateTime::now_utc() {
+        let Some(access_token) = &session.access_token else {
+            return Ok(None);
+        };
+        let guilds = match fetch_guilds(&state.client, access_token).await {
+            Ok(guilds) => guilds,
+            Err(err) => {
+                tracing::info!(
+                    error = &err as &dyn std::error::Error,
+                    session.id = %session.id,
+                    "failed to refresh servers of dashboard session, asking to log in again"
+                );
+                return Ok(None);
+            }
+        };
+        save_officer_guilds(&state.db, session.id, guilds)
+            .await
+            .context(error::DatabaseSnafu)?;
+    }
+    let guilds = session
+        .find_related(web_session_guild::Entity)
+        .order_by_asc(web_session_guild::Column::Name)
+        .all(&state.db)
+        .await
+        .context(error::DatabaseSnafu)?;
+    Ok(Some((user, guilds)))
+}
+
+/// Replaces the servers that `session` is an officer of with the ones that `guilds` says it is
+async fn save_officer_guilds(
+    db: &DatabaseConnection,
+    session: Uuid,
+    guilds: Vec<DiscordGuild>,
+) -> Result<(), DbErr> {
+    let txn = db.begin().await?;
+    web_session_guild::Entity::delete_many()
+        .filter(web_session_guild::Column::Session.eq(session))
+        .exec(&txn)
+        .await?;
+    let officer_of = guilds
+        .into_iter()
+        .filter(|guild| guild.is_officer())
+        .map(|guild| web_session_guild::ActiveModel {
+            session: Set(session),
+            discord_guild_id: Set(guild.id.db_id()),
+            name: Set(guild.name),
+        })
+        .collect::<Vec<_>>();
+    if !officer_of.is_empty() {
+        web_session_guild::Entity::insert_many(officer_of)
+            .exec(&txn)
+            .await?;
+    }
+    web_session::ActiveModel {
+        id: Set(session),
+        guilds_fetched_at: Set(Some(OffsetDateTime::now_utc())),
+        ..Default::default()
+    }
+    .update(&txn)
+    .await?;
+    txn.commit().await
+}
+
+fn redirect_uri(config: &Config) -> String {
+    format!("{}/oauth/callback", config.base_url.trim_end_matches('/'))
+}
+
+/// What the visitor is logging in for, which is where they are sent back to afterwards
+enum LoginFor {
+    ClaimLink(Uuid),
+    Dashboard,
+}
+
+/// Sends the visitor to Discord to log in
+///
+/// The OAuth2 state is a nonce that is also kept in a cookie, so that [`finish_login`] only
+/// finishes logins that were started in the same browser. The claim link (if any) rides along
+/// after the nonce.
+fn login(config: &Config, login_for: LoginFor) -> Response {
+    let nonce = Uuid::new_v4();
+    let mut url = reqwest::Url::parse("https://discord.com/oauth2/authorize").unwrap();
+    url.query_pairs_mut()
+        .append_pair("client_id", &config.client_id.to_string())
+        .append_pair("response_type", "code")
+        .append_pair("redirect_uri", &redirect_uri(config));
+    match login_for {
+        LoginFor::ClaimLink(link) => url
+            .query_pairs_mut()
+            .append_pair("scope", "identify")
+            .append_pair("state", &format!("{nonce}.{link}")),
+        LoginFor::Dashboard => url
+            .query_pairs_mut()
+            .append_pair("scope", "identify guilds")
+            .append_pair("state", &nonce.to_string()),
+    };
+    let cookie = set_cookie(
+        config,
+        LOGIN_STATE_COOKIE,
+        &nonce.to_string(),
+        LOGIN_LIFETIME,
+    );
+    ([(header::SET_COOKIE, cookie)], Redirect::to(url.as_str())).into_response()
+}
+
+async fn show_claim_link(
+    State(state): State<AppState>,
+    Path(link): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Response, Error> {
+    let request = find_link_request(&state.db, link).await?;
+    let Some(viewer) = session_user(&state.db, &headers)
+        .await
+        .context(error::DatabaseSnafu)?
+    else {
+        return Ok(login(&state.config, LoginFor::ClaimLink(link)));
+    };
+    let tasks = request
+        .find_related(task::Entity)
+        .filter(task::Column::MovedTo.is_null())
+        .filter(task::Column::RemovedAt.is_null())
+        .order_by_asc(task::Column::Weight)
+        .all(&state.db)
+        .await
+        .context(error::DatabaseSnafu)?;
+    render(claim_page(link, &request, &tasks, &viewer))
+}
+
+/// Lists the tasks of the request, with a button for claiming each task that is still up for grabs
+fn claim_page(
+    link: Uuid,
+    request: &request::Model,
+    tasks: &[task::Model],
+    viewer: &user::Model,
+) -> ClaimPage {
+    ClaimPage {
+        title: request.title.clone(),
+        link,
+        open: request.archived_on.is_none(),
+        tasks: tasks
+            .iter()
+            .map(|task| ClaimTask {
+                id: task.id,
+                weight: task.weight,
+                text: task.task.clone(),
+                status: if task.completed_at.is_some() {
+                    TaskStatus::Done
+                } else if task.started_at.is_some() && task.assigned_to == Some(viewer.id) {
+                    TaskStatus::ClaimedByViewer
+                } else if task.started_at.is_some() {
+                    TaskStatus::Claimed
+                } else {
+                    TaskStatus::Available
+                },
+            })
+            .collect(),
+    }
+}
+
+async fn claim_task(
+    State(state): State<AppState>,
+    Path((link, task)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+) -> Result<Response, Error> {
+    let Some(user) = session_user(&state.db, &headers)
+        .await
+        .context(error::DatabaseSnafu)?
+    else {
+        return Ok(login(&state.config, LoginFor::ClaimLink(link)));
+    };
+    ensure!(!state.config.read_only, error::ReadOnlySnafu);
+    let request = find_link_request(&state.db, link).await?;
+    claim(&state.db, state.api(&request), link, task, &user).await?;
+    Ok(Redirect::to(&format!("/claim/{link}")).into_response())
+}
+
+async fn dashboard_login(State(state): State<AppState>) -> Response {
+    login(&state.config, LoginFor::Dashboard)
+}
+
+#[derive(Deserialize)]
+struct DashboardQuery {
+    #[serde(default)]
+    sort: dashboard::Order,
+}
+
+async fn show_dashboard(
+    State(state): State<AppState>,
+    Query(query): Query<DashboardQuery>,
+    headers: HeaderMap,
+) -> Result<Response, Error> {
+    let Some((_, guilds)) = session_officer(&state, &headers).await? else {
+        return Ok(login(&state.config, LoginFor::Dashboard));
+    };
+    let guild_ids = guilds
+        .iter()
+        .map(|guild| guild.discord_guild_id.discord())
+        .collect::<Vec<_>>();
+    let mut requests = HashMap::<_, Vec<_>>::new();
+    for open in dashboard::open_requests(&state.db, &guild_ids, query.sort)
+        .await
+        .context(error::DatabaseSnafu)?
+    {
+        requests
+            .entry(open.request.discord_guild_id)
+            .or_default()
+            .push(open);
+    }
+    let guilds = guilds
+        .into_iter()
+        .map(|guild| DashboardGuild {
+            id: guild.discord_guild_id.discord(),
+            name: guild.name,
+            requests: requests
+                .remove(&Some(guild.discord_guild_id))
+                .unwrap_or_default()
+                .into_iter()
+                .map(|open| DashboardRequest {
+                    id: open.request.id,
+                    title: open.request.title,
+                    kind: open.request.kind,
+                    link: open.link,
+                    contents: open.contents.unwrap_or_default(),
+                    creator: open
+                        .creator
+                        .as_ref()
+                        .map_or_else(|| "deleted user".to_string(), user_profile::display_name),
+                    creator_avatar: open.creator.and_then(|creator| creator.avatar_url),
+                    tasks: open.tasks,
+                    completed: open.completed,
+                    created_at: format_time(open.request.created_at),
+                    updated_at: format_time(open.request.updated_at),
+                    expires_on: open
+                        .request
+                        .expires_on
+                        .map_or_else(|| "never".to_string(), format_time),
+                    sticky: open.request.sticky,
+                })
+                .collect(),
+        })
+        .collect();
+    render(DashboardPage {
+        title: "Open requests",
+        guilds,
+        recently_active_first: query.sort == dashboard::Order::RecentlyActive,
+        actions: BulkAction::all()
+            .into_iter()
+            .map(|(action, name)| (name, action.label()))
+            .collect(),
+    })
+}
+
+async fn edit_requests(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Form(fields): Form<Vec<(String, String)>>,
+) -> Result<Response, Error> {
+    let Some((officer, guilds)) = session_officer(&state, &headers).await? else {
+        return Ok(login(&state.config, LoginFor::Dashboard));
+    };
+    ensure!(!state.config.read_only, error::ReadOnlySnafu);
+    let action = fields
+        .iter()
+        .find(|(field, _)| field == "action")
+        .and_then(|(_, action)| BulkAction::parse(action));
+    let Some(action) = action else {
+        return Ok((StatusCode::BAD_REQUEST, "unknown action").into_response());
+    };
+    let ids = fields
+        .iter()
+        .filter(|(field, _)| field == "request")
+        .filter_map(|(_, id)| Uuid::parse_str(id).ok())
+        .collect::<Vec<_>>();
+    let guilds = guilds
+        .iter()
+        .map(|guild| guild.discord_guild_id.discord())
+        .collect::<Vec<_>>();
+    let requests = dashboard::find_requests(&state.db, &ids, &guilds)
+        .await
+        .context(error::DatabaseSnafu)?;
+    let mut results = Vec::new();
+    for request in requests {
+        let outcome = match dashboard::apply(
+            &state.db,
+            state.api(&request),
+            &request,
+            &officer,
+            action,
+        )
+        .await
+        {
+            Ok(()) => "done".to_string(),
+            Err(err) => Report::from_error(err).to_string(),
+        };
+        results.push((request.title, outcome));
+    }
+    render(BulkResultPage {
+        title: action.label(),
+        results,
+    })
+}
+
+#[derive(Deserialize)]
+struct StatsQuery {
+    days: Option<u32>,
+}
+
+/// The statistics of `guild` over the last `days` days, as long as the visitor is one of its officers
+async fn officer_stats(
+    db: &DatabaseConnection,
+    guilds: &[web_session_guild::Model],
+    guild: GuildId,
+    days: u32,
+) -> Result<stats::GuildStats, Error> {
+    ensure!(
+        guilds
+            .iter()
+            .any(|officer_of| officer_of.discord_guild_id == guild.db_id()),
+        error::NotAnOfficerSnafu
+    );
+    let tz = time_zone::guild_time_zone(db, Some(guild))
+        .await
+        .context(error::DatabaseSnafu)?
+        .unwrap_or_else(time_zone::default);
+    stats::guild_stats(db, guild, days, tz, OffsetDateTime::now_utc())
+        .await
+        .context(error::DatabaseSnafu)
+}
+
+async fn show_stats(
+    State(state): State<AppState>,
+    Path(guild): Path<GuildId>,
+    Query(query): Query<StatsQuery>,
+    headers: HeaderMap,
+) -> Result<Response, Error> {
+    let Some((_, guilds)) = session_officer(&state, &headers).await? else {
+        return Ok(login(&state.config, LoginFor::Dashboard));
+    };
+    let days = query
+        .days
+        .unwrap_or(DEFAULT_STATS_DAYS)
+        .clamp(1, MAX_STATS_DAYS);
+    let stats = officer_stats(&state.db, &guilds, guild, days).await?;
+    let name = guilds
+        .iter()
+        .find(|officer_of| officer_of.discord_guild_id == guild.db_id())
+        .map_or("", |officer_of| &officer_of.name);
+    render(StatsPage {
+        title: format!("Requests made in {name} over the last {days} day(s)"),
+        guild,
+        days,
+        max_days: MAX_STATS_DAYS,
+        created: stats.created,
+        completed: stats.completed,
+        completion_rate: stats
+            .completion_rate()
+            .map(|rate| format!("{:.0}%", rate * 100.0)),
+        average_time_to_completion: stats
+            .average_time_to_completion
+            .map(stats::format_time_to_completion),
+        target_attainment: stats.target_completion.map(|target| {
+            (
+                stats::format_time_to_completion(target),
+                stats
+                    .target_attainment()
+                    .map_or_else(String::new, |rate| format!("{:.0}%", rate * 100.0)),
+            )
+        }),
+        deadline_attainment: stats
+            .deadline_attainment()
+            .map(|rate| format!("{:.0}%", rate * 100.0)),
+        days_stats: stats
+            .days
+            .iter()
+            .map(|day| DayRow {
+                date: day.date.to_string(),
+                created: day.created,
+                completed: day.completed,
+                average_time_to_completion: day
+                    .average_time_to_completion
+                    .map_or_else(String::new, stats::format_time_to_completion),
+                target_attainment: day
+                    .target_attainment()
+                    .map_or_else(String::new, |rate| format!("{:.0}%", rate * 100.0)),
+            })
+            .collect(),
+    })
+}
+
+async fn show_stats_chart(
+    State(state): State<AppState>,
+    Path(guild): Path<GuildId>,
+    Query(query): Query<StatsQuery>,
+    headers: HeaderMap,
+) -> Result<Response, Error> {
+    let Some((_, guilds)) = session_officer(&state, &headers).await? else {
+        return Err(Error::NotAnOfficer);
+    };
+    let days = query
+        .days
+        .unwrap_or(DEFAULT_STATS_DAYS)
+        .clamp(1, MAX_STATS_DAYS);
+    let stats = officer_stats(&state.db, &guilds, guild, days).await?;
+    let png = tokio::task::spawn_blocking(move || chart::render(&stats))
+        .await
+        .unwrap()
+        .context(error::ChartSnafu)?;
+    Ok(([(header::CONTENT_TYPE, "image/png")], png).into_response())
+}
+
+/// Draws the map snippet of a request's delivery point, which anyone may see since it's only the map
+async fn show_map_snippet(
+    State(state): State<AppState>,
+    Path((hex, grid)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    // The hex is a file name, so it mustn't lead out of the map directory
+    ensure!(
+        hex.chars().all(|c| c.is_ascii_alphanumeric()),
+        error::MapNotFoundSnafu
+    );
+    let grid = grid
+        .parse::<GridRef>()
+        .ok()
+        .context(error::MapNotFoundSnafu)?;
+    let map = match war_map::read_map(&state.config.map_dir, &hex) {
+        Ok(map) => map,
+        Err(err) => {
+            tracing::warn!(
+                error = &err as &dyn std::error::Error,
+                hex,
+                "failed to read map of hex"
+            );
+            return Err(Error::MapNotFound);
+        }
+    };
+    // The snippet is still useful without the town names
+    let labels = state.war_api.labels(&hex).await.unwrap_or_else(|err| {
+        tracing::warn!(
+            error = &err as &dyn std::error::Error,
+            hex,
+            "failed to fetch towns of hex, drawing map snippet without them"
+        );
+        Vec::new()
+    });
+    let png =
+        tokio::task::spawn_blocking(move || war_map::render_snippet(&hex, &map, grid, &labels))
+            .await
+            .unwrap()
+            .context(error::MapSnafu)?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "image/png"),
+            // The map only changes between wars
+            (header::CACHE_CONTROL, "public, max-age=86400"),
+        ],
+        png,
+    )
+        .into_response())
+}
+
+#[derive(Deserialize)]
+struct LoginCallback {
+    code: String,
+    /// The nonce of [`login`], followed by the claim link that the visitor was logging in for (if
+    /// any, they were logging in for the dashboard if there is none)
+    state: String,
+}
+
+impl LoginCallback {
+    /// The claim link that the visitor was logging in for, as long as the login was started by
+    /// this browser (`headers`)
+    fn verify(&self, headers: &HeaderMap) -> Result<LoginFor, Error> {
+        let (nonce, link) = match self.state.split_once('.') {
+            Some((nonce, link)) => (nonce, Some(link)),
+            None => (self.state.as_str(), None),
+        };
+        let expected = cookie(headers, LOGIN_STATE_COOKIE).context(error::LoginStateSnafu)?;
+        ensure!(
+            Uuid::parse_str(nonce).is_ok() && nonce == expected,
+            error::LoginStateSnafu
+        );
+        match link {
+            Some(link) => Ok(LoginFor::ClaimLink(
+                Uuid::parse_str(link).ok().context(error::LoginStateSnafu)?,
+            )),
+            None => Ok(LoginFor::Dashboard),
+        }
+    }
+}
+
+async fn finish_login(
+    State(state): State<AppState>,
+    Query(callback): Query<LoginCallback>,
+    headers: HeaderMap,
+) -> Result<Response, Error> {
+    let login_for = callback.verify(&headers)?;
+    let login = discord_login(&state.client, &state.config, &callback.code)
+        .await
+        .context(error::LoginSnafu)?;
+    let user = get_user_by_discord(&state.db, login.user)
+        .await
+        .context(error::DatabaseSnafu)?;
+    let session = web_session::ActiveModel {
+        user: Set(user.id),
+        expires_at: Set(OffsetDateTime::now_utc() + SESSION_LIFETIME),
+        // Only dashboard sessions need the token, to check the visitor's servers again later
+        access_token: Set(login.guilds.is_some().then_some(login.access_token)),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await
+    .context(error::DatabaseSnafu)?;
+    if let Some(guilds) = login.guilds {
+        save_officer_guilds(&state.db, session.id, guilds)
+            .await
+            .context(error::DatabaseSnafu)?;
+    }
+    let cookies = [
+        set_cookie(
+            &state.config,
+            SESSION_COOKIE,
+            &session.id.to_string(),
+            SESSION_LIFETIME,
+        ),
+        // The nonce has been used up
+        set_cookie(&state.config, LOGIN_STATE_COOKIE, "", Duration::ZERO),
+    ];
+    let back_to = match login_for {
+        LoginFor::ClaimLink(link) => format!("/claim/{link}"),
+        LoginFor::Dashboard => "/dashboard".to_string(),
+    };
+    Ok((
+        AppendHeaders(cookies.map(|cookie| (header::SET_COOKIE, cookie))),
+        Redirect::to(&back_to),
+    )
+        .into_response())
+}
+
+/// Who logged in, and which servers they are in if they were asked for
+struct DiscordLogin {
+    user: UserId,
+    access_token: String,
+    guilds: Option<Vec<DiscordGuild>>,
+}
+
+#[derive(Deserialize)]
+struct DiscordGuild {
+    id: GuildId,
+    name: String,
+    owner: bool,
+    /// The visitor's permissions in the server, as a stringified bitset
+    permissions: String,
+}
+
+impl DiscordGuild {
+    fn is_officer(&self) -> bool {
+        let permissions = self.permissions.parse().unwrap_or_default();
+        dashboard::is_officer(self.owner, Permissions::from_bits_truncate(permissions))
+    }
+}
+
+/// Looks up who logged in, from the code that Discord sent them back with
+async fn discord_login(
+    client: &reqwest::Client,
+    config: &Config,
+    code: &str,
+) -> reqwest::Result<DiscordLogin> {
+    #[derive(Deserialize)]
+    struct Token {
+        access_token: String,
+        /// The scopes that the visitor granted, separated by spaces
+        scope: String,
+    }
+    #[derive(Deserialize)]
+    struct DiscordUser {
+        id: UserId,
+    }
+    let token: Token = client
+        .post("https://discord.com/api/v10/oauth2/token")
+        .basic_auth(config.client_id, Some(&config.client_secret))
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &redirect_uri(config)),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let user: DiscordUser = client
+        .get("https://discord.com/api/v10/users/@me")
+        .bearer_auth(&token.access_token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let guilds = if token.scope.split(' ').any(|scope| scope == "guilds") {
+        Some(fetch_guilds(client, &token.access_token).await?)
+    } else {
+        None
+    };
+    Ok(DiscordLogin {
+        user: user.id,
+        access_token: token.access_token,
+        guilds,
+    })
+}
+
+/// Lists the servers that the visitor is in, along with their permissions in them
+async fn fetch_guilds(
+    client: &reqwest::Client,
+    access_token: &str,
+) -> reqwest::Result<Vec<DiscordGuild>> {
+    client
+        .get("https://discord.com/api/v10/users/@me/guilds")
+        .bearer_auth(access_token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use askama::Template;
+    use axum::http::{header, HeaderMap};
+    use entity::task;
+    use sea_orm::prelude::Uuid;
+    use time::OffsetDateTime;
+
+    use super::{claim_page, LoginCallback, LoginFor};
+    use crate::testing;
+
+    #[test]
+    fn claim_page_escapes_tasks_and_offers_open_ones() {
+        let viewer = testing::user(1);
+        let request = testing::request(2);
+        let task = |id: u128, text: &str, assigned_to: Option<Uuid>| task::Model {
+            weight: id as i32,
+            task: text.to_string(),
+            assigned_to,
+            started_at: assigned_to.map(|_| OffsetDateTime::UNIX_EPOCH),
+            ..testing::task(&request, id)
+        };
+        let tasks = [
+            task(1, "<b>\"fuel\" & 'diesel'</b>", None),
+            task(2, "shirts", Some(viewer.id)),
+        ];
+        let html = claim_page(Uuid::from_u128(3), &request, &tasks, &viewer)
+            .render()
+            .unwrap();
+        assert!(html.contains("&lt;b&gt;&quot;fuel&quot; &amp; &#x27;diesel&#x27;&lt;/b&gt;"));
+        assert!(html.contains(&format!(
+            "action=\"/claim/{}/tasks/{}\"",
+            Uuid::from_u128(3),
+            Uuid::from_u128(1)
+        )));
+        assert!(html.contains("shirts (claimed by you)"));
+        assert!(!html.contains(&format!("/tasks/{}", Uuid::from_u128(2))));
+    }
+
+    #[test]
+    fn logins_are_only_finished_in_the_browser_that_started_them() {
+        let nonce = Uuid::from_u128(1);
+        let link = Uuid::from_u128(2);
+        let callback = |state: String| LoginCallback {
+            code: "code".to_string(),
+            state,
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::COOKIE,
+            format!("session=x; login_state={nonce}").parse().unwrap(),
+        );
+
+        assert!(matches!(
+            callback(format!("{nonce}.{link}")).verify(&headers),
+            Ok(LoginFor::ClaimLink(claimed)) if claimed == link
+        ));
+        assert!(matches!(
+            callback(nonce.to_string()).verify(&headers),
+            Ok(LoginFor::Dashboard)
+        ));
+        assert!(callback(Uuid::from_u128(3).to_string())
+            .verify(&headers)
+            .is_err());
+        assert!(callback(nonce.to_string())
+            .verify(&HeaderMap::new())
+            .is_err());
+    }
+}